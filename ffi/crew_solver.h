@@ -0,0 +1,89 @@
+/* C API of the-crew-solver. Generated by cbindgen from src/ffi.rs: do not edit. */
+
+#ifndef CREW_SOLVER_H
+#define CREW_SOLVER_H
+
+
+
+#include <stddef.h>
+
+// Result of a call, `CREW_STATUS_OK` (0) on success.
+typedef enum {
+  CREW_STATUS_OK = 0,
+  // A pointer given was null.
+  CREW_STATUS_NULL_POINTER = 1,
+  // A string given is not valid UTF-8.
+  CREW_STATUS_INVALID_UTF8 = 2,
+  // The description of the game cannot be read.
+  CREW_STATUS_PARSE = 3,
+  // The library was built without the feature needed by the call.
+  CREW_STATUS_UNSUPPORTED = 4,
+  // The game described cannot happen: wrong number of players, duplicate cards, no captain...
+  CREW_STATUS_INVALID_GAME = 5,
+  // A card played in the history of the game breaks the rules.
+  CREW_STATUS_ILLEGAL_PLAY = 6,
+  // A communication or a distress signal of the game breaks the rules.
+  CREW_STATUS_ILLEGAL_ACTION = 7,
+  // The mission cannot be completed.
+  CREW_STATUS_NO_SOLUTION = 8,
+  // The solver gave up before finding a solution or proving there is none.
+  CREW_STATUS_BUDGET_EXCEEDED = 9,
+  CREW_STATUS_PANIC = 10,
+} CrewStatus;
+
+// A game, created by [`crew_game_from_text`] or [`crew_game_from_json`].
+typedef struct CrewGame CrewGame;
+
+// Creates a game from its text description (see the `game_file` module of the crate).
+//
+// # Safety
+//
+// `text` must be null or point to a NUL-terminated string, and `game` must be null or valid
+// for writes. On success, `*game` must be freed with [`crew_game_free`].
+CrewStatus crew_game_from_text(const char *text, CrewGame **game);
+
+// Creates a game from the JSON description of the API: the hands dealt, the tasks by catalog
+// ID and the cards played. Returns `CREW_STATUS_UNSUPPORTED` without the `serde` feature.
+//
+// # Safety
+//
+// Same as [`crew_game_from_text`].
+CrewStatus crew_game_from_json(const char *json, CrewGame **game);
+
+// Looks for a way to complete the mission, exploring at most `budget` states (or without
+// limit if `budget` is 0). On success, the solution is given by [`crew_game_solution_line`]
+// and [`crew_game_solution_text`]; the game itself is left as it was.
+//
+// # Safety
+//
+// `game` must be null or a handle which was not freed.
+CrewStatus crew_game_solve(CrewGame *game, size_t budget);
+
+// The cards to play to complete the mission, in order and separated by spaces (as in
+// `P1 S4 B2`), or null if the game is not solved.
+//
+// # Safety
+//
+// `game` must be null or a handle which was not freed.
+const char *crew_game_solution_line(const CrewGame *game);
+
+// The text description of the game once the mission is completed, or null if the game is
+// not solved.
+//
+// # Safety
+//
+// `game` must be null or a handle which was not freed.
+const char *crew_game_solution_text(const CrewGame *game);
+
+// Frees a game. Does nothing if `game` is null.
+//
+// # Safety
+//
+// `game` must be null or a handle which was not freed.
+void crew_game_free(CrewGame *game);
+
+// The message of the last error of the calling thread, or null if its last call succeeded.
+// The string remains valid until the next call of this thread.
+const char *crew_last_error(void);
+
+#endif  /* CREW_SOLVER_H */
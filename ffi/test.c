@@ -0,0 +1,90 @@
+/* Test program of the C API: solves a small game, then checks that errors are reported.
+ * Build the library with `cargo build --features serde` first, then run `make -C ffi`. */
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "crew_solver.h"
+
+/* A 2-trick game where the captain (seat 1) must win the pink 1. */
+static const char *GAME = "seat 0\nhand: P1 B2\n"
+                          "seat 1\nhand: S4 P3\ntask: win_pink_1\n"
+                          "seat 2\nhand: P5 B1\n";
+
+static void solve(void) {
+    CrewGame *game = NULL;
+    assert(crew_game_from_text(GAME, &game) == CREW_STATUS_OK);
+    assert(crew_game_solution_line(game) == NULL);
+    assert(crew_game_solve(game, 1000) == CREW_STATUS_OK);
+    const char *line = crew_game_solution_line(game);
+    assert(line != NULL && strncmp(line, "S4", 2) == 0);
+    assert(strstr(crew_game_solution_text(game), "won: #0 @1: S4") != NULL);
+    printf("solution: %s\n", line);
+    crew_game_free(game);
+}
+
+static void errors(void) {
+    CrewGame *game = NULL;
+    assert(crew_game_from_text("seat 0\nhand: P12\n", &game) == CREW_STATUS_PARSE);
+    assert(game == NULL);
+    printf("parse error: %s\n", crew_last_error());
+    assert(crew_game_from_text(NULL, &game) == CREW_STATUS_NULL_POINTER);
+    assert(crew_game_solve(NULL, 0) == CREW_STATUS_NULL_POINTER);
+
+    /* Two seats want the pink 1 */
+    const char *both = "seat 0\nhand: P1 B2\n"
+                       "seat 1\nhand: S4 P3\ntask: win_pink_1\n"
+                       "seat 2\nhand: P5 B1\ntask: win_pink_1\n";
+    assert(crew_game_from_text(both, &game) == CREW_STATUS_OK);
+    assert(crew_last_error() == NULL);
+    assert(crew_game_solve(game, 0) == CREW_STATUS_NO_SOLUTION);
+    assert(crew_game_solution_line(game) == NULL);
+    printf("no solution: %s\n", crew_last_error());
+    crew_game_free(game);
+    crew_game_free(NULL);
+
+    /* Seat 0 holds a higher pink card than the one communicated */
+    const char *lie = "seat 0\nhand: P1 P2\n"
+                      "seat 1\nhand: S4 P3\n"
+                      "seat 2\nhand: P5 B1\n"
+                      "communication: 0 P1 highest\n";
+    game = NULL;
+    assert(crew_game_from_text(lie, &game) == CREW_STATUS_ILLEGAL_ACTION);
+    assert(game == NULL);
+    printf("illegal action: %s\n", crew_last_error());
+}
+
+static void json(void) {
+    CrewGame *game = NULL;
+    const char *description = "{\"hands\": [[\"P1\", \"B2\"], [\"S4\", \"P3\"], [\"P5\", \"B1\"]],"
+                              " \"tasks\": [[], [{\"id\": \"win_pink_1\"}], []],"
+                              " \"history\": [\"P3\"]}";
+    CrewStatus status = crew_game_from_json(description, &game);
+    if (status == CREW_STATUS_UNSUPPORTED) {
+        printf("json: %s\n", crew_last_error());
+        return;
+    }
+    assert(status == CREW_STATUS_OK);
+    /* Seat 2 must follow with the pink 5 and win the pink 1 */
+    assert(crew_game_solve(game, 0) == CREW_STATUS_NO_SOLUTION);
+    crew_game_free(game);
+
+    assert(crew_game_from_json("{\"hands\": []}", &game) == CREW_STATUS_INVALID_GAME);
+    printf("invalid game: %s\n", crew_last_error());
+
+    /* Seat 2 holds the pink 5 and must follow the pink 3 */
+    const char *illegal = "{\"hands\": [[\"P1\", \"B2\"], [\"S4\", \"P3\"], [\"P5\", \"B1\"]],"
+                          " \"history\": [\"P3\", \"B1\"]}";
+    game = NULL;
+    assert(crew_game_from_json(illegal, &game) == CREW_STATUS_ILLEGAL_PLAY);
+    assert(game == NULL);
+    printf("illegal play: %s\n", crew_last_error());
+}
+
+int main(void) {
+    solve();
+    errors();
+    json();
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,82 @@
+//! Next-card advice during a live game.
+//!
+//! The perfect-information solver cannot help a real player, who does not know the other hands.
+//! Advice is instead computed from the [`PlayerView`] of the seat to play: hidden hands are
+//! sampled, and each legal card is ranked by the fraction of samples in which the mission can
+//! still be completed after playing it.
+use crate::{card::Card, state::GameError, strategy::sampling::SamplingStrategy, view::PlayerView};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Advice {
+    pub card: Card,
+    /// Estimated probability of completing the mission after playing this card.
+    pub probability: f64,
+}
+
+impl PlayerView {
+    /// Ranks the legal cards of this seat, the best one first. Each card is evaluated on
+    /// `samples` sampled deals, the solver exploring at most `budget` states per deal.
+    ///
+    /// Fails if it is not the turn of this seat.
+    pub fn advise(
+        &self,
+        samples: usize,
+        budget: usize,
+        seed: u64,
+    ) -> Result<Vec<Advice>, GameError> {
+        if self.seat() != self.get_current_player_idx() {
+            return Err(GameError::NotYourTurn(self.seat()));
+        }
+        let mut strategy = SamplingStrategy::new(seed, samples, budget);
+        let mut advice: Vec<Advice> = strategy
+            .evaluate(self, &self.legal_cards())
+            .into_iter()
+            .map(|(card, probability)| Advice { card, probability })
+            .collect();
+        advice.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        Ok(advice)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{player::Player, state::State, task::win_cards::TaskWinCards};
+
+    use super::*;
+
+    #[test]
+    fn ranks_winning_card_first() {
+        let p0 = Player::new([Card::Submarine(4), Card::Pink(1), Card::Blue(3)].into());
+        let p1 = Player::new([Card::Pink(9), Card::Blue(2), Card::Yellow(2)].into());
+        let mut p2 = Player::new([Card::Blue(1), Card::Yellow(1), Card::Submarine(1)].into());
+        p2.add_task(TaskWinCards::new(None, [Card::Pink(9)]))
+            .unwrap();
+        let mut state = State::new([p0, p1, p2]);
+        assert!(state.validate().is_ok());
+        state.play_card(&Card::Pink(1)).unwrap();
+        state.play_card(&Card::Pink(9)).unwrap();
+
+        let advice = state.view(2).advise(4, 1000, 0).unwrap();
+        assert_eq!(advice.len(), 3);
+        assert_eq!(
+            advice[0],
+            Advice {
+                card: Card::Submarine(1),
+                probability: 1.
+            }
+        );
+        assert!(advice[1..].iter().all(|a| a.probability == 0.));
+    }
+
+    #[test]
+    fn not_your_turn() {
+        let state = State::new_random_with_rng(4, &mut StdRng::seed_from_u64(0));
+        let seat = (state.get_current_player_idx() + 1) % 4;
+        assert!(matches!(
+            state.view(seat).advise(1, 1, 0),
+            Err(GameError::NotYourTurn(s)) if s == seat
+        ));
+    }
+}
@@ -0,0 +1,630 @@
+//! JSON facade of the solver for front ends, behind the `serde` feature.
+//!
+//! A front end (such as the web front end planned in CONTRIBUTING.md, through WebAssembly)
+//! sends a [`Request`] as JSON to [`handle`] and gets a [`Response`] back as JSON. A game is
+//! described by the hands dealt, the tasks of each seat by catalog ID and the cards played so
+//! far, and is rebuilt with the rule checks of [`State::replay`].
+//!
+//! Whatever the input, [`handle`] does not panic: malformed requests and illegal games are
+//! reported in an error response. Requests and responses carry [`API_VERSION`], to be increased
+//! whenever their schema changes. Every search of the solver explores at most `budget` states,
+//! so that a request cannot hang the front end.
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    card::Card,
+    catalog,
+    hand::Hand,
+    player,
+    state::{GameError, State},
+    task::{BaseTask, Task, TaskStatus},
+};
+
+/// Version of the schema of requests and responses.
+pub const API_VERSION: u32 = 1;
+
+/// Number of states a search explores when the request does not give a budget.
+pub const DEFAULT_BUDGET: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    /// Finds the cards to play to complete the mission.
+    Solve,
+    /// Tells which cards of the seat to play keep the mission feasible.
+    Analyze,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Request {
+    pub version: u32,
+    pub command: Command,
+    pub game: Game,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<usize>,
+}
+
+/// A game from its start: the hands dealt, the tasks of every seat and the cards played since,
+/// in order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Game {
+    pub hands: Vec<Vec<Card>>,
+    /// Tasks of every seat. May be left out for a game without tasks.
+    #[serde(default)]
+    pub tasks: Vec<Vec<TaskRef>>,
+    #[serde(default)]
+    pub history: Vec<Card>,
+}
+
+/// A task of the catalog, see [`catalog::get`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskRef {
+    pub id: String,
+    /// The number of tricks predicted, for the "Win X tricks" tasks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prediction: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The request is not valid JSON or does not follow the schema.
+    InvalidRequest,
+    UnsupportedVersion,
+    /// The game cannot be rebuilt: see the message.
+    InvalidGame,
+}
+
+#[derive(Debug, Clone, PartialEq, Error, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct ApiError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// The rule the game breaks, if that is the error. Not sent to front ends.
+    #[serde(skip)]
+    #[source]
+    pub game_error: Option<GameError>,
+}
+
+impl ApiError {
+    fn invalid_game(message: impl ToString) -> Self {
+        ApiError {
+            kind: ErrorKind::InvalidGame,
+            message: message.to_string(),
+            game_error: None,
+        }
+    }
+}
+
+impl From<GameError> for ApiError {
+    fn from(e: GameError) -> Self {
+        ApiError {
+            game_error: Some(e.clone()),
+            ..ApiError::invalid_game(e)
+        }
+    }
+}
+
+/// Whether the solver found a mission feasible, within its budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feasibility {
+    Feasible,
+    Infeasible,
+    /// The budget was exceeded before the solver could tell.
+    Unknown,
+}
+
+/// Where a task of the game stands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskReport {
+    pub seat: usize,
+    pub id: String,
+    pub status: TaskStatus,
+    /// For an infeasible mission: whether the mission would be feasible with this task only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feasible_alone: Option<Feasibility>,
+}
+
+/// A legal card of the seat to play.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    pub card: Card,
+    /// Whether the mission can still be completed after playing the card, or null if the
+    /// budget was exceeded.
+    pub winning: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Outcome {
+    /// The cards to play from the position given to complete the mission.
+    Solved {
+        line: Vec<Card>,
+    },
+    /// The mission cannot be completed, with the status of every task.
+    Infeasible {
+        tasks: Vec<TaskReport>,
+    },
+    BudgetExceeded {
+        budget: usize,
+    },
+    Analysis {
+        mission: TaskStatus,
+        to_play: usize,
+        tasks: Vec<TaskReport>,
+        /// Empty once every trick is won.
+        moves: Vec<Move>,
+    },
+    Error(ApiError),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub version: u32,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+impl Game {
+    /// Rebuilds the game, checking every card played against the rules.
+    pub fn to_state(&self) -> Result<State, ApiError> {
+        self.build(|_, _| true)
+    }
+
+    /// Same as [`Game::to_state`], with only the tasks for which `keep(seat, i)` is true.
+    fn build<F>(&self, keep: F) -> Result<State, ApiError>
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        let n_players = self.hands.len();
+        if player::check_valid_n_players(n_players).is_err() {
+            return Err(GameError::InvalidNPlayers(n_players).into());
+        }
+        // Checked here as building a hand panics on invalid or duplicate cards
+        let mut seen = HashSet::new();
+        for card in self.hands.iter().flatten() {
+            if !card.is_valid() {
+                return Err(ApiError::invalid_game(format!(
+                    "{} is not a card of the game.",
+                    card
+                )));
+            }
+            if !seen.insert(card) {
+                return Err(GameError::DuplicateCard(*card).into());
+            }
+        }
+
+        let mut tasks = vec![vec![]; n_players];
+        if !self.tasks.is_empty() {
+            tasks.clear();
+            for (seat, refs) in self.tasks.iter().enumerate() {
+                let mut seat_tasks = vec![];
+                for (i, r) in refs.iter().enumerate() {
+                    let task = r.build(n_players)?;
+                    if keep(seat, i) {
+                        seat_tasks.push(task);
+                    }
+                }
+                tasks.push(seat_tasks);
+            }
+        }
+        let hands = self.hands.iter().map(|h| Hand::from(h.clone())).collect();
+        Ok(State::replay(hands, tasks, &self.history)?)
+    }
+
+    fn task_reports(&self, state: &State) -> Vec<TaskReport> {
+        let mut reports = vec![];
+        for (seat, refs) in self.tasks.iter().enumerate() {
+            let tasks = state.get_player(seat).get_tasks();
+            for (r, task) in refs.iter().zip(tasks) {
+                reports.push(TaskReport {
+                    seat,
+                    id: r.id.clone(),
+                    status: task.eval(state, seat),
+                    feasible_alone: None,
+                });
+            }
+        }
+        reports
+    }
+}
+
+impl TaskRef {
+    fn build(&self, n_players: usize) -> Result<Task, ApiError> {
+        let Some(entry) = catalog::get(&self.id) else {
+            return Err(ApiError::invalid_game(format!(
+                "Unknown task ID \"{}\".",
+                self.id
+            )));
+        };
+        let mut task = entry.build(n_players);
+        if let Some(x) = self.prediction {
+            match &mut task {
+                Task::WinNbTricks(t) if t.prediction().is_none() => {
+                    if x > player::n_tricks_total(n_players) {
+                        return Err(ApiError::invalid_game(format!(
+                            "Prediction {} of task \"{}\" exceeds the number of tricks.",
+                            x, self.id
+                        )));
+                    }
+                    t.predict(x);
+                }
+                _ => {
+                    return Err(ApiError::invalid_game(format!(
+                        "Task \"{}\" takes no prediction.",
+                        self.id
+                    )));
+                }
+            }
+        }
+        Ok(task)
+    }
+}
+
+/// Runs the solver, telling apart an infeasible mission (`Ok(None)`) from the other errors.
+fn search(state: &State, budget: usize) -> Result<Option<State>, GameError> {
+    match state.clone().play_with_budget(budget) {
+        Ok(solution) => Ok(Some(solution)),
+        Err(GameError::NoSolutionFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn feasibility(state: &State, budget: usize) -> Result<Feasibility, ApiError> {
+    match search(state, budget) {
+        Ok(Some(_)) => Ok(Feasibility::Feasible),
+        Ok(None) => Ok(Feasibility::Infeasible),
+        Err(GameError::BudgetExceeded(_)) => Ok(Feasibility::Unknown),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn solve(game: &Game, budget: usize) -> Result<Outcome, ApiError> {
+    let state = game.to_state()?;
+    match search(&state, budget) {
+        Ok(Some(solution)) => {
+            let (_, plays) = solution.history()?;
+            let line = plays[game.history.len()..].to_vec();
+            return Ok(Outcome::Solved { line });
+        }
+        Ok(None) => (),
+        Err(GameError::BudgetExceeded(_)) => return Ok(Outcome::BudgetExceeded { budget }),
+        Err(e) => return Err(e.into()),
+    }
+
+    // Finding which tasks cannot be done, even without the others
+    let mut tasks = game.task_reports(&state);
+    let mut i_report = 0;
+    for (seat, refs) in game.tasks.iter().enumerate() {
+        for i in 0..refs.len() {
+            let alone = game.build(|s, j| (s, j) == (seat, i))?;
+            tasks[i_report].feasible_alone = Some(feasibility(&alone, budget)?);
+            i_report += 1;
+        }
+    }
+    Ok(Outcome::Infeasible { tasks })
+}
+
+fn analyze(game: &Game, budget: usize) -> Result<Outcome, ApiError> {
+    let state = game.to_state()?;
+    let mut moves = vec![];
+    if state.n_tricks_left() > 0 {
+        let hand = state.get_current_player().get_hand();
+        for card in hand.playable_cards(state.get_current_trick().first()) {
+            let mut next = state.clone();
+            next.play_card(&card)?;
+            let winning = match feasibility(&next, budget)? {
+                Feasibility::Feasible => Some(true),
+                Feasibility::Infeasible => Some(false),
+                Feasibility::Unknown => None,
+            };
+            moves.push(Move { card, winning });
+        }
+    }
+    Ok(Outcome::Analysis {
+        mission: state.game_status(),
+        to_play: state.get_current_player_idx(),
+        tasks: game.task_reports(&state),
+        moves,
+    })
+}
+
+/// Answers a request.
+pub fn handle_request(request: &Request) -> Response {
+    let outcome = if request.version != API_VERSION {
+        Outcome::Error(ApiError {
+            kind: ErrorKind::UnsupportedVersion,
+            message: format!(
+                "Unsupported API version {} (expected {}).",
+                request.version, API_VERSION
+            ),
+            game_error: None,
+        })
+    } else {
+        let budget = request.budget.unwrap_or(DEFAULT_BUDGET);
+        let outcome = match request.command {
+            Command::Solve => solve(&request.game, budget),
+            Command::Analyze => analyze(&request.game, budget),
+        };
+        outcome.unwrap_or_else(Outcome::Error)
+    };
+    Response {
+        version: API_VERSION,
+        outcome,
+    }
+}
+
+/// Answers a request given in JSON, in JSON.
+pub fn handle(request: &str) -> String {
+    let response = match serde_json::from_str::<Request>(request) {
+        Ok(request) => handle_request(&request),
+        Err(e) => Response {
+            version: API_VERSION,
+            outcome: Outcome::Error(ApiError {
+                kind: ErrorKind::InvalidRequest,
+                message: e.to_string(),
+                game_error: None,
+            }),
+        },
+    };
+    serde_json::to_string(&response).expect("responses are always serializable")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{blue, pink, submarine};
+    use serde_json::{Value, json};
+
+    /// A 2-trick game where the captain (seat 1) opens.
+    fn game(tasks: Value) -> Value {
+        json!({
+            "hands": [["P1", "B2"], ["S4", "P3"], ["P5", "B1"]],
+            "tasks": tasks,
+        })
+    }
+
+    fn call(command: &str, game: Value) -> Value {
+        let request = json!({ "version": API_VERSION, "command": command, "game": game });
+        serde_json::from_str(&handle(&request.to_string())).unwrap()
+    }
+
+    fn error(response: &Value) -> (&str, &str) {
+        assert_eq!(response["status"], "error");
+        (
+            response["kind"].as_str().unwrap(),
+            response["message"].as_str().unwrap(),
+        )
+    }
+
+    #[test]
+    fn solve() {
+        let response = call("solve", game(json!([[], [{ "id": "win_pink_1" }], []])));
+        assert_eq!(response["version"], API_VERSION);
+        assert_eq!(response["status"], "solved");
+        let line: Vec<Card> = serde_json::from_value(response["line"].clone()).unwrap();
+        assert!(!line.is_empty());
+
+        let mut game: Game =
+            serde_json::from_value(game(json!([[], [{ "id": "win_pink_1" }], []]))).unwrap();
+        game.history = line;
+        assert_eq!(game.to_state().unwrap().game_status(), TaskStatus::Done);
+
+        // The line starts after the history
+        let mut with_history = super::test::game(json!([[], [{ "id": "win_pink_1" }], []]));
+        with_history["history"] = json!(["S4", "P5", "P1"]);
+        let response = call("solve", with_history);
+        assert_eq!(response["line"], json!([]));
+    }
+
+    #[test]
+    fn infeasible() {
+        let tasks =
+            json!([[{ "id": "win_all_3s" }], [{ "id": "win_pink_1" }], [{ "id": "win_pink_1" }]]);
+        let response: Response = serde_json::from_str(&handle(
+            &json!({ "version": 1, "command": "solve", "game": game(tasks) }).to_string(),
+        ))
+        .unwrap();
+        let report = |seat: usize, feasible: Feasibility| TaskReport {
+            seat,
+            id: if seat == 0 {
+                "win_all_3s"
+            } else {
+                "win_pink_1"
+            }
+            .to_string(),
+            status: TaskStatus::Unknown,
+            feasible_alone: Some(feasible),
+        };
+        assert_eq!(
+            response.outcome,
+            Outcome::Infeasible {
+                tasks: vec![
+                    report(0, Feasibility::Infeasible),
+                    report(1, Feasibility::Feasible),
+                    report(2, Feasibility::Feasible)
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn feasible_alone_unknown() {
+        let tasks =
+            json!([[{ "id": "win_all_3s" }], [{ "id": "win_pink_1" }], [{ "id": "win_pink_1" }]]);
+        let request =
+            json!({ "version": 1, "command": "solve", "game": game(tasks), "budget": 20 });
+        let json = handle(&request.to_string());
+        let response: Response = serde_json::from_str(&json).unwrap();
+        let Outcome::Infeasible { tasks } = &response.outcome else {
+            panic!("unexpected outcome {:?}", response.outcome);
+        };
+        let feasible: Vec<_> = tasks.iter().map(|t| t.feasible_alone).collect();
+        assert_eq!(
+            feasible,
+            [
+                Some(Feasibility::Unknown),
+                Some(Feasibility::Feasible),
+                Some(Feasibility::Feasible)
+            ]
+        );
+        assert_eq!(serde_json::to_string(&response).unwrap(), json);
+    }
+
+    #[test]
+    fn analyze() {
+        let mut game = game(json!([[], [{ "id": "win_pink_1" }], []]));
+        game["history"] = json!(["P3", "P5"]);
+        let response: Response = serde_json::from_value(call("analyze", game)).unwrap();
+        assert_eq!(
+            response.outcome,
+            Outcome::Analysis {
+                mission: TaskStatus::Unknown,
+                to_play: 0,
+                tasks: vec![TaskReport {
+                    seat: 1,
+                    id: "win_pink_1".to_string(),
+                    status: TaskStatus::Unknown,
+                    feasible_alone: None,
+                }],
+                moves: vec![Move {
+                    card: pink(1),
+                    winning: Some(false),
+                }],
+            }
+        );
+
+        // Once every trick is won, there is no move left
+        let mut game = super::test::game(json!([]));
+        game["history"] = json!(["S4", "P5", "P1", "P3", "B1", "B2"]);
+        let response = call("analyze", game);
+        assert_eq!(response["mission"], "Done");
+        assert_eq!(response["moves"], json!([]));
+
+        let response = call("analyze", super::test::game(json!([])));
+        let cards: Vec<Card> = response["moves"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| serde_json::from_value(m["card"].clone()).unwrap())
+            .collect();
+        assert_eq!(cards, [submarine(4), pink(3)]);
+    }
+
+    #[test]
+    fn budget() {
+        let mut hands = vec![vec![]; 3];
+        for (i, card) in crate::card::deck().into_iter().enumerate() {
+            hands[i % 3].push(card);
+        }
+        let tasks: Vec<_> = ["win_pink_1", "dont_win_9", "win_all_3s"]
+            .iter()
+            .map(|id| vec![json!({ "id": id })])
+            .collect();
+        let request = json!({
+            "version": API_VERSION,
+            "command": "solve",
+            "game": { "hands": hands, "tasks": tasks },
+            "budget": 10,
+        });
+        let response: Value = serde_json::from_str(&handle(&request.to_string())).unwrap();
+        assert_eq!(
+            response,
+            json!({ "version": API_VERSION, "status": "budget_exceeded", "budget": 10 })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let response: Value = serde_json::from_str(&handle("{")).unwrap();
+        assert_eq!(error(&response).0, "invalid_request");
+        let request =
+            json!({ "version": API_VERSION, "command": "solve", "game": game(json!([])), "x": 1 });
+        let response: Value = serde_json::from_str(&handle(&request.to_string())).unwrap();
+        assert_eq!(error(&response).0, "invalid_request");
+        let request = json!({ "version": 0, "command": "solve", "game": game(json!([])) });
+        let response: Value = serde_json::from_str(&handle(&request.to_string())).unwrap();
+        assert_eq!(error(&response).0, "unsupported_version");
+
+        // Cards that cannot be read are rejected with the request
+        let bad_card = json!({ "hands": [["P12"], ["S4"], ["B1"]] });
+        assert_eq!(error(&call("solve", bad_card)).0, "invalid_request");
+
+        let invalid = |game: Value, message: &str| {
+            let response = call("solve", game);
+            assert_eq!(error(&response), ("invalid_game", message));
+        };
+        invalid(
+            json!({ "hands": [["P1", "B2"], ["S4", "P1"], ["P5", "B1"]] }),
+            &GameError::DuplicateCard(pink(1)).to_string(),
+        );
+        invalid(
+            json!({ "hands": [["S4", "P1", "P2", "P3"], [], []] }),
+            &GameError::UnevenHands(0, 4).to_string(),
+        );
+        invalid(
+            json!({ "hands": [["P1"], ["S4"]] }),
+            &GameError::InvalidNPlayers(2).to_string(),
+        );
+        invalid(
+            game(json!([[{ "id": "win_pink_10" }], [], []])),
+            "Unknown task ID \"win_pink_10\".",
+        );
+        invalid(
+            game(json!([[{ "id": "win_pink_1", "prediction": 1 }], [], []])),
+            "Task \"win_pink_1\" takes no prediction.",
+        );
+        invalid(
+            game(json!([[{ "id": "win_x_tricks", "prediction": 14 }], [], []])),
+            "Prediction 14 of task \"win_x_tricks\" exceeds the number of tricks.",
+        );
+        invalid(
+            game(json!([[], [{ "id": "win_pink_1" }]])),
+            &GameError::InvalidTaskAssignment(3, 2).to_string(),
+        );
+        let mut illegal = game(json!([]));
+        illegal["history"] = json!(["P3", "B1"]);
+        invalid(
+            illegal,
+            &GameError::MustFollowColor(1, 2, blue(1), pink(3)).to_string(),
+        );
+
+        // A valid prediction is given to the task
+        let response = call(
+            "analyze",
+            game(json!([[{ "id": "win_x_tricks", "prediction": 0 }], [], []])),
+        );
+        assert_eq!(response["status"], "analysis");
+    }
+
+    #[test]
+    fn no_panic() {
+        let request = json!({
+            "version": API_VERSION,
+            "command": "analyze",
+            "game": {
+                "hands": [["P1", "B2", "G3"], ["S4", "P3"], ["P5"], []],
+                "tasks": [[{ "id": "win_x_tricks_hidden", "prediction": 2 }], [], [], [{ "id": "win_all_3s" }]],
+                "history": ["S4", "P5"],
+            },
+        })
+        .to_string();
+        for end in 0..=request.len() {
+            if request.is_char_boundary(end) {
+                let response: Response = serde_json::from_str(&handle(&request[..end])).unwrap();
+                assert_eq!(response.version, API_VERSION);
+            }
+        }
+        for garbage in ["", "null", "[]", "\"solve\"", "{\"version\": -1}", "\u{0}"] {
+            let response: Value = serde_json::from_str(&handle(garbage)).unwrap();
+            assert_eq!(error(&response).0, "invalid_request");
+        }
+    }
+}
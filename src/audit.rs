@@ -0,0 +1,215 @@
+//! Auditing of task evaluation.
+//!
+//! The early exits of the solver ([`State::play`]) rely on every task being monotonic: once
+//! [`BaseTask::eval`] returns `Done` or `Failed`, it must keep returning it for the rest of the
+//! game. A task breaking this rule silently makes the solver give wrong answers, so the
+//! functions of this module replay games, evaluating every task after each card, and report the
+//! first transition that breaks the rule. They are meant to be run in tests and debug builds,
+//! not during normal play.
+use std::fmt::Display;
+
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+    catalog,
+    state::State,
+    task::{BaseTask, Task, TaskStatus},
+};
+
+/// A task whose status went from `before` to `after` when the last card of `state` was played.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub player: usize,
+    /// Index of the task among the tasks of the player.
+    pub task_idx: usize,
+    pub task: Task,
+    pub before: TaskStatus,
+    pub after: TaskStatus,
+    /// The state right after the transition.
+    pub state: State,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "task {} of player {} ({:?}) went from {:?} to {:?}",
+            self.task_idx, self.player, self.task, self.before, self.after
+        )
+    }
+}
+
+/// Only `Unknown` may change, and it may change to anything.
+fn is_allowed(before: TaskStatus, after: TaskStatus) -> bool {
+    before == TaskStatus::Unknown || before == after
+}
+
+impl State {
+    /// Status of every task, player by player.
+    fn task_statuses(&self) -> Vec<Vec<TaskStatus>> {
+        self.get_players()
+            .iter()
+            .enumerate()
+            .map(|(ip, p)| p.get_tasks().iter().map(|t| t.eval(self, ip)).collect())
+            .collect()
+    }
+
+    /// Returns the first task of `self` whose status changed illegally since `before`.
+    fn check_transition(&self, before: &[Vec<TaskStatus>]) -> Result<(), Box<Violation>> {
+        let after = self.task_statuses();
+        for (ip, (before, after)) in before.iter().zip(&after).enumerate() {
+            for (task_idx, (&before, &after)) in before.iter().zip(after).enumerate() {
+                if !is_allowed(before, after) {
+                    return Err(Box::new(Violation {
+                        player: ip,
+                        task_idx,
+                        task: self.get_player(ip).get_tasks()[task_idx].clone(),
+                        before,
+                        after,
+                        state: self.clone(),
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Explores the lines of play the solver may follow, without its early exits, and checks
+    /// every transition. At most `budget` states are explored: returns `Ok(true)` if all lines
+    /// were checked, `Ok(false)` if the budget was exceeded.
+    pub fn audit_solver_paths(&self, budget: usize) -> Result<bool, Box<Violation>> {
+        let mut stack = vec![(self.clone(), self.task_statuses())];
+        let mut explored = 0;
+
+        while let Some((state, statuses)) = stack.pop() {
+            if explored >= budget {
+                return Ok(false);
+            }
+            explored += 1;
+
+            for card in state
+                .get_current_player()
+                .get_hand()
+                .playable_cards(state.get_current_trick().first())
+            {
+                let mut new_state = state.clone();
+                if new_state.play_card(&card).is_err() {
+                    continue;
+                }
+                new_state.check_transition(&statuses)?;
+                let new_statuses = new_state.task_statuses();
+                stack.push((new_state, new_statuses));
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Plays `n_playouts` random games from `self` until no card is left, checking every
+    /// transition.
+    pub fn audit_random_playouts<R: Rng>(
+        &self,
+        n_playouts: usize,
+        rng: &mut R,
+    ) -> Result<(), Box<Violation>> {
+        for _ in 0..n_playouts {
+            let mut state = self.clone();
+            let mut statuses = state.task_statuses();
+            loop {
+                let playable = state
+                    .get_current_player()
+                    .get_hand()
+                    .playable_cards(state.get_current_trick().first());
+                let Some(card) = playable.choose(rng) else {
+                    break;
+                };
+                state
+                    .play_card(card)
+                    .expect("a playable card can always be played");
+                state.check_transition(&statuses)?;
+                statuses = state.task_statuses();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Audits every task of the catalog on `n_games` random deals, with one random playout each.
+/// The task is given to the player after the captain, so that tasks the captain cannot take
+/// are audited too.
+pub fn audit_catalog<R: Rng>(
+    n_players: usize,
+    n_games: usize,
+    rng: &mut R,
+) -> Result<(), Box<Violation>> {
+    for entry in catalog::entries() {
+        for _ in 0..n_games {
+            let mut state = State::new_random_with_rng(n_players, rng);
+            let ip = (state.first_player() + 1) % n_players;
+            state
+                .get_mut_player(ip)
+                .add_task(entry.build(n_players))
+                .expect("only the captain may be forbidden a task");
+            state.audit_random_playouts(1, rng)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::task;
+
+    #[test]
+    fn catalog_is_monotonic() {
+        let mut rng = StdRng::seed_from_u64(40);
+        for n_players in 3..=5 {
+            if let Err(violation) = audit_catalog(n_players, 20, &mut rng) {
+                panic!("{}", violation);
+            }
+        }
+    }
+
+    #[test]
+    fn solver_paths_are_monotonic() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let mut state = State::new_random_with_rng(3, &mut rng);
+        let ip = (state.first_player() + 1) % 3;
+        for t in [
+            task::win_trick_with_only_odd_numbers().into(),
+            task::win_exactly_two_consecutive_tricks().into(),
+            task::win_last_trick_only().into(),
+        ] {
+            state.get_mut_player(ip).add_task::<Task>(t).unwrap();
+        }
+        if let Err(violation) = state.audit_solver_paths(20_000) {
+            panic!("{}", violation);
+        }
+    }
+
+    #[test]
+    fn reports_violation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut state = State::new_random_with_rng(3, &mut rng);
+        let ip = (state.first_player() + 1) % 3;
+        state
+            .get_mut_player(ip)
+            .add_task(task::win_trick_with_only_odd_numbers())
+            .unwrap();
+
+        // Pretending the task was done before the first card: it is unknown now.
+        let mut before = state.task_statuses();
+        before[ip][0] = TaskStatus::Done;
+        let violation = state.check_transition(&before).unwrap_err();
+        assert_eq!((violation.player, violation.task_idx), (ip, 0));
+        assert_eq!(violation.after, TaskStatus::Unknown);
+        assert!(
+            violation
+                .to_string()
+                .starts_with(&format!("task 0 of player {}", ip))
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
 pub const NB_CARDS: usize = 40;
 pub const COLORS: [fn(usize) -> Card; 4] = [Card::Pink, Card::Green, Card::Blue, Card::Yellow];
 pub const COLOR_RANGE: std::ops::Range<usize> = 1..10;
@@ -36,6 +40,20 @@ card_ctor_decl!(blue, Card::Blue);
 card_ctor_decl!(yellow, Card::Yellow);
 card_ctor_decl!(submarine, Card::Submarine);
 
+/// Returns the full deck of the game, ordered by value then color.
+pub fn deck() -> Vec<Card> {
+    let mut cards = Vec::with_capacity(NB_CARDS);
+    for i in COLOR_RANGE {
+        for variant in COLORS {
+            cards.push(variant(i));
+        }
+    }
+    for i in SUBMARINE_RANGE {
+        cards.push(Card::Submarine(i));
+    }
+    cards
+}
+
 impl Card {
     pub fn is_valid(&self) -> bool {
         match *self {
@@ -72,6 +90,109 @@ impl Card {
     }
 }
 
+/// Error returned when reading cards in the short notation (see [`Card::from_str`]).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseCardError {
+    #[error("Missing card.")]
+    Empty,
+    #[error(
+        "Unknown color in {0:?}: expected P, G, B, Y or S (or pink, green, blue, yellow, submarine)."
+    )]
+    UnknownColor(String),
+    #[error("Invalid value in {0:?}.")]
+    InvalidValue(String),
+    #[error("{0:?} is not a card of the game.")]
+    InvalidCard(String),
+    #[error("Card {0} appears twice.")]
+    Duplicate(Card),
+    #[error("Invalid trick header {0:?}: expected \"#<index> @<first player>:\".")]
+    InvalidTrickHeader(String),
+}
+
+/// Reads a card in the short notation: a color letter followed by the value, such as `P7` or
+/// `S4`. The color may also be written in full (`pink7`, `submarine4`, or `sub4`), and case
+/// does not matter.
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseCardError::Empty);
+        }
+        // Long names first, so that they are tried first
+        let names = [
+            ("submarine", Card::Submarine as fn(usize) -> Card),
+            ("yellow", Card::Yellow),
+            ("green", Card::Green),
+            ("pink", Card::Pink),
+            ("blue", Card::Blue),
+            ("sub", Card::Submarine),
+            ("p", Card::Pink),
+            ("g", Card::Green),
+            ("b", Card::Blue),
+            ("y", Card::Yellow),
+            ("s", Card::Submarine),
+        ];
+        let lower = s.to_ascii_lowercase();
+        let (color, value) = names
+            .iter()
+            .find_map(|(name, color)| lower.strip_prefix(name).map(|value| (color, value)))
+            .ok_or_else(|| ParseCardError::UnknownColor(s.to_string()))?;
+        if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ParseCardError::InvalidValue(s.to_string()));
+        }
+        let value = value
+            .parse()
+            .map_err(|_| ParseCardError::InvalidValue(s.to_string()))?;
+        let card = color(value);
+        if !card.is_valid() {
+            return Err(ParseCardError::InvalidCard(s.to_string()));
+        }
+        Ok(card)
+    }
+}
+
+/// Writes the card in the short notation, such as `P7` or `S4`.
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let letter = match self {
+            Card::Pink(_) => 'P',
+            Card::Green(_) => 'G',
+            Card::Blue(_) => 'B',
+            Card::Yellow(_) => 'Y',
+            Card::Submarine(_) => 'S',
+        };
+        write!(f, "{}{}", letter, self.val())
+    }
+}
+
+/// Reads distinct cards separated by whitespace or commas, as in `P7 G3, B9`.
+pub(crate) fn parse_cards(s: &str) -> Result<Vec<Card>, ParseCardError> {
+    let mut cards: Vec<Card> = Vec::new();
+    for word in s.split(|c: char| c.is_whitespace() || c == ',') {
+        if word.is_empty() {
+            continue;
+        }
+        let card = word.parse()?;
+        if cards.contains(&card) {
+            return Err(ParseCardError::Duplicate(card));
+        }
+        cards.push(card);
+    }
+    Ok(cards)
+}
+
+/// Writes the cards separated by spaces.
+pub(crate) fn fmt_cards(cards: &[Card], f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for (i, card) in cards.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", card)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -163,4 +284,58 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn round_trip() {
+        for card in deck() {
+            assert_eq!(card.to_string().parse(), Ok(card));
+        }
+        assert_eq!(pink(7).to_string(), "P7");
+        assert_eq!(submarine(4).to_string(), "S4");
+    }
+
+    #[test]
+    fn aliases() {
+        for s in ["P7", "p7", "pink7", "Pink7", "PINK7"] {
+            assert_eq!(s.parse(), Ok(pink(7)));
+        }
+        for s in ["S3", "sub3", "submarine3"] {
+            assert_eq!(s.parse(), Ok(submarine(3)));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!("".parse::<Card>(), Err(ParseCardError::Empty));
+        assert_eq!(
+            "R7".parse::<Card>(),
+            Err(ParseCardError::UnknownColor("R7".to_string()))
+        );
+        assert_eq!(
+            "P".parse::<Card>(),
+            Err(ParseCardError::InvalidValue("P".to_string()))
+        );
+        assert_eq!(
+            "P+7".parse::<Card>(),
+            Err(ParseCardError::InvalidValue("P+7".to_string()))
+        );
+        assert_eq!(
+            "P99999999999999999999999".parse::<Card>(),
+            Err(ParseCardError::InvalidValue(
+                "P99999999999999999999999".to_string()
+            ))
+        );
+        assert_eq!(
+            "S5".parse::<Card>(),
+            Err(ParseCardError::InvalidCard("S5".to_string()))
+        );
+        assert_eq!(
+            "B0".parse::<Card>(),
+            Err(ParseCardError::InvalidCard("B0".to_string()))
+        );
+        assert_eq!(
+            parse_cards("P7 G3 p7"),
+            Err(ParseCardError::Duplicate(pink(7)))
+        );
+    }
 }
@@ -0,0 +1,492 @@
+//! Registry of the 96 tasks of the game.
+//!
+//! Each entry has a stable ID (the name of the function building the task in [`crate::task`]),
+//! which UIs, file formats and mission generators use to refer to catalog tasks.
+use crate::task::{self, BaseTask, Task, TaskDifficulty};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TaskFlags {
+    /// The captain cannot take this task.
+    pub captain_forbidden: bool,
+    /// Cards must be dealt again if someone holds some specific submarines.
+    pub redeal: bool,
+    /// Part of the task (the prediction) is only known to its holder.
+    pub hidden_information: bool,
+}
+
+const NONE: TaskFlags = TaskFlags {
+    captain_forbidden: false,
+    redeal: false,
+    hidden_information: false,
+};
+const CAPTAIN_FORBIDDEN: TaskFlags = TaskFlags {
+    captain_forbidden: true,
+    ..NONE
+};
+const REDEAL: TaskFlags = TaskFlags {
+    redeal: true,
+    ..NONE
+};
+const HIDDEN_INFORMATION: TaskFlags = TaskFlags {
+    hidden_information: true,
+    ..NONE
+};
+
+#[derive(Debug)]
+pub struct CatalogEntry {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub flags: TaskFlags,
+    build: fn(usize) -> Task,
+}
+
+impl CatalogEntry {
+    /// Builds the task for a game with `n_players` players.
+    pub fn build(&self, n_players: usize) -> Task {
+        (self.build)(n_players)
+    }
+
+    pub fn difficulty(&self) -> TaskDifficulty {
+        self.build(3)
+            .difficulty()
+            .expect("catalog tasks have a difficulty")
+    }
+}
+
+macro_rules! entry {
+    ($id:ident, $label:expr) => {
+        entry!($id, $label, NONE)
+    };
+    ($id:ident, $label:expr, $flags:expr) => {
+        CatalogEntry {
+            id: stringify!($id),
+            label: $label,
+            flags: $flags,
+            build: |_| task::$id().into(),
+        }
+    };
+}
+
+/// Same as [`entry!`] for tasks depending on the number of players.
+macro_rules! entry_n_players {
+    ($id:ident, $label:expr) => {
+        CatalogEntry {
+            id: stringify!($id),
+            label: $label,
+            flags: NONE,
+            build: |n_players| task::$id(n_players).into(),
+        }
+    };
+}
+
+static CATALOG: [CatalogEntry; 96] = [
+    entry!(win_pink_1, "Win the pink 1"),
+    entry!(win_yellow_1, "Win the yellow 1"),
+    entry!(win_blue_4, "Win the blue 4"),
+    entry!(win_green_6, "Win the green 6"),
+    entry!(win_all_3s, "Win all four 3s"),
+    entry!(win_all_9s, "Win all four 9s"),
+    entry!(win_blue_1_2_3, "Win the blue 1, 2 and 3"),
+    entry!(win_blue_6_yellow_7, "Win the blue 6 and the yellow 7"),
+    entry!(win_pink_5_yellow_6, "Win the pink 5 and the yellow 6"),
+    entry!(win_green_5_blue_8, "Win the green 5 and the blue 8"),
+    entry!(win_blue_5_pink_8, "Win the blue 5 and the pink 8"),
+    entry!(win_pink_9_yellow_8, "Win the pink 9 and the yellow 8"),
+    entry!(win_pink_1_green_7, "Win the pink 1 and the green 7"),
+    entry!(win_yellow_9_blue_7, "Win the yellow 9 and the blue 7"),
+    entry!(
+        win_green_3_yellow_4_5,
+        "Win the green 3 and the yellow 4 and 5"
+    ),
+    entry!(win_3_submarine, "Win the 3 submarine"),
+    entry!(dont_win_pink, "Win no pink cards"),
+    entry!(dont_win_submarine, "Win no submarines"),
+    entry!(dont_win_green, "Don't win any green cards"),
+    entry!(dont_win_yellow, "Don't win any yellow cards"),
+    entry!(dont_win_pink_blue, "Don't win any pink or blue cards"),
+    entry!(dont_win_yellow_green, "Don't win any yellow or green cards"),
+    entry!(dont_win_8_9, "Don't win any 8s or 9s"),
+    entry!(dont_win_9, "Don't win any 9s"),
+    entry!(dont_win_5, "Don't win any 5s"),
+    entry!(dont_win_1, "Don't win any 1s"),
+    entry!(dont_win_1_2_3, "Don't win any 1s, 2s or 3s"),
+    entry!(
+        dont_open_with_pink_yellow_blue,
+        "Don't open a trick with a pink, yellow or blue card"
+    ),
+    entry!(
+        dont_open_with_pink_green,
+        "Don't open a trick with a pink or green card"
+    ),
+    entry!(win_trick_with_6, "Win a trick with a 6"),
+    entry!(win_trick_with_5, "Win a trick with a 5"),
+    entry!(win_trick_with_3, "Win a trick with a 3"),
+    entry!(win_trick_containing_5_with_7, "Win a 5 with a 7"),
+    entry!(win_trick_containing_8_with_4, "Win an 8 with a 4"),
+    entry!(win_trick_containing_6_with_6, "Win a 6 with another 6"),
+    entry!(win_trick_with_2, "Win a trick with a 2"),
+    entry!(
+        win_more_tricks_than_captain,
+        "Win more tricks than the captain",
+        CAPTAIN_FORBIDDEN
+    ),
+    entry!(
+        win_less_tricks_than_captain,
+        "Win fewer tricks than the captain",
+        CAPTAIN_FORBIDDEN
+    ),
+    entry!(
+        win_same_nb_tricks_that_captain,
+        "Win the same number of tricks as the captain",
+        CAPTAIN_FORBIDDEN
+    ),
+    entry!(
+        win_trick_with_all_cards_lower_than_7,
+        "Win a trick where all cards are of lower value than 7 (without submarines)"
+    ),
+    entry!(
+        win_trick_with_all_cards_greater_than_5,
+        "Win a trick where all cards are of greater value than 5"
+    ),
+    entry!(
+        win_trick_with_only_even_numbers,
+        "Win a trick that has only even numbers"
+    ),
+    entry!(
+        win_trick_with_only_odd_numbers,
+        "Win a trick that has only odd numbers"
+    ),
+    entry_n_players!(
+        win_trick_total_value_higher_than,
+        "Win a trick with a total value higher than 23/28/31 (without submarines)"
+    ),
+    entry_n_players!(
+        win_trick_total_value_lower_than,
+        "Win a trick with a total value lower than 8/12/16 (without submarines)"
+    ),
+    entry!(
+        win_trick_with_total_value_22_or_23,
+        "Win a trick with a total value of 22 or 23"
+    ),
+    entry!(
+        win_trick_with_same_amount_green_and_yellow,
+        "Win a trick with the same amount of green and yellow cards (more than 0)"
+    ),
+    entry!(
+        win_trick_with_same_amount_pink_and_blue,
+        "Win a trick with the same amount of pink and blue cards (more than 0)"
+    ),
+    entry!(win_pink_7_with_submarine, "Win the pink 7 with a submarine"),
+    entry!(
+        win_green_9_with_submarine,
+        "Win the green 9 with a submarine"
+    ),
+    entry_n_players!(
+        win_green_2_in_last_trick,
+        "Win the green 2 in the last trick"
+    ),
+    entry!(
+        dont_win_first_four_tricks,
+        "Don't win any of the first four tricks"
+    ),
+    entry!(
+        dont_win_first_three_tricks,
+        "Don't win any of the first three tricks"
+    ),
+    entry!(
+        dont_win_first_five_tricks,
+        "Don't win any of the first five tricks"
+    ),
+    entry!(dont_win_any_tricks, "Don't win any tricks"),
+    entry!(win_last_trick, "Win the last trick"),
+    entry!(win_first_three_tricks, "Win the first three tricks"),
+    entry!(win_first_two_tricks, "Win the first two tricks"),
+    entry!(win_first_trick, "Win the first trick"),
+    entry!(win_first_and_last_trick, "Win the first and the last trick"),
+    entry!(win_last_trick_only, "Win only the last trick"),
+    entry!(win_first_trick_only, "Win only the first trick"),
+    entry!(win_exactly_one_trick, "Win exactly one trick"),
+    entry!(win_exactly_two_tricks, "Win exactly two tricks"),
+    entry!(win_exactly_four_tricks, "Win exactly four tricks"),
+    entry!(
+        win_x_tricks,
+        "Win X tricks (predict the exact number and show it)"
+    ),
+    entry!(
+        win_x_tricks_hidden,
+        "Win X tricks (predict the exact number but keep it hidden)",
+        HIDDEN_INFORMATION
+    ),
+    entry!(
+        win_exactly_1_pink_1_green,
+        "Win exactly one pink and one green card"
+    ),
+    entry!(win_at_least_7_yellows, "Win at least seven yellow cards"),
+    entry!(win_at_least_5_pinks, "Win at least five pink cards"),
+    entry!(win_exactly_2_greens, "Win exactly two green cards"),
+    entry!(win_exactly_2_blues, "Win exactly two blue cards"),
+    entry!(win_exactly_1_pink, "Win exactly one pink card"),
+    entry!(
+        win_at_least_one_each_color,
+        "Win at least one card of each color (excluding submarines)"
+    ),
+    entry!(
+        win_exactly_1_submarine,
+        "Win exactly one submarine (deal new cards if someone has all submarines in hand)",
+        REDEAL
+    ),
+    entry!(
+        win_exactly_2_submarines,
+        "Win exactly two submarines (deal new cards if someone has submarines 2, 3 and 4 in hand)",
+        REDEAL
+    ),
+    entry!(
+        win_exactly_3_submarines,
+        "Win exactly three submarines (deal new cards if someone has all submarines in hand)",
+        REDEAL
+    ),
+    entry!(win_at_least_three_5s, "Win at least three 5s"),
+    entry!(win_at_least_three_9s, "Win at least three 9s"),
+    entry!(win_at_least_two_7s, "Win at least two 7s"),
+    entry!(win_exactly_three_6s, "Win exactly three 6s"),
+    entry!(win_exactly_two_9s, "Win exactly two 9s"),
+    entry!(win_two_consecutive_tricks, "Win two consecutive tricks"),
+    entry!(win_three_consecutive_tricks, "Win three consecutive tricks"),
+    entry!(
+        win_exactly_three_consecutive_tricks,
+        "Win exactly three consecutive tricks"
+    ),
+    entry!(
+        win_exactly_two_consecutive_tricks,
+        "Win exactly two consecutive tricks"
+    ),
+    entry!(
+        dont_win_consecutive_tricks,
+        "Don't win two consecutive tricks"
+    ),
+    entry!(
+        win_submarine_only_1,
+        "Win the 1 submarine and no other (deal new cards if someone has submarines 1 and 4 or 1, 2 and 3 in hand)",
+        REDEAL
+    ),
+    entry!(
+        win_submarine_only_2,
+        "Win the 2 submarine and no other (deal new cards if someone has submarines 2 and 4 or 1, 2 and 3 in hand)",
+        REDEAL
+    ),
+    entry!(
+        win_all_cards_color,
+        "Win all cards of at least one color (excluding submarines)"
+    ),
+    entry!(
+        win_same_amount_pink_and_yellow,
+        "Win the same amount of pink and yellow cards (more than 0)"
+    ),
+    entry!(
+        win_more_yellow_than_blue,
+        "Win more yellow cards than blue cards (0 blue cards are allowed)"
+    ),
+    entry!(
+        win_more_pink_than_green,
+        "Win more pink cards than green cards (0 green cards are allowed)"
+    ),
+    entry!(
+        win_more_tricks_than_everyone_else,
+        "Win more tricks than everyone else"
+    ),
+    entry!(
+        win_more_tricks_than_everyone_else_together,
+        "Win more tricks than everyone else together"
+    ),
+    entry!(
+        win_fewer_tricks_than_everyone_else,
+        "Win fewer tricks than everyone else"
+    ),
+];
+
+/// All entries of the catalog, in a stable order.
+pub fn entries() -> &'static [CatalogEntry] {
+    &CATALOG
+}
+
+pub fn get(id: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|e| e.id == id)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        card::Card::{Blue, Green, Submarine},
+        locale::Locale,
+        player::Player,
+        state::State,
+    };
+
+    use super::*;
+
+    #[test]
+    fn unique_ids() {
+        let ids: HashSet<&str> = entries().iter().map(|e| e.id).collect();
+        assert_eq!(ids.len(), 96);
+    }
+
+    #[test]
+    fn lookup() {
+        for e in entries() {
+            assert_eq!(get(e.id).unwrap().id, e.id);
+        }
+        assert!(get("win_pink_10").is_none());
+        assert!(get("win_exactly_1_submarine").unwrap().flags.redeal);
+        assert!(
+            get("win_less_tricks_than_captain")
+                .unwrap()
+                .flags
+                .captain_forbidden
+        );
+        assert_eq!(
+            get("win_at_least_two_7s").unwrap().difficulty(),
+            [2, 2, 2].into()
+        );
+    }
+
+    #[test]
+    fn flags_match_tasks() {
+        let state = State::new([
+            Player::new([Submarine(1), Submarine(2), Submarine(3), Submarine(4)].into()),
+            Player::new([Blue(1), Blue(2), Blue(3), Blue(4)].into()),
+            Player::new([Green(1), Green(2), Green(3), Green(4)].into()),
+        ]);
+        for e in entries() {
+            let task = e.build(3);
+            assert_eq!(
+                task.captain_forbidden(),
+                e.flags.captain_forbidden,
+                "{}",
+                e.id
+            );
+            assert_eq!(task.needs_redeal(&state), e.flags.redeal, "{}", e.id);
+        }
+        assert!(
+            !get("win_exactly_1_submarine")
+                .unwrap()
+                .build(3)
+                .needs_redeal(&State::new([
+                    Player::new([Submarine(1), Submarine(2), Blue(3), Submarine(4)].into()),
+                    Player::new([Blue(1), Blue(2), Submarine(3), Blue(4)].into()),
+                    Player::new([Green(1), Green(2), Green(3), Green(4)].into()),
+                ]))
+        );
+    }
+
+    #[test]
+    fn describe_all() {
+        for e in entries() {
+            for n_players in 3..=5 {
+                let task = e.build(n_players);
+                let label = e
+                    .label
+                    .replace("23/28/31", ["23", "28", "31"][n_players - 3])
+                    .replace("8/12/16", ["8", "12", "16"][n_players - 3]);
+                assert_eq!(task.describe(Locale::English), label);
+            }
+        }
+
+        let french: HashSet<String> = entries()
+            .iter()
+            .map(|e| e.build(4).describe(Locale::French))
+            .collect();
+        assert_eq!(french.len(), entries().len());
+    }
+
+    #[test]
+    fn describe_french() {
+        let expected = [
+            ("win_pink_9_yellow_8", "Remportez le 9 rose et le 8 jaune"),
+            (
+                "win_green_3_yellow_4_5",
+                "Remportez le 3 vert et les 4 et 5 jaunes",
+            ),
+            ("dont_win_submarine", "Ne remportez aucun sous-marin"),
+            (
+                "dont_win_yellow_green",
+                "Ne remportez aucune carte jaune ou verte",
+            ),
+            ("dont_win_8_9", "Ne remportez aucun 8 ou 9"),
+            (
+                "dont_open_with_pink_yellow_blue",
+                "N'ouvrez pas de pli avec une carte rose, jaune ou bleue",
+            ),
+            (
+                "dont_win_consecutive_tricks",
+                "Ne remportez jamais deux plis consécutifs",
+            ),
+            (
+                "dont_win_first_four_tricks",
+                "Ne remportez aucun des quatre premiers plis",
+            ),
+            (
+                "win_all_cards_color",
+                "Remportez toutes les cartes d'au moins une couleur (hors sous-marins)",
+            ),
+            (
+                "win_exactly_1_pink_1_green",
+                "Remportez exactement une carte rose et une carte verte",
+            ),
+            ("win_at_least_three_5s", "Remportez au moins trois 5"),
+            (
+                "win_two_consecutive_tricks",
+                "Remportez deux plis consécutifs",
+            ),
+            (
+                "win_same_amount_pink_and_yellow",
+                "Remportez autant de cartes roses que de cartes jaunes (au moins une)",
+            ),
+            (
+                "win_more_pink_than_green",
+                "Remportez plus de cartes roses que de cartes vertes (ne remporter aucune carte verte est permis)",
+            ),
+            (
+                "win_fewer_tricks_than_everyone_else",
+                "Remportez moins de plis que chacun des autres joueurs",
+            ),
+            ("win_exactly_one_trick", "Remportez exactement un pli"),
+            (
+                "win_x_tricks_hidden",
+                "Remportez X plis (prédisez le nombre exact mais gardez-le secret)",
+            ),
+            (
+                "win_more_tricks_than_captain",
+                "Remportez plus de plis que le capitaine",
+            ),
+            (
+                "win_submarine_only_1",
+                "Remportez le sous-marin 1 et aucun autre (redistribuez les cartes si quelqu'un a les sous-marins 1 et 4 ou 1, 2 et 3 en main)",
+            ),
+            ("win_trick_with_6", "Remportez un pli avec un 6"),
+            (
+                "win_trick_with_all_cards_lower_than_7",
+                "Remportez un pli dont toutes les cartes ont une valeur inférieure à 7 (sans sous-marin)",
+            ),
+            (
+                "win_trick_total_value_higher_than",
+                "Remportez un pli d'une valeur totale supérieure à 28 (sans sous-marin)",
+            ),
+            ("win_last_trick", "Remportez le dernier pli"),
+        ];
+        for (id, text) in expected {
+            assert_eq!(get(id).unwrap().build(4).describe(Locale::French), text);
+        }
+    }
+
+    #[test]
+    fn build_all() {
+        for e in entries() {
+            for n_players in 3..=5 {
+                let task = e.build(n_players);
+                assert_eq!(task.difficulty(), Some(e.difficulty()));
+            }
+        }
+    }
+}
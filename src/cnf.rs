@@ -0,0 +1,932 @@
+//! Export of a mission as a SAT problem in DIMACS CNF.
+//!
+//! [`State::to_cnf`] encodes the rest of a game as boolean clauses, so that external SAT
+//! solvers can be compared with the depth-first search of [`State::play`]. There is one
+//! variable per card and trick left, true when the card is played in that trick. Every seat
+//! plays exactly one card per trick, each card is played at most once, cards must follow the
+//! color led when possible, and the winner of each trick leads the next one. On top of these
+//! rules, every task of every player must be done once all tricks are won.
+//!
+//! A model found by a SAT solver is read back with [`Cnf::decode`], which replays the cards it
+//! chooses with the rules engine of this crate: a wrong encoding or a broken model cannot go
+//! unnoticed.
+//!
+//! Tasks are encoded by [`BaseTask::to_cnf`], from the building blocks of [`Encoder`]. As with
+//! [`State::play`], the cards left over with 3 players are never played.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    ops::Not,
+};
+
+use thiserror::Error;
+
+use crate::{
+    card::{BLUE, Card, GREEN, NB_CARDS, PINK, SUBMARINE, YELLOW},
+    state::{GameError, State},
+    task::{BaseTask, Task, TaskStatus},
+};
+
+/// A literal of the formula, or a constant when its value is already known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lit {
+    Const(bool),
+    /// A DIMACS literal: a variable, negated if negative.
+    Var(i32),
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        match self {
+            Lit::Const(b) => Lit::Const(!b),
+            Lit::Var(v) => Lit::Var(-v),
+        }
+    }
+}
+
+impl From<bool> for Lit {
+    fn from(value: bool) -> Self {
+        Lit::Const(value)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CnfError {
+    #[error("Task `{0}` cannot be encoded in CNF.")]
+    UnsupportedTask(String),
+    #[error("The SAT solver found the mission unsatisfiable.")]
+    Unsatisfiable,
+    #[error("Invalid model: {0}.")]
+    InvalidModel(String),
+    #[error(transparent)]
+    Game(#[from] GameError),
+}
+
+/// A sum of weighted literals in unary: [`Sum::at_least`] gives a literal for every bound.
+#[derive(Debug, Clone)]
+pub struct Sum {
+    /// `ge[j - 1]` is true if and only if the sum is at least `j`.
+    ge: Vec<Lit>,
+}
+
+impl Sum {
+    /// The largest value the sum may take.
+    pub fn max(&self) -> usize {
+        self.ge.len()
+    }
+
+    /// True if the sum is at least `j`.
+    pub fn at_least(&self, j: usize) -> Lit {
+        match j {
+            0 => Lit::Const(true),
+            j => self.ge.get(j - 1).copied().unwrap_or(Lit::Const(false)),
+        }
+    }
+}
+
+/// A card which may be played in a trick, by `seat`, if `lit` is true.
+#[derive(Debug, Clone, Copy)]
+pub struct Play {
+    pub card: Card,
+    pub seat: usize,
+    pub lit: Lit,
+}
+
+/// One of the tricks left, the current one included.
+#[derive(Debug, Clone)]
+pub struct FutureTrick {
+    pub idx: usize,
+    pub plays: Vec<Play>,
+    /// For every seat, true if it leads the trick.
+    pub leaders: Vec<Lit>,
+    /// For every seat, true if it wins the trick.
+    pub winners: Vec<Lit>,
+}
+
+impl FutureTrick {
+    /// True if `card` is played in this trick.
+    pub fn play(&self, card: &Card) -> Lit {
+        self.plays
+            .iter()
+            .find(|p| p.card == *card)
+            .map_or(Lit::Const(false), |p| p.lit)
+    }
+}
+
+/// Builds the clauses of a mission: the rules of the game, then the tasks.
+pub struct Encoder<'a> {
+    state: &'a State,
+    n_vars: i32,
+    clauses: Vec<Vec<i32>>,
+    /// Cards of the tricks already won, with the seat which won them.
+    won_before: HashMap<Card, usize>,
+    tricks: Vec<FutureTrick>,
+    won: HashMap<(Card, usize), Lit>,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(state: &'a State) -> Self {
+        let mut won_before = HashMap::new();
+        for (ip, p) in state.get_players().iter().enumerate() {
+            for trick in p.get_tricks() {
+                won_before.extend(trick.iter().map(|c| (*c, ip)));
+            }
+        }
+        let mut encoder = Encoder {
+            state,
+            n_vars: 0,
+            clauses: vec![],
+            won_before,
+            tricks: vec![],
+            won: HashMap::new(),
+        };
+        encoder.encode_rules();
+        encoder
+    }
+
+    fn encode_rules(&mut self) {
+        let state = self.state;
+        let n_players = state.n_players();
+        let current = state.get_current_trick();
+        let first = current.get_first_player();
+        let played_current: Vec<usize> = (0..current.len())
+            .map(|j| (first + j) % n_players)
+            .collect();
+        let hands: Vec<Vec<Card>> = state
+            .get_players()
+            .iter()
+            .map(|p| p.get_hand().to_vec())
+            .collect();
+
+        let mut leaders: Vec<Lit> = (0..n_players).map(|ip| (ip == first).into()).collect();
+        for t in 0..state.n_tricks_left() {
+            let mut plays = vec![];
+            if t == 0 {
+                for (j, card) in current.iter().enumerate() {
+                    let seat = (first + j) % n_players;
+                    let lit = Lit::Const(true);
+                    plays.push(Play {
+                        card: *card,
+                        seat,
+                        lit,
+                    });
+                }
+            }
+            for (seat, hand) in hands.iter().enumerate() {
+                if t == 0 && played_current.contains(&seat) {
+                    continue;
+                }
+                let lits: Vec<Lit> = hand.iter().map(|_| self.var()).collect();
+                self.exactly_one(&lits);
+                for (card, lit) in hand.iter().zip(lits) {
+                    plays.push(Play {
+                        card: *card,
+                        seat,
+                        lit,
+                    });
+                }
+            }
+            let mut trick = FutureTrick {
+                idx: current.idx() + t,
+                plays,
+                leaders,
+                winners: vec![],
+            };
+            self.encode_trick(&mut trick, t == 0 && !current.is_empty());
+            leaders = trick.winners.clone();
+            self.tricks.push(trick);
+        }
+
+        // Each card is played at most once
+        for card in hands.iter().flatten() {
+            let lits: Vec<Lit> = self.tricks.iter().map(|t| t.play(card)).collect();
+            self.at_most_one(&lits);
+        }
+    }
+
+    /// Adds the follow-suit rule to `trick`, and finds its winner.
+    fn encode_trick(&mut self, trick: &mut FutureTrick, started: bool) {
+        let state = self.state;
+        let mut lead = vec![];
+        for color in [PINK, GREEN, BLUE, YELLOW, SUBMARINE] {
+            let color = color(1);
+            let lit = if started {
+                Lit::Const(trick.plays[0].card.same_color(&color))
+            } else {
+                let leads: Vec<Lit> = trick
+                    .plays
+                    .iter()
+                    .filter(|p| p.card.same_color(&color))
+                    .map(|p| self.and([p.lit, trick.leaders[p.seat]]))
+                    .collect();
+                self.or(leads)
+            };
+            lead.push((color, lit));
+        }
+        let lead = |card: &Card| lead.iter().find(|(c, _)| c.same_color(card)).unwrap().1;
+
+        // A card of another color can only be played once the cards of the color led are out
+        let t = self.tricks.len();
+        for p in trick.plays.iter().filter(|p| matches!(p.lit, Lit::Var(_))) {
+            let hand = state.get_player(p.seat).get_hand();
+            for other in hand.iter().filter(|c| !c.same_color(&p.card)) {
+                let mut clause = vec![!p.lit, !lead(other)];
+                clause.extend(self.tricks[..t].iter().map(|t| t.play(other)));
+                self.clause(clause);
+            }
+        }
+
+        let mut winners = vec![vec![]; trick.leaders.len()];
+        for p in &trick.plays {
+            let mut wins = vec![p.lit];
+            if !p.card.is_submarine() {
+                wins.push(lead(&p.card));
+            }
+            for other in &trick.plays {
+                if other.seat != p.seat && beats(&other.card, &p.card) {
+                    wins.push(!other.lit);
+                }
+            }
+            let wins = self.and(wins);
+            winners[p.seat].push(wins);
+        }
+        trick.winners = winners.into_iter().map(|w| self.or(w)).collect();
+    }
+
+    /// The state being encoded.
+    pub fn state(&self) -> &'a State {
+        self.state
+    }
+
+    /// The tricks left, the current one included.
+    pub fn tricks(&self) -> &[FutureTrick] {
+        &self.tricks
+    }
+
+    /// The index of the first trick not played, once the game is over.
+    pub fn final_trick_idx(&self) -> usize {
+        self.state.get_current_trick().idx() + self.tricks.len()
+    }
+
+    /// Returns true if the game is over once every trick left is won, see [`State::game_is_over`].
+    pub fn game_is_over(&self) -> bool {
+        let n_players = self.state.n_players();
+        let n_played = self.won_before.len() + self.tricks.len() * n_players;
+        n_played == NB_CARDS - NB_CARDS % n_players
+    }
+
+    /// True if player `ip` ends up with `card` in its tricks.
+    pub fn won(&mut self, card: &Card, ip: usize) -> Lit {
+        if let Some(&winner) = self.won_before.get(card) {
+            return (winner == ip).into();
+        }
+        if let Some(&lit) = self.won.get(&(*card, ip)) {
+            return lit;
+        }
+        let plays: Vec<(Lit, Lit)> = self
+            .tricks
+            .iter()
+            .map(|t| (t.play(card), t.winners[ip]))
+            .collect();
+        let wins: Vec<Lit> = plays.into_iter().map(|(p, w)| self.and([p, w])).collect();
+        let lit = self.or(wins);
+        self.won.insert((*card, ip), lit);
+        lit
+    }
+
+    /// True if `card` ends up in a trick, whoever wins it.
+    pub fn played(&mut self, card: &Card) -> Lit {
+        if self.won_before.contains_key(card) {
+            return Lit::Const(true);
+        }
+        let plays: Vec<Lit> = self.tricks.iter().map(|t| t.play(card)).collect();
+        self.or(plays)
+    }
+
+    /// True if player `ip` wins the trick of index `idx`.
+    pub fn trick_won(&self, idx: usize, ip: usize) -> Lit {
+        let current = self.state.get_current_trick().idx();
+        if idx < current {
+            let tricks = self.state.get_player(ip).get_tricks();
+            return tricks.iter().any(|t| t.idx() == idx).into();
+        }
+        self.tricks
+            .get(idx - current)
+            .map_or(Lit::Const(false), |t| t.winners[ip])
+    }
+
+    /// The number of tricks won by player `ip` at the end of the game.
+    pub fn n_tricks_won(&mut self, ip: usize) -> Sum {
+        let lits: Vec<Lit> = (0..self.final_trick_idx())
+            .map(|idx| self.trick_won(idx, ip))
+            .collect();
+        self.count(lits)
+    }
+
+    /// True if the task is done at the end of the game.
+    pub fn task(&mut self, task: &Task, ip: usize) -> Result<Lit, CnfError> {
+        match task.eval(self.state, ip) {
+            TaskStatus::Done => Ok(Lit::Const(true)),
+            TaskStatus::Failed => Ok(Lit::Const(false)),
+            TaskStatus::Unknown => task.to_cnf(self, ip),
+        }
+    }
+
+    /// A new variable.
+    pub fn var(&mut self) -> Lit {
+        self.n_vars += 1;
+        Lit::Var(self.n_vars)
+    }
+
+    /// Requires one of the literals to be true.
+    pub fn clause<I>(&mut self, lits: I)
+    where
+        I: IntoIterator<Item = Lit>,
+    {
+        let mut clause = vec![];
+        for lit in lits {
+            match lit {
+                Lit::Const(true) => return,
+                Lit::Const(false) => (),
+                Lit::Var(v) => clause.push(v),
+            }
+        }
+        self.clauses.push(clause);
+    }
+
+    fn at_most_one(&mut self, lits: &[Lit]) {
+        for (i, &a) in lits.iter().enumerate() {
+            for &b in &lits[i + 1..] {
+                self.clause([!a, !b]);
+            }
+        }
+    }
+
+    fn exactly_one(&mut self, lits: &[Lit]) {
+        self.clause(lits.iter().copied());
+        self.at_most_one(lits);
+    }
+
+    /// True if all the literals are.
+    pub fn and<I>(&mut self, lits: I) -> Lit
+    where
+        I: IntoIterator<Item = Lit>,
+    {
+        let mut vars = vec![];
+        for lit in lits {
+            match lit {
+                Lit::Const(true) => (),
+                Lit::Const(false) => return Lit::Const(false),
+                Lit::Var(v) if vars.contains(&-v) => return Lit::Const(false),
+                Lit::Var(v) if !vars.contains(&v) => vars.push(v),
+                Lit::Var(_) => (),
+            }
+        }
+        match vars[..] {
+            [] => Lit::Const(true),
+            [v] => Lit::Var(v),
+            _ => {
+                let and = self.var();
+                for &v in &vars {
+                    self.clause([!and, Lit::Var(v)]);
+                }
+                self.clause([and].into_iter().chain(vars.iter().map(|&v| Lit::Var(-v))));
+                and
+            }
+        }
+    }
+
+    /// True if one of the literals is.
+    pub fn or<I>(&mut self, lits: I) -> Lit
+    where
+        I: IntoIterator<Item = Lit>,
+    {
+        let lits: Vec<Lit> = lits.into_iter().map(|l| !l).collect();
+        !self.and(lits)
+    }
+
+    /// True if both literals have the same value.
+    pub fn iff(&mut self, a: Lit, b: Lit) -> Lit {
+        let a_to_b = self.or([!a, b]);
+        let b_to_a = self.or([a, !b]);
+        self.and([a_to_b, b_to_a])
+    }
+
+    /// The sum of the weights of the true literals.
+    pub fn sum<I>(&mut self, items: I) -> Sum
+    where
+        I: IntoIterator<Item = (Lit, usize)>,
+    {
+        let mut sum = Sum { ge: vec![] };
+        for (lit, weight) in items {
+            if weight == 0 || lit == Lit::Const(false) {
+                continue;
+            }
+            let ge = if lit == Lit::Const(true) {
+                let mut ge = vec![Lit::Const(true); weight];
+                ge.extend(sum.ge);
+                ge
+            } else {
+                // The sum reaches j if it already did, or if it reaches j - weight and lit is true
+                (1..=sum.max() + weight)
+                    .map(|j| {
+                        let added = self.and([lit, sum.at_least(j.saturating_sub(weight))]);
+                        self.or([sum.at_least(j), added])
+                    })
+                    .collect()
+            };
+            sum = Sum { ge };
+        }
+        sum
+    }
+
+    /// The number of true literals.
+    pub fn count<I>(&mut self, lits: I) -> Sum
+    where
+        I: IntoIterator<Item = Lit>,
+    {
+        self.sum(lits.into_iter().map(|l| (l, 1)))
+    }
+
+    /// True if the sum is exactly `j`.
+    pub fn exactly(&mut self, sum: &Sum, j: usize) -> Lit {
+        self.and([sum.at_least(j), !sum.at_least(j + 1)])
+    }
+
+    /// True if `a` is greater than `b`.
+    pub fn greater(&mut self, a: &Sum, b: &Sum) -> Lit {
+        let lits: Vec<Lit> = (0..=b.max())
+            .map(|j| self.or([!b.at_least(j), a.at_least(j + 1)]))
+            .collect();
+        self.and(lits)
+    }
+
+    /// True if `a` and `b` are equal.
+    pub fn equal(&mut self, a: &Sum, b: &Sum) -> Lit {
+        let lits: Vec<Lit> = (1..=a.max().max(b.max()))
+            .map(|j| self.iff(a.at_least(j), b.at_least(j)))
+            .collect();
+        self.and(lits)
+    }
+}
+
+/// Returns true if `card` wins the trick over `other` when both are played.
+fn beats(card: &Card, other: &Card) -> bool {
+    if card.is_submarine() {
+        !other.is_submarine() || card.val() > other.val()
+    } else {
+        card.same_color(other) && card.val() > other.val()
+    }
+}
+
+/// A mission encoded in CNF, see [`State::to_cnf`].
+#[derive(Debug, Clone)]
+pub struct Cnf {
+    state: State,
+    n_vars: usize,
+    clauses: Vec<Vec<i32>>,
+    /// The variable of every card in every trick left, by trick index.
+    plays: HashMap<(usize, Card), i32>,
+}
+
+impl State {
+    /// Encodes the rest of the game and the tasks of all players as a SAT problem: the
+    /// formula is satisfiable if and only if the mission can be completed, like
+    /// [`State::play`] finds. Tasks without an encoding, such as [`Task::Not`], are rejected.
+    pub fn to_cnf(&self) -> Result<Cnf, CnfError> {
+        self.validate()?;
+        let mut encoder = Encoder::new(self);
+        for (ip, p) in self.get_players().iter().enumerate() {
+            for task in p.get_tasks() {
+                let lit = encoder.task(task, ip)?;
+                encoder.clause([lit]);
+            }
+        }
+
+        let mut plays = HashMap::new();
+        for trick in &encoder.tricks {
+            for p in &trick.plays {
+                if let Lit::Var(v) = p.lit {
+                    plays.insert((trick.idx, p.card), v);
+                }
+            }
+        }
+        Ok(Cnf {
+            state: self.clone(),
+            n_vars: encoder.n_vars as usize,
+            clauses: encoder.clauses,
+            plays,
+        })
+    }
+}
+
+impl Cnf {
+    pub fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    pub fn clauses(&self) -> &[Vec<i32>] {
+        &self.clauses
+    }
+
+    /// Writes the formula in the DIMACS format read by SAT solvers. Comment lines
+    /// `c play <trick> <card> <variable>` tell which variable plays each card.
+    pub fn to_dimacs(&self) -> String {
+        let mut text = String::new();
+        let n_players = self.state.n_players();
+        let first = self.state.get_current_trick().idx();
+        let n_tricks = self.state.n_tricks_left();
+        writeln!(
+            text,
+            "c The Crew: Deep Sea mission with {} players",
+            n_players
+        )
+        .unwrap();
+        writeln!(text, "c {} tricks left from trick {}", n_tricks, first).unwrap();
+        let mut plays: Vec<(&(usize, Card), &i32)> = self.plays.iter().collect();
+        plays.sort_by_key(|(_, v)| **v);
+        for ((idx, card), v) in plays {
+            writeln!(text, "c play {} {} {}", idx, card, v).unwrap();
+        }
+        writeln!(text, "p cnf {} {}", self.n_vars, self.clauses.len()).unwrap();
+        for clause in &self.clauses {
+            for lit in clause {
+                write!(text, "{} ", lit).unwrap();
+            }
+            writeln!(text, "0").unwrap();
+        }
+        text
+    }
+
+    /// Reads the cards played in a model of the formula, given as its true and false
+    /// literals. Cards are played with the rules engine, which also checks the mission is
+    /// completed at the end.
+    pub fn decode(&self, model: &[i32]) -> Result<Vec<Card>, CnfError> {
+        let model: HashSet<i32> = model.iter().copied().filter(|&l| l > 0).collect();
+        let mut state = self.state.clone();
+        let mut cards = vec![];
+        while state.n_tricks_left() > 0 {
+            let idx = state.get_current_trick().idx();
+            let ip = state.get_current_player_idx();
+            let hand = state.get_current_player().get_hand();
+            let played: Vec<Card> = hand
+                .iter()
+                .filter(|c| {
+                    self.plays
+                        .get(&(idx, **c))
+                        .is_some_and(|v| model.contains(v))
+                })
+                .copied()
+                .collect();
+            let [card] = played[..] else {
+                return Err(CnfError::InvalidModel(format!(
+                    "player {} plays {} cards in trick {}",
+                    ip,
+                    played.len(),
+                    idx
+                )));
+            };
+            if !hand
+                .playable_cards(state.get_current_trick().first())
+                .contains(&card)
+            {
+                return Err(CnfError::InvalidModel(format!(
+                    "player {} cannot play {} in trick {}",
+                    ip, card, idx
+                )));
+            }
+            state.play_card(&card)?;
+            cards.push(card);
+        }
+        if state.game_status() != TaskStatus::Done {
+            return Err(CnfError::InvalidModel(
+                "the mission is not completed".into(),
+            ));
+        }
+        Ok(cards)
+    }
+}
+
+/// Reads the model printed by a SAT solver in the usual competition format: a `s` status line
+/// and `v` lines of literals. Lines of bare literals are accepted too.
+pub fn parse_model(output: &str) -> Result<Vec<i32>, CnfError> {
+    let mut model = vec![];
+    for line in output.lines().map(str::trim) {
+        if let Some(status) = line.strip_prefix('s') {
+            if status.trim() == "UNSATISFIABLE" {
+                return Err(CnfError::Unsatisfiable);
+            }
+            continue;
+        }
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        for token in line.strip_prefix('v').unwrap_or(line).split_whitespace() {
+            match token.parse::<i32>() {
+                Ok(0) => (),
+                Ok(lit) => model.push(lit),
+                Err(_) => {
+                    return Err(CnfError::InvalidModel(format!(
+                        "unexpected {:?} in the solver output",
+                        token
+                    )));
+                }
+            }
+        }
+    }
+    Ok(model)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use rand::{
+        Rng, SeedableRng,
+        rngs::StdRng,
+        seq::{IndexedRandom, SliceRandom},
+    };
+
+    use super::*;
+    use crate::{
+        card::{COLORS, SUBMARINE, blue, deck, green, pink, submarine},
+        catalog,
+        player::Player,
+        task::{
+            all_of::TaskAllOf, any_of::TaskAnyOf, at_least::TaskAtLeast,
+            dont_open_trick_with::TaskDontOpenTrickWith, dont_win_cards::TaskDontWinCards,
+            dont_win_consecutive_tricks::TaskDontWinConsecutiveTricks,
+            dont_win_tricks::TaskDontWinTricks, not::TaskNot,
+            win_all_cards_color::TaskWinAllCardsColor, win_cards::TaskWinCards,
+            win_cards_amount_color::TaskWinCardsAmountColor,
+            win_cards_amount_number::TaskWinCardsAmountNumber,
+            win_consecutive_tricks::TaskWinConsecutiveTricks,
+            win_more_cards_color::TaskWinMoreCardsColor, win_more_tricks::TaskWinMoreTricks,
+            win_nb_tricks::TaskWinNbTricks,
+            win_nb_tricks_compared_captain::TaskWinNbTricksComparedCaptain,
+            win_specific_submarine::TaskWinSpecificSubmarine, win_trick_with::TaskWinTrickWith,
+            win_trick_with_pred::TaskWinTrickWithPred, win_tricks::TaskWinTricks,
+        },
+    };
+
+    /// A plain DPLL with unit propagation, enough for a few tricks.
+    fn solve(cnf: &Cnf) -> Option<Vec<i32>> {
+        let mut values = vec![None; cnf.n_vars() + 1];
+        if !dpll(cnf.clauses(), &mut values) {
+            return None;
+        }
+        let model = (1..values.len()).map(|v| match values[v] {
+            Some(true) => v as i32,
+            _ => -(v as i32),
+        });
+        Some(model.collect())
+    }
+
+    fn dpll(clauses: &[Vec<i32>], values: &mut [Option<bool>]) -> bool {
+        let value = |values: &[Option<bool>], l: i32| {
+            values[l.unsigned_abs() as usize].map(|b| b == (l > 0))
+        };
+        let mut assigned = vec![];
+        let mut conflict = false;
+        let mut propagate = true;
+        while propagate && !conflict {
+            propagate = false;
+            for clause in clauses {
+                if clause.iter().any(|&l| value(values, l) == Some(true)) {
+                    continue;
+                }
+                let mut free = clause.iter().filter(|&&l| value(values, l).is_none());
+                match (free.next(), free.next()) {
+                    (None, _) => {
+                        conflict = true;
+                        break;
+                    }
+                    (Some(&l), None) => {
+                        values[l.unsigned_abs() as usize] = Some(l > 0);
+                        assigned.push(l.unsigned_abs() as usize);
+                        propagate = true;
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        if !conflict {
+            let Some(v) = (1..values.len()).find(|&v| values[v].is_none()) else {
+                return true;
+            };
+            for b in [true, false] {
+                values[v] = Some(b);
+                if dpll(clauses, values) {
+                    return true;
+                }
+            }
+            values[v] = None;
+        }
+        for v in assigned {
+            values[v] = None;
+        }
+        false
+    }
+
+    fn random_task(rng: &mut StdRng, cards: &[Card], depth: usize) -> Task {
+        let card = *cards.choose(rng).unwrap();
+        let color = *COLORS.choose(rng).unwrap();
+        let value = rng.random_range(1..10);
+        match rng.random_range(0..if depth > 0 { 21 } else { 18 }) {
+            0 => TaskWinCards::new(None, [card]).into(),
+            1 => TaskWinCards::new(None, cards.choose_multiple(rng, 2).copied()).into(),
+            2 => TaskDontWinCards::new(None, [card]).into(),
+            3 => TaskWinNbTricks::new(None, rng.random_range(0..4)).into(),
+            4 => TaskWinTricks::new(None, [rng.random_range(0..3)], false, rng.random()).into(),
+            5 => TaskDontWinTricks::new_n_first_tricks(None, rng.random_range(1..3)).into(),
+            6 => TaskDontWinTricks::new_any(None).into(),
+            7 => TaskWinTrickWith::new(None, card.val(), rng.random::<bool>().then_some(value))
+                .into(),
+            8 => match rng.random_range(0..10) {
+                0 => TaskWinTrickWithPred::new_even(None),
+                1 => TaskWinTrickWithPred::new_odd(None),
+                2 => TaskWinTrickWithPred::new_greater(None, value),
+                3 => TaskWinTrickWithPred::new_lower(None, value),
+                4 => TaskWinTrickWithPred::new_total_greater(None, 2 * value),
+                5 => TaskWinTrickWithPred::new_total_lower(None, 2 * value),
+                6 => TaskWinTrickWithPred::new_total_in(None, [2 * value, 2 * value + 1]),
+                7 => TaskWinTrickWithPred::new_same_nb_of_colors(None, pink, green),
+                8 => TaskWinTrickWithPred::new_win_card_with_submarine(None, card),
+                _ => TaskWinTrickWithPred::new_win_card_last_trick(None, card, 3),
+            }
+            .into(),
+            9 => match rng.random_range(0..3) {
+                0 => TaskWinMoreTricks::new(None, true, false),
+                1 => TaskWinMoreTricks::new(None, false, true),
+                _ => TaskWinMoreTricks::new(None, false, false),
+            }
+            .into(),
+            10 => TaskWinConsecutiveTricks::new(None, rng.random_range(1..3), rng.random()).into(),
+            11 => TaskDontWinConsecutiveTricks::new(None).into(),
+            12 => TaskWinSpecificSubmarine::new(None, rng.random_range(1..5)).into(),
+            13 => {
+                let color = *[color, SUBMARINE].choose(rng).unwrap();
+                TaskWinCardsAmountColor::new(None, rng.random(), [(color, rng.random_range(0..3))])
+                    .into()
+            }
+            14 => {
+                TaskWinCardsAmountNumber::new(None, rng.random(), [(value, rng.random_range(0..3))])
+                    .into()
+            }
+            15 => TaskWinMoreCardsColor::new(None, color, blue, rng.random()).into(),
+            16 => {
+                let comp = *[Ordering::Less, Ordering::Equal, Ordering::Greater]
+                    .choose(rng)
+                    .unwrap();
+                TaskWinNbTricksComparedCaptain::new(None, comp).into()
+            }
+            17 => match rng.random() {
+                true => TaskDontOpenTrickWith::new(None, [color]).into(),
+                false => TaskWinAllCardsColor::new(None).into(),
+            },
+            i => {
+                let tasks = [(); 2].map(|_| random_task(rng, cards, depth - 1));
+                match i {
+                    18 => TaskAllOf::new(None, tasks).into(),
+                    19 => TaskAnyOf::new(None, tasks).into(),
+                    _ => TaskAtLeast::new(None, 1, tasks).into(),
+                }
+            }
+        }
+    }
+
+    /// Three players with three cards each and random tasks, possibly in the middle of the
+    /// game.
+    fn random_state(rng: &mut StdRng) -> State {
+        loop {
+            let mut cards: Vec<Card> = deck().into_iter().filter(|c| *c != submarine(4)).collect();
+            cards.shuffle(rng);
+            cards.truncate(8);
+            cards.push(submarine(4));
+            cards.shuffle(rng);
+
+            let mut players: Vec<Player> = cards
+                .chunks(3)
+                .map(|c| Player::new(c.to_vec().into()))
+                .collect();
+            for _ in 0..rng.random_range(1..4) {
+                let task = random_task(rng, &cards, 1);
+                let ip = rng.random_range(0..3);
+                let _ = players[ip].add_task(task);
+            }
+            let mut state = State::new(players);
+            if state.validate().is_err() {
+                continue;
+            }
+            for _ in 0..rng.random_range(0..5) {
+                let playable = state
+                    .get_current_player()
+                    .get_hand()
+                    .playable_cards(state.get_current_trick().first());
+                state.play_card(playable.choose(rng).unwrap()).unwrap();
+            }
+            return state;
+        }
+    }
+
+    #[test]
+    fn same_as_solver() {
+        let mut rng = StdRng::seed_from_u64(48);
+        let mut n_feasible = 0;
+        for _ in 0..400 {
+            let state = random_state(&mut rng);
+            let cnf = state.to_cnf().unwrap();
+            let model = solve(&cnf);
+            let feasible = state.clone().play().is_ok();
+            assert_eq!(model.is_some(), feasible, "\n{}", state);
+
+            if let Some(model) = model {
+                n_feasible += 1;
+                let cards = cnf.decode(&model).unwrap();
+                let mut replayed = state.clone();
+                for card in &cards {
+                    replayed.play_card(card).unwrap();
+                }
+                assert_eq!(replayed.n_tricks_left(), 0);
+                assert_eq!(replayed.game_status(), TaskStatus::Done);
+            }
+        }
+        // Both outcomes are covered
+        assert!((50..350).contains(&n_feasible), "{}", n_feasible);
+    }
+
+    #[test]
+    fn dimacs() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut state = State::new_random_with_rng(4, &mut rng);
+        for (ip, entry) in catalog::entries().choose_multiple(&mut rng, 6).enumerate() {
+            let _ = state.get_mut_player(ip % 4).add_task(entry.build(4));
+        }
+        let cnf = state.to_cnf().unwrap();
+        let dimacs = cnf.to_dimacs();
+        let header = format!("p cnf {} {}", cnf.n_vars(), cnf.clauses().len());
+        let mut lines = dimacs.lines().skip_while(|l| l.starts_with('c'));
+        assert_eq!(lines.next(), Some(header.as_str()));
+        assert_eq!(lines.count(), cnf.clauses().len());
+        // Every card of every hand may be played in any of the 10 tricks
+        let n_plays = dimacs.lines().filter(|l| l.starts_with("c play ")).count();
+        assert_eq!(n_plays, 400);
+        assert!(dimacs.contains("\nc play 0 S4 "));
+    }
+
+    #[test]
+    fn decode() {
+        let players = [
+            Player::new([blue(1), green(7)].into()),
+            Player::new([submarine(4), green(3)].into()),
+            Player::new([green(5), blue(2)].into()),
+        ];
+        let mut state = State::new(players);
+        state
+            .get_mut_player(0)
+            .add_task(TaskWinCards::new(None, [green(5)]))
+            .unwrap();
+        let cnf = state.to_cnf().unwrap();
+        let cards = cnf.decode(&solve(&cnf).unwrap()).unwrap();
+        // Player 0 wins G5 with G7, once the captain leads G3
+        let mut replayed = state.clone();
+        for card in &cards {
+            replayed.play_card(card).unwrap();
+        }
+        let tricks = replayed.get_player(0).get_tricks();
+        assert!(tricks[0].contains(&green(5)) && tricks[0].contains(&green(3)));
+
+        let none: Vec<i32> = (1..=cnf.n_vars() as i32).map(|v| -v).collect();
+        assert!(matches!(
+            cnf.decode(&none),
+            Err(CnfError::InvalidModel(e)) if e == "player 1 plays 0 cards in trick 0"
+        ));
+
+        state
+            .get_mut_player(2)
+            .add_task(TaskNot::new(None, TaskWinNbTricks::new(None, 1)))
+            .unwrap();
+        assert!(matches!(
+            state.to_cnf(),
+            Err(CnfError::UnsupportedTask(t)) if t == "not (win exactly 1 tricks)"
+        ));
+    }
+
+    #[test]
+    fn parse() {
+        let output = "c comment\ns SATISFIABLE\nv 1 -2 3\nv -4 0\n";
+        assert_eq!(parse_model(output).unwrap(), [1, -2, 3, -4]);
+        assert_eq!(parse_model("1 -2 0").unwrap(), [1, -2]);
+        assert!(matches!(
+            parse_model("s UNSATISFIABLE\n"),
+            Err(CnfError::Unsatisfiable)
+        ));
+        assert!(matches!(
+            parse_model("v 1 x 0"),
+            Err(CnfError::InvalidModel(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Deep Sea's communication.
+//!
+//! Once per game, at the start of a trick, a player may reveal one card of its hand (but not a
+//! submarine) and state that it is its highest, its lowest or its only card of that color.
+use std::fmt::Display;
+
+use crate::{
+    card::Card,
+    state::{GameError, State},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommunicationKind {
+    Highest,
+    Only,
+    Lowest,
+}
+
+impl Display for CommunicationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommunicationKind::Highest => write!(f, "highest"),
+            CommunicationKind::Only => write!(f, "only"),
+            CommunicationKind::Lowest => write!(f, "lowest"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Communication {
+    pub seat: usize,
+    pub card: Card,
+    pub kind: CommunicationKind,
+}
+
+impl Communication {
+    /// Returns true if `hand` may be the hand of the player who communicated. As hands only
+    /// shrink, this stays true for the rest of the game.
+    pub fn allows(&self, hand: &[Card]) -> bool {
+        hand.iter()
+            .filter(|c| **c != self.card && c.same_color(&self.card))
+            .all(|c| self.allows_other(c))
+    }
+
+    /// Returns true if the player who communicated may hold `card` besides the communicated one.
+    pub fn allows_other(&self, card: &Card) -> bool {
+        if *card == self.card || !card.same_color(&self.card) {
+            return true;
+        }
+        match self.kind {
+            CommunicationKind::Highest => card.val() < self.card.val(),
+            CommunicationKind::Only => false,
+            CommunicationKind::Lowest => card.val() > self.card.val(),
+        }
+    }
+}
+
+impl State {
+    /// Records a communication, checking that it follows the rules and tells the truth.
+    pub fn communicate(&mut self, communication: Communication) -> Result<(), GameError> {
+        let Communication { seat, card, .. } = communication;
+        if seat >= self.n_players() {
+            return Err(GameError::InvalidSeat(seat));
+        }
+        if self.get_communications().iter().any(|c| c.seat == seat) {
+            return Err(GameError::AlreadyCommunicated(seat));
+        }
+        if !self.get_current_trick().is_empty() {
+            return Err(GameError::CommunicationDuringTrick);
+        }
+        if card.is_submarine() {
+            return Err(GameError::CannotCommunicateSubmarine(card));
+        }
+        let hand = self.get_player(seat).get_hand();
+        if !hand.contains(&card) {
+            return Err(GameError::CardNotFound(card, hand.to_vec()));
+        }
+        if !communication.allows(hand) {
+            return Err(GameError::FalseCommunication(card, hand.to_vec()));
+        }
+
+        self.get_mut_communications().push(communication);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::player::Player;
+
+    use super::*;
+
+    fn state() -> State {
+        let p0 = Player::new([Card::Submarine(4), Card::Blue(5), Card::Blue(2)].into());
+        let p1 = Player::new([Card::Pink(1), Card::Blue(3), Card::Yellow(3)].into());
+        let p2 = Player::new([Card::Green(1), Card::Green(2), Card::Pink(7)].into());
+        State::new([p0, p1, p2])
+    }
+
+    fn comm(seat: usize, card: Card, kind: CommunicationKind) -> Communication {
+        Communication { seat, card, kind }
+    }
+
+    #[test]
+    fn test_communicate() {
+        let mut s = state();
+        assert!(
+            s.communicate(comm(0, Card::Blue(5), CommunicationKind::Highest))
+                .is_ok()
+        );
+        assert!(matches!(
+            s.communicate(comm(0, Card::Blue(2), CommunicationKind::Lowest)),
+            Err(GameError::AlreadyCommunicated(0))
+        ));
+        assert!(
+            s.communicate(comm(1, Card::Yellow(3), CommunicationKind::Only))
+                .is_ok()
+        );
+        assert_eq!(s.get_communications().len(), 2);
+    }
+
+    #[test]
+    fn test_communicate_invalid() {
+        let mut s = state();
+        assert!(matches!(
+            s.communicate(comm(0, Card::Blue(5), CommunicationKind::Only)),
+            Err(GameError::FalseCommunication(_, _))
+        ));
+        assert!(matches!(
+            s.communicate(comm(0, Card::Blue(5), CommunicationKind::Lowest)),
+            Err(GameError::FalseCommunication(_, _))
+        ));
+        assert!(matches!(
+            s.communicate(comm(0, Card::Submarine(4), CommunicationKind::Only)),
+            Err(GameError::CannotCommunicateSubmarine(_))
+        ));
+        assert!(matches!(
+            s.communicate(comm(0, Card::Pink(1), CommunicationKind::Only)),
+            Err(GameError::CardNotFound(_, _))
+        ));
+        assert!(matches!(
+            s.communicate(comm(7, Card::Blue(5), CommunicationKind::Only)),
+            Err(GameError::InvalidSeat(7))
+        ));
+
+        s.play_card(&Card::Blue(2)).unwrap();
+        assert!(matches!(
+            s.communicate(comm(1, Card::Pink(1), CommunicationKind::Only)),
+            Err(GameError::CommunicationDuringTrick)
+        ));
+    }
+}
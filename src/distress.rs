@@ -0,0 +1,249 @@
+//! Deep Sea's distress signal.
+//!
+//! Before the first trick, the team may call a distress signal: every player passes one card to
+//! the neighbor in a direction chosen by the team (submarines cannot be passed). This makes the
+//! mission count as harder, so it is only worth it if the mission is otherwise infeasible.
+use crate::{
+    card::Card,
+    state::{GameError, State},
+};
+
+/// Direction in which cards are passed. Players are seated in play order, so the left neighbor
+/// of player `i` is player `i + 1` (the next one to play) and the right neighbor is player `i - 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn neighbor(&self, ip: usize, n_players: usize) -> usize {
+        match self {
+            Direction::Left => (ip + 1) % n_players,
+            Direction::Right => (ip + n_players - 1) % n_players,
+        }
+    }
+}
+
+/// A distress pass: `passes[i]` is the card given by player `i` to its neighbor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distress {
+    pub direction: Direction,
+    pub passes: Vec<Card>,
+}
+
+impl State {
+    /// Applies a distress signal: every player gives one card to its neighbor in `direction`.
+    /// This is only allowed before the first card of the game is played.
+    pub fn apply_distress(&mut self, distress: &Distress) -> Result<(), GameError> {
+        let n_players = self.n_players();
+        let started = self.get_current_trick().idx() > 0
+            || !self.get_current_trick().is_empty()
+            || self
+                .get_players()
+                .iter()
+                .any(|p| !p.get_tricks().is_empty());
+        if started {
+            return Err(GameError::DistressAfterStart);
+        }
+        if distress.passes.len() != n_players {
+            return Err(GameError::InvalidDistressSize(
+                n_players,
+                distress.passes.len(),
+            ));
+        }
+
+        // Every pass is checked before any hand changes, so that an invalid signal leaves the
+        // game as it was.
+        for (ip, card) in distress.passes.iter().enumerate() {
+            if card.is_submarine() {
+                return Err(GameError::CannotPassSubmarine(*card));
+            }
+            let hand = self.get_player(ip).get_hand();
+            if !hand.contains(card) {
+                return Err(GameError::CardNotFound(*card, hand.to_vec()));
+            }
+        }
+        // All cards are removed first so that a card received cannot be passed again.
+        for (ip, card) in distress.passes.iter().enumerate() {
+            self.get_mut_player(ip).remove_card_from_hand(card)?;
+        }
+        for (ip, card) in distress.passes.iter().enumerate() {
+            let to = distress.direction.neighbor(ip, n_players);
+            self.get_mut_player(to).add_card_to_hand(*card);
+        }
+
+        let captain = State::retrieve_captain(self.get_players(), None)?;
+        self.get_mut_current_trick().set_first_player(captain);
+        Ok(())
+    }
+
+    /// Looks for a distress signal making the mission feasible, trying both directions and
+    /// every combination of passed cards. Returns the first solution found along with the
+    /// distress signal leading to it.
+    ///
+    /// All the searches share `budget` explored states, after which the search gives up with
+    /// [`GameError::BudgetExceeded`]: with 4 players, there are thousands of signals to try.
+    pub fn play_with_distress(&self, budget: usize) -> Result<(Self, Distress), GameError> {
+        let candidates: Vec<Vec<Card>> = self
+            .get_players()
+            .iter()
+            .map(|p| {
+                p.get_hand()
+                    .iter()
+                    .copied()
+                    .filter(|c| !c.is_submarine())
+                    .collect()
+            })
+            .collect();
+        if candidates.iter().any(|c| c.is_empty()) {
+            return Err(GameError::NoSolutionFound);
+        }
+
+        let mut remaining = budget;
+        for direction in [Direction::Left, Direction::Right] {
+            // Enumerating all combinations of passes like an odometer.
+            let mut choice = vec![0; candidates.len()];
+            loop {
+                let distress = Distress {
+                    direction,
+                    passes: choice
+                        .iter()
+                        .zip(&candidates)
+                        .map(|(&i, cards)| cards[i])
+                        .collect(),
+                };
+                let mut state = self.clone();
+                state.apply_distress(&distress)?;
+                match state.search_within(&mut remaining) {
+                    Ok(solution) => return Ok((solution, distress)),
+                    Err(GameError::NoSolutionFound) => (),
+                    Err(GameError::BudgetExceeded(_)) => {
+                        return Err(GameError::BudgetExceeded(budget));
+                    }
+                    Err(e) => return Err(e),
+                }
+
+                let mut i = 0;
+                while i < choice.len() {
+                    choice[i] += 1;
+                    if choice[i] < candidates[i].len() {
+                        break;
+                    }
+                    choice[i] = 0;
+                    i += 1;
+                }
+                if i == choice.len() {
+                    break;
+                }
+            }
+        }
+
+        Err(GameError::NoSolutionFound)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{player::Player, task::win_cards::TaskWinCards};
+
+    use super::*;
+
+    fn state() -> State {
+        let p0 = Player::new([Card::Submarine(4), Card::Blue(5)].into());
+        let mut p1 = Player::new([Card::Pink(1), Card::Blue(1)].into());
+        p1.add_task(TaskWinCards::new(None, [Card::Blue(5)]))
+            .unwrap();
+        let p2 = Player::new([Card::Blue(2), Card::Green(2)].into());
+        State::new([p0, p1, p2])
+    }
+
+    #[test]
+    fn test_apply_distress() {
+        let mut s = state();
+        let distress = Distress {
+            direction: Direction::Right,
+            passes: vec![Card::Blue(5), Card::Pink(1), Card::Green(2)],
+        };
+        s.apply_distress(&distress).unwrap();
+        assert_eq!(
+            *s.get_player(0).get_hand(),
+            vec![Card::Submarine(4), Card::Pink(1)].into()
+        );
+        assert_eq!(
+            *s.get_player(1).get_hand(),
+            vec![Card::Blue(1), Card::Green(2)].into()
+        );
+        assert_eq!(
+            *s.get_player(2).get_hand(),
+            vec![Card::Blue(2), Card::Blue(5)].into()
+        );
+        assert_eq!(s.first_player(), 0);
+    }
+
+    #[test]
+    fn test_apply_distress_invalid() {
+        let mut s = state();
+        let with_submarine = Distress {
+            direction: Direction::Left,
+            passes: vec![Card::Submarine(4), Card::Pink(1), Card::Green(2)],
+        };
+        assert!(matches!(
+            s.apply_distress(&with_submarine),
+            Err(GameError::CannotPassSubmarine(_))
+        ));
+
+        // Nothing is passed when a later pass is invalid
+        let not_held = Distress {
+            direction: Direction::Left,
+            passes: vec![Card::Blue(5), Card::Pink(1), Card::Green(9)],
+        };
+        assert!(matches!(
+            s.apply_distress(&not_held),
+            Err(GameError::CardNotFound(..))
+        ));
+        let with_submarine = Distress {
+            direction: Direction::Left,
+            passes: vec![Card::Blue(5), Card::Pink(1), Card::Submarine(1)],
+        };
+        assert!(s.apply_distress(&with_submarine).is_err());
+        assert_eq!(s, state());
+
+        let too_short = Distress {
+            direction: Direction::Left,
+            passes: vec![Card::Blue(5)],
+        };
+        assert!(matches!(
+            s.apply_distress(&too_short),
+            Err(GameError::InvalidDistressSize(3, 1))
+        ));
+
+        let valid = Distress {
+            direction: Direction::Left,
+            passes: vec![Card::Blue(5), Card::Pink(1), Card::Green(2)],
+        };
+        assert!(s.clone().apply_distress(&valid).is_ok());
+        s.play_card(&Card::Blue(5)).unwrap();
+        assert!(matches!(
+            s.apply_distress(&valid),
+            Err(GameError::DistressAfterStart)
+        ));
+    }
+
+    #[test]
+    fn test_play_with_distress() {
+        let mut s = state();
+        assert!(matches!(s.play(), Err(GameError::NoSolutionFound)));
+
+        let (solution, distress) = s.play_with_distress(1000).unwrap();
+        assert_eq!(distress.direction, Direction::Left);
+        assert_eq!(distress.passes[0], Card::Blue(5));
+        assert_eq!(distress.passes[2], Card::Blue(2));
+        assert_eq!(solution.game_status(), crate::task::TaskStatus::Done);
+
+        assert!(matches!(
+            s.play_with_distress(3),
+            Err(GameError::BudgetExceeded(3))
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+//! Task draft and assignment.
+//!
+//! In Deep Sea, tasks are drafted one at a time, starting with the captain and going clockwise,
+//! so the number of tasks each seat ends up with only depends on the size of the pool. This
+//! module looks for the assignments of a pool of tasks to seats which make the mission feasible,
+//! and ranks them by robustness (number of winning lines).
+use thiserror::Error;
+
+use crate::{
+    solver::SolutionCount,
+    state::State,
+    task::{BaseTask, Task},
+};
+
+/// An assignment of the pool: `seats[i]` holds the `i`-th task of the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedAssignment {
+    pub seats: Vec<usize>,
+    pub count: SolutionCount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DraftError {
+    #[error("There is no task {0} in the pool.")]
+    UnknownTask(usize),
+    #[error("Task {0} has already been drafted.")]
+    AlreadyDrafted(usize),
+    #[error("Task {0} cannot be drafted by the captain.")]
+    CaptainForbidden(usize),
+    #[error("No player holds the submarine 4.")]
+    MissingCaptain,
+}
+
+pub struct DraftOptimizer {
+    state: State,
+    pool: Vec<Task>,
+    budget: usize,
+    captain: usize,
+}
+
+impl DraftOptimizer {
+    /// `state` should have its hands dealt; the solver explores at most `budget` states for
+    /// each assignment. The game may already be started, the draft then starts with the captain
+    /// rather than with the player leading the current trick.
+    pub fn new(state: State, pool: Vec<Task>, budget: usize) -> Result<Self, DraftError> {
+        let captain = State::retrieve_captain(state.get_players(), Some(state.get_current_trick()))
+            .map_err(|_| DraftError::MissingCaptain)?;
+        Ok(Self {
+            state,
+            pool,
+            budget,
+            captain,
+        })
+    }
+
+    /// Seat drafting the `k`-th task.
+    fn drafter(&self, k: usize) -> usize {
+        (self.captain + k) % self.state.n_players()
+    }
+
+    fn allowed(&self, task: &Task, ip: usize) -> bool {
+        ip != self.captain || !task.captain_forbidden()
+    }
+
+    /// Enumerates all assignments compatible with the draft, where `drafted[k]` is the index in
+    /// the pool of the `k`-th task already drafted. Fails if a drafted task is not in the pool,
+    /// was drafted twice, or was drafted by a seat which cannot take it.
+    fn assignments(&self, drafted: &[usize]) -> Result<Vec<Vec<usize>>, DraftError> {
+        let n_tasks = self.pool.len();
+        let mut seats: Vec<Option<usize>> = vec![None; n_tasks];
+        for (k, &i) in drafted.iter().enumerate() {
+            let seat = seats.get_mut(i).ok_or(DraftError::UnknownTask(i))?;
+            if seat.is_some() {
+                return Err(DraftError::AlreadyDrafted(i));
+            }
+            if !self.allowed(&self.pool[i], self.drafter(k)) {
+                return Err(DraftError::CaptainForbidden(i));
+            }
+            *seat = Some(self.drafter(k));
+        }
+
+        let mut slots = vec![0; self.state.n_players()];
+        for k in drafted.len()..n_tasks {
+            slots[self.drafter(k)] += 1;
+        }
+
+        let mut result = Vec::new();
+        self.enumerate(0, &mut seats, &mut slots, &mut result);
+        Ok(result)
+    }
+
+    fn enumerate(
+        &self,
+        i: usize,
+        seats: &mut Vec<Option<usize>>,
+        slots: &mut [usize],
+        result: &mut Vec<Vec<usize>>,
+    ) {
+        if i == seats.len() {
+            result.push(seats.iter().map(|s| s.unwrap()).collect());
+            return;
+        }
+        if seats[i].is_some() {
+            return self.enumerate(i + 1, seats, slots, result);
+        }
+        for ip in 0..slots.len() {
+            if slots[ip] > 0 && self.allowed(&self.pool[i], ip) {
+                slots[ip] -= 1;
+                seats[i] = Some(ip);
+                self.enumerate(i + 1, seats, slots, result);
+                seats[i] = None;
+                slots[ip] += 1;
+            }
+        }
+    }
+
+    fn assign(&self, seats: &[usize]) -> Result<State, DraftError> {
+        let mut state = self.state.clone();
+        for (i, (task, &ip)) in self.pool.iter().zip(seats).enumerate() {
+            state
+                .get_mut_player(ip)
+                .add_task(task.clone())
+                .map_err(|_| DraftError::CaptainForbidden(i))?;
+        }
+        Ok(state)
+    }
+
+    /// Returns the first assignment found making the mission feasible, if any.
+    pub fn find_feasible(&self) -> Result<Option<Vec<usize>>, DraftError> {
+        for seats in self.assignments(&[])? {
+            if self.assign(&seats)?.play_with_budget(self.budget).is_ok() {
+                return Ok(Some(seats));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Ranks the feasible assignments, the most robust first, given the tasks already drafted.
+    pub fn rank(&self, drafted: &[usize]) -> Result<Vec<RankedAssignment>, DraftError> {
+        let mut ranked = Vec::new();
+        for seats in self.assignments(drafted)? {
+            let count = self.assign(&seats)?.count_solutions(self.budget);
+            if count.lines > 0 {
+                ranked.push(RankedAssignment { seats, count });
+            }
+        }
+        ranked.sort_by_key(|a| std::cmp::Reverse(a.count.lines));
+        Ok(ranked)
+    }
+
+    /// Recommends which task the current drafter should pick, given the tasks already drafted:
+    /// returns the index in the pool of the task leading to the most robust assignment.
+    pub fn recommend(&self, drafted: &[usize]) -> Result<Option<usize>, DraftError> {
+        let drafter = self.drafter(drafted.len());
+        Ok(self.rank(drafted)?.into_iter().find_map(|a| {
+            (0..self.pool.len()).find(|i| !drafted.contains(i) && a.seats[*i] == drafter)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        card::Card, player::Player, task::win_cards::TaskWinCards,
+        task::win_more_tricks_than_captain,
+    };
+
+    use super::*;
+
+    fn optimizer() -> DraftOptimizer {
+        let p0 = Player::new([Card::Submarine(4), Card::Blue(1)].into());
+        let p1 = Player::new([Card::Submarine(1), Card::Pink(1)].into());
+        let p2 = Player::new([Card::Blue(9), Card::Pink(2)].into());
+        let pool = vec![
+            TaskWinCards::new(None, [Card::Blue(9)]).into(),
+            TaskWinCards::new(None, [Card::Submarine(4)]).into(),
+        ];
+        DraftOptimizer::new(State::new([p0, p1, p2]), pool, 1000).unwrap()
+    }
+
+    #[test]
+    fn test_assignments() {
+        let optimizer = optimizer();
+        assert_eq!(optimizer.assignments(&[]), Ok(vec![vec![0, 1], vec![1, 0]]));
+        assert_eq!(optimizer.assignments(&[1]), Ok(vec![vec![1, 0]]));
+    }
+
+    #[test]
+    fn test_rank() {
+        let optimizer = optimizer();
+        let ranked = optimizer.rank(&[]).unwrap();
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].seats, vec![1, 0]);
+        assert!(ranked[0].count.exhaustive);
+        assert_eq!(optimizer.find_feasible(), Ok(Some(vec![1, 0])));
+        assert!(optimizer.rank(&[0]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recommend() {
+        let optimizer = optimizer();
+        assert_eq!(optimizer.recommend(&[]), Ok(Some(1)));
+        assert_eq!(optimizer.recommend(&[1]), Ok(Some(0)));
+        assert_eq!(optimizer.recommend(&[0]), Ok(None));
+    }
+
+    #[test]
+    fn test_invalid_draft() {
+        let optimizer = optimizer();
+        assert_eq!(optimizer.rank(&[5]), Err(DraftError::UnknownTask(5)));
+        assert_eq!(optimizer.rank(&[1, 1]), Err(DraftError::AlreadyDrafted(1)));
+        assert_eq!(
+            optimizer.recommend(&[0, 2]),
+            Err(DraftError::UnknownTask(2))
+        );
+    }
+
+    #[test]
+    fn test_captain_restriction() {
+        let p0 = Player::new([Card::Submarine(4)].into());
+        let p1 = Player::new([Card::Blue(1)].into());
+        let p2 = Player::new([Card::Blue(2)].into());
+        let pool = vec![
+            win_more_tricks_than_captain().into(),
+            TaskWinCards::new(None, [Card::Blue(1)]).into(),
+        ];
+        let optimizer = DraftOptimizer::new(State::new([p0, p1, p2]), pool, 1000).unwrap();
+        assert_eq!(optimizer.assignments(&[]), Ok(vec![vec![1, 0]]));
+        assert_eq!(optimizer.rank(&[0]), Err(DraftError::CaptainForbidden(0)));
+        assert!(optimizer.rank(&[1]).is_ok());
+    }
+
+    #[test]
+    fn test_started_game() {
+        // Player 1 won the first trick and leads the second one, but player 0 is the captain.
+        let p0 = Player::new([Card::Submarine(4)].into());
+        let mut p1 = Player::new([Card::Blue(1)].into());
+        p1.add_trick((0, 0, vec![Card::Pink(2), Card::Pink(9), Card::Pink(3)]).into())
+            .unwrap();
+        let p2 = Player::new([Card::Blue(2)].into());
+        let state = State::from_parts(vec![p0, p1, p2], (1, 1, vec![]).into(), vec![]);
+        let pool = vec![
+            win_more_tricks_than_captain().into(),
+            TaskWinCards::new(None, [Card::Blue(2)]).into(),
+        ];
+        let optimizer = DraftOptimizer::new(state, pool, 1000).unwrap();
+        assert_eq!(optimizer.assignments(&[]), Ok(vec![vec![1, 0]]));
+        assert_eq!(optimizer.rank(&[0]), Err(DraftError::CaptainForbidden(0)));
+        assert!(optimizer.rank(&[]).is_ok());
+        assert!(optimizer.find_feasible().is_ok());
+    }
+}
@@ -0,0 +1,1178 @@
+//! A small text language to define custom tasks, for instance in configuration files.
+//!
+//! Keywords are case-insensitive, cards are written in the short notation of
+//! [`Card::from_str`](crate::card::Card) (`P7`, `G3`, `B9`, `Y1`, `S4`) and colors by name
+//! (`pink`, `green`, `blue`, `yellow`, `submarine`). Lists are either comma-separated or written
+//! between brackets. A task may end with its difficulty for 3, 4 and 5 players (`@ 2/3/3`, or
+//! `@ 2` if it does not depend on the number of players).
+//! Tasks which cannot be completed, such as listing a card twice or winning more tricks than the
+//! game has, are reported at the offending token like syntax errors.
+//!
+//! ```text
+//! task    := base ["@" N ["/" N "/" N]]
+//! base    := "win" win | "dont" dont
+//!          | "all" "of" group | "any" "of" group | "at" "least" N "of" group | "not" group
+//! group   := "(" task (";" task)* ")"
+//! win     := "cards" cards | "no" ("cards" cards | "color" colors | "value" numbers)
+//!          | CARD "with" "submarine" | CARD "in" "last" "trick"
+//!          | "exactly" N ("trick" | "tricks") | "x" "tricks" ["hidden"] ["predicted" N]
+//!          | ["exactly"] N "consecutive" "tricks"
+//!          | ("exactly" | "at" "least") amount ("," amount)* [redeal]
+//!          | ("more" | "fewer") "tricks" "than" ("captain" | "everyone")
+//!          | "more" "tricks" "than" "everyone" "together"
+//!          | "same" "tricks" "as" "captain"
+//!          | "more" COLOR "than" COLOR | "as" "many" COLOR "as" COLOR
+//!          | "all" "of" "a" "color"
+//!          | "submarine" N "only" [redeal]
+//!          | "trick" "with" "value" N ["containing" N]
+//!          | "trick" "where" condition
+//!          | ("first" ("trick" | N "tricks") | "tricks" numbers) ["and" "last" "trick"] ["only"]
+//!          | "last" "trick" ["only"]
+//! amount  := N COLOR | N "of" N
+//! redeal  := "redeal" "if" cards ("or" cards)*
+//! condition := "all" ("even" | "odd" | ">" N | "<" N)
+//!            | "total" (">" N | "<" N | "in" numbers) | "same" COLOR "and" COLOR
+//! dont    := "win" ("cards" cards | "color" colors | "value" numbers | "tricks"
+//!                  | "first" N "tricks" | "consecutive" "tricks")
+//!          | "open" "with" colors
+//! ```
+//!
+//! For instance `win cards [B1 B2 B3]`, `dont win color pink, blue`,
+//! `win trick with value 6 containing 5 @ 1/2/2`, `win exactly 2 tricks` or
+//! `any of (win trick where all odd; not (win last trick))`.
+//!
+//! Any task can be written back in this language with [`BaseTask::to_dsl`].
+use std::cmp::Ordering;
+
+use thiserror::Error;
+
+use crate::{
+    card::{BLUE, COLOR_RANGE, COLORS, Card, GREEN, PINK, SUBMARINE, YELLOW, deck},
+    player,
+    task::{
+        BaseTask, Task, TaskDifficulty, all_of::TaskAllOf, any_of::TaskAnyOf,
+        at_least::TaskAtLeast, dont_open_trick_with::TaskDontOpenTrickWith,
+        dont_win_cards::TaskDontWinCards,
+        dont_win_consecutive_tricks::TaskDontWinConsecutiveTricks,
+        dont_win_tricks::TaskDontWinTricks, not::TaskNot,
+        win_all_cards_color::TaskWinAllCardsColor, win_cards::TaskWinCards,
+        win_cards_amount_color::TaskWinCardsAmountColor,
+        win_cards_amount_number::TaskWinCardsAmountNumber,
+        win_consecutive_tricks::TaskWinConsecutiveTricks,
+        win_more_cards_color::TaskWinMoreCardsColor, win_more_tricks::TaskWinMoreTricks,
+        win_nb_tricks::TaskWinNbTricks,
+        win_nb_tricks_compared_captain::TaskWinNbTricksComparedCaptain,
+        win_specific_submarine::TaskWinSpecificSubmarine, win_trick_with::TaskWinTrickWith,
+        win_trick_with_pred::TaskWinTrickWithPred, win_tricks::TaskWinTricks,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseErrorKind {
+    #[error("expected {expected}, found {found}")]
+    Expected { expected: String, found: String },
+    #[error("invalid card `{0}`")]
+    InvalidCard(String),
+    #[error("invalid color `{0}`")]
+    InvalidColor(String),
+    #[error("invalid number `{0}`")]
+    InvalidNumber(String),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// Error with its position in the source (lines and columns start at 1).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("line {line}, column {column}: {kind}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+/// Parses a single task, for a game with `n_players` players.
+pub fn parse_task(src: &str, n_players: usize) -> Result<Task, ParseError> {
+    parse_line(src, 1, n_players)
+}
+
+/// Parses one task per line. Empty lines and comments (starting with `#`) are ignored.
+pub fn parse_tasks(src: &str, n_players: usize) -> Result<Vec<Task>, ParseError> {
+    src.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(i, line)| parse_line(line, i + 1, n_players))
+        .collect()
+}
+
+fn parse_line(src: &str, line: usize, n_players: usize) -> Result<Task, ParseError> {
+    let mut parser = Parser::new(src, line, n_players);
+    if let Err(e) = player::check_valid_n_players(n_players) {
+        return Err(parser.error(ParseErrorKind::Invalid(e)));
+    }
+    let task = parser.task()?;
+    match parser.peek() {
+        None => Ok(task),
+        Some(_) => Err(parser.expected("end of task")),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    column: usize,
+}
+
+impl Token {
+    fn is(&self, keyword: &str) -> bool {
+        self.text.eq_ignore_ascii_case(keyword)
+    }
+}
+
+fn tokenize(src: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().enumerate().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut text = c.to_string();
+        if c.is_alphanumeric() || c == '\'' {
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '\'') {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+        }
+        tokens.push(Token {
+            text,
+            column: i + 1,
+        });
+    }
+    tokens
+}
+
+/// Builds a task once its difficulty is known (it is written after the task).
+type Build = Box<dyn FnOnce(Option<TaskDifficulty>) -> Task>;
+
+/// A color, as the constructor of its cards.
+type Color = fn(usize) -> Card;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    line: usize,
+    end_column: usize,
+    n_players: usize,
+}
+
+impl Parser {
+    fn new(src: &str, line: usize, n_players: usize) -> Self {
+        Self {
+            tokens: tokenize(src),
+            pos: 0,
+            line,
+            end_column: src.chars().count() + 1,
+            n_players,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_is(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|t| t.is(keyword))
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        self.error_at(self.pos, kind)
+    }
+
+    fn error_at(&self, pos: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.tokens.get(pos).map_or(self.end_column, |t| t.column),
+            kind,
+        }
+    }
+
+    fn expected(&self, expected: &str) -> ParseError {
+        let found = match self.peek() {
+            Some(t) => format!("`{}`", t.text),
+            None => "end of input".to_string(),
+        };
+        self.error(ParseErrorKind::Expected {
+            expected: expected.to_string(),
+            found,
+        })
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token, ParseError> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| self.expected(expected))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    /// Consumes the keyword if it comes next.
+    fn eat(&mut self, keyword: &str) -> bool {
+        let found = self.peek_is(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if self.eat(keyword) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", keyword)))
+        }
+    }
+
+    /// Consumes one of the keywords, returning its index.
+    fn one_of(&mut self, keywords: &[&str]) -> Result<usize, ParseError> {
+        match keywords.iter().position(|k| self.peek_is(k)) {
+            Some(i) => {
+                self.pos += 1;
+                Ok(i)
+            }
+            None => {
+                let expected: Vec<String> = keywords.iter().map(|k| format!("`{}`", k)).collect();
+                Err(self.expected(&expected.join(" or ")))
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, ParseError> {
+        let token = self.next("a number")?;
+        if !token.text.chars().all(|c| c.is_ascii_digit()) {
+            self.pos -= 1;
+            return Err(self.expected("a number"));
+        }
+        token
+            .text
+            .parse()
+            .map_err(|_| self.error_at(self.pos - 1, ParseErrorKind::InvalidNumber(token.text)))
+    }
+
+    /// A number of at least 1.
+    fn positive(&mut self) -> Result<usize, ParseError> {
+        let n = self.number()?;
+        if n == 0 {
+            return Err(self.error_at(
+                self.pos - 1,
+                ParseErrorKind::Invalid("expected a positive number".to_string()),
+            ));
+        }
+        Ok(n)
+    }
+
+    /// A trick total from `min` to `max`.
+    fn total(&mut self, min: usize, max: usize) -> Result<usize, ParseError> {
+        let v = self.number()?;
+        if !(min..=max).contains(&v) {
+            return Err(self.error_at(
+                self.pos - 1,
+                ParseErrorKind::Invalid(format!("expected a total from {} to {}", min, max)),
+            ));
+        }
+        Ok(v)
+    }
+
+    /// The lowest and highest total values of a trick.
+    fn total_range(&self) -> (usize, usize) {
+        let mut values: Vec<usize> = deck().iter().map(Card::val).collect();
+        values.sort_unstable();
+        let n = self.n_players;
+        (
+            values[..n].iter().sum(),
+            values[values.len() - n..].iter().sum(),
+        )
+    }
+
+    /// The value of a colored card, from 1 to 9.
+    fn value(&mut self) -> Result<usize, ParseError> {
+        let v = self.number()?;
+        if !COLOR_RANGE.contains(&v) {
+            return Err(self.error_at(
+                self.pos - 1,
+                ParseErrorKind::Invalid("expected a card value from 1 to 9".to_string()),
+            ));
+        }
+        Ok(v)
+    }
+
+    fn card(&mut self) -> Result<Card, ParseError> {
+        let token = self.next("a card")?;
+        token
+            .text
+            .parse()
+            .map_err(|_| self.error_at(self.pos - 1, ParseErrorKind::InvalidCard(token.text)))
+    }
+
+    fn color(&mut self) -> Result<Color, ParseError> {
+        let token = self.next("a color")?;
+        let name = token.text.to_ascii_lowercase();
+        match name.strip_suffix('s').unwrap_or(&name) {
+            "pink" => Ok(PINK),
+            "green" => Ok(GREEN),
+            "blue" => Ok(BLUE),
+            "yellow" => Ok(YELLOW),
+            "submarine" => Ok(SUBMARINE),
+            _ => Err(self.error_at(self.pos - 1, ParseErrorKind::InvalidColor(token.text))),
+        }
+    }
+
+    /// A non-empty list, either between brackets or comma-separated.
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![];
+        if self.eat("[") {
+            items.push(item(self)?);
+            while !self.eat("]") {
+                self.eat(",");
+                items.push(item(self)?);
+            }
+        } else {
+            items.push(item(self)?);
+            while self.eat(",") {
+                items.push(item(self)?);
+            }
+        }
+        Ok(items)
+    }
+
+    /// A non-empty list in which no item is repeated, items being compared by `key`.
+    fn unique_list<T, K: PartialEq>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+        key: impl Fn(&T) -> K,
+    ) -> Result<Vec<T>, ParseError> {
+        let items = self.list(|p| Ok((p.pos, item(p)?)))?;
+        for (i, (pos, x)) in items.iter().enumerate() {
+            if items[..i].iter().any(|(_, y)| key(y) == key(x)) {
+                return Err(self.repeated(*pos));
+            }
+        }
+        Ok(items.into_iter().map(|(_, x)| x).collect())
+    }
+
+    fn repeated(&self, pos: usize) -> ParseError {
+        self.error_at(
+            pos,
+            ParseErrorKind::Invalid(format!(
+                "`{}` is listed more than once",
+                self.tokens[pos].text
+            )),
+        )
+    }
+
+    fn cards(&mut self) -> Result<Vec<Card>, ParseError> {
+        self.unique_list(Self::card, |c| *c)
+    }
+
+    fn colors(&mut self) -> Result<Vec<Color>, ParseError> {
+        self.unique_list(Self::color, |color| color(1))
+    }
+
+    fn values(&mut self) -> Result<Vec<usize>, ParseError> {
+        self.unique_list(Self::value, |v| *v)
+    }
+
+    /// Checks that the number of tricks (or the trick index, from 1) at `pos` does not exceed
+    /// the number of tricks of the game.
+    fn check_n_tricks(&self, pos: usize, n: usize) -> Result<(), ParseError> {
+        let n_tricks = player::n_tricks_total(self.n_players);
+        if n > n_tricks {
+            return Err(self.error_at(
+                pos,
+                ParseErrorKind::Invalid(format!(
+                    "a game of {} players has only {} tricks",
+                    self.n_players, n_tricks
+                )),
+            ));
+        }
+        Ok(())
+    }
+
+    /// A number of tricks, positive if `positive` is set.
+    fn n_tricks(&mut self, positive: bool) -> Result<usize, ParseError> {
+        let pos = self.pos;
+        let n = if positive {
+            self.positive()?
+        } else {
+            self.number()?
+        };
+        self.check_n_tricks(pos, n)?;
+        Ok(n)
+    }
+
+    fn task(&mut self) -> Result<Task, ParseError> {
+        let build = self.base()?;
+        let difficulty = if self.eat("@") {
+            let first = self.number()?;
+            if self.eat("/") {
+                let second = self.number()?;
+                self.keyword("/")?;
+                Some([first, second, self.number()?].into())
+            } else {
+                Some([first; 3].into())
+            }
+        } else {
+            None
+        };
+        Ok(build(difficulty))
+    }
+
+    fn group(&mut self) -> Result<Vec<Task>, ParseError> {
+        self.keyword("(")?;
+        let mut tasks = vec![self.task()?];
+        while self.eat(";") {
+            tasks.push(self.task()?);
+        }
+        self.keyword(")")?;
+        Ok(tasks)
+    }
+
+    fn base(&mut self) -> Result<Build, ParseError> {
+        match self.one_of(&["win", "dont", "don't", "all", "any", "at", "not"])? {
+            0 => self.win(),
+            1 | 2 => self.dont(),
+            3 => {
+                self.keyword("of")?;
+                let tasks = self.group()?;
+                Ok(Box::new(|d| TaskAllOf::new(d, tasks).into()))
+            }
+            4 => {
+                self.keyword("of")?;
+                let tasks = self.group()?;
+                Ok(Box::new(|d| TaskAnyOf::new(d, tasks).into()))
+            }
+            5 => {
+                self.keyword("least")?;
+                let k_pos = self.pos;
+                let k = self.positive()?;
+                self.keyword("of")?;
+                let tasks = self.group()?;
+                if k > tasks.len() {
+                    return Err(self.error_at(
+                        k_pos,
+                        ParseErrorKind::Invalid(format!(
+                            "cannot complete {} of {} tasks",
+                            k,
+                            tasks.len()
+                        )),
+                    ));
+                }
+                Ok(Box::new(move |d| TaskAtLeast::new(d, k, tasks).into()))
+            }
+            _ => {
+                let mut tasks = self.group()?;
+                if tasks.len() != 1 {
+                    return Err(self.error(ParseErrorKind::Invalid(
+                        "`not` applies to a single task".to_string(),
+                    )));
+                }
+                let task = tasks.remove(0);
+                Ok(Box::new(|d| TaskNot::new(d, task).into()))
+            }
+        }
+    }
+
+    fn dont(&mut self) -> Result<Build, ParseError> {
+        if self.eat("open") {
+            self.keyword("with")?;
+            let colors = self.colors()?;
+            return Ok(Box::new(|d| TaskDontOpenTrickWith::new(d, colors).into()));
+        }
+        self.keyword("win")?;
+        match self.one_of(&["cards", "color", "value", "tricks", "first", "consecutive"])? {
+            kind @ 0..=2 => self.dont_win_cards(kind, false),
+            3 => Ok(Box::new(|d| TaskDontWinTricks::new_any(d).into())),
+            4 => {
+                let n = self.n_tricks(true)?;
+                self.keyword("tricks")?;
+                Ok(Box::new(move |d| {
+                    TaskDontWinTricks::new_n_first_tricks(d, n).into()
+                }))
+            }
+            _ => {
+                self.keyword("tricks")?;
+                Ok(Box::new(|d| TaskDontWinConsecutiveTricks::new(d).into()))
+            }
+        }
+    }
+
+    /// Cards not to win, listed one by one (`kind` 0), by color (1) or by value (2). `win_no`
+    /// words the task as "Win no ..." (`win no color pink`).
+    fn dont_win_cards(&mut self, kind: usize, win_no: bool) -> Result<Build, ParseError> {
+        let build: Box<dyn FnOnce(Option<TaskDifficulty>) -> TaskDontWinCards> = match kind {
+            0 => {
+                let cards = self.cards()?;
+                Box::new(|d| TaskDontWinCards::new(d, cards))
+            }
+            1 => {
+                let colors = self.colors()?;
+                Box::new(|d| TaskDontWinCards::new_from_colors(d, colors))
+            }
+            _ => {
+                let values = self.values()?;
+                Box::new(|d| TaskDontWinCards::new_from_values(d, values))
+            }
+        };
+        Ok(Box::new(move |d| match win_no {
+            true => build(d).worded_win_no().into(),
+            false => build(d).into(),
+        }))
+    }
+
+    fn win(&mut self) -> Result<Build, ParseError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.expected("what to win"));
+        };
+        let keyword = token.text.to_ascii_lowercase();
+        match keyword.as_str() {
+            "cards" => {
+                self.pos += 1;
+                let cards = self.cards()?;
+                Ok(Box::new(|d| TaskWinCards::new(d, cards).into()))
+            }
+            "no" => {
+                self.pos += 1;
+                let kind = self.one_of(&["cards", "color", "value"])?;
+                self.dont_win_cards(kind, true)
+            }
+            "exactly" => {
+                self.pos += 1;
+                let n_pos = self.pos;
+                let n = self.number()?;
+                if self.eat("trick") || self.eat("tricks") {
+                    self.check_n_tricks(n_pos, n)?;
+                    Ok(Box::new(move |d| TaskWinNbTricks::new(d, n).into()))
+                } else if self.peek_is("consecutive") {
+                    self.pos = n_pos;
+                    self.consecutive(true)
+                } else {
+                    self.pos = n_pos;
+                    self.amounts(true)
+                }
+            }
+            "at" => {
+                self.pos += 1;
+                self.keyword("least")?;
+                self.amounts(false)
+            }
+            "x" => {
+                self.pos += 1;
+                self.keyword("tricks")?;
+                let hidden = self.eat("hidden");
+                let prediction = if self.eat("predicted") {
+                    Some(self.n_tricks(false)?)
+                } else {
+                    None
+                };
+                Ok(Box::new(move |d| {
+                    let mut task = TaskWinNbTricks::new_prediction(d, hidden);
+                    if let Some(n) = prediction {
+                        task.predict(n);
+                    }
+                    task.into()
+                }))
+            }
+            "more" | "fewer" => {
+                self.pos += 1;
+                let fewer = keyword == "fewer";
+                if fewer || self.peek_is("tricks") {
+                    self.keyword("tricks")?;
+                    self.keyword("than")?;
+                    if self.one_of(&["captain", "everyone"])? == 0 {
+                        let comp = if fewer {
+                            Ordering::Less
+                        } else {
+                            Ordering::Greater
+                        };
+                        Ok(Box::new(move |d| {
+                            TaskWinNbTricksComparedCaptain::new(d, comp).into()
+                        }))
+                    } else {
+                        let together = self.eat("together");
+                        if together && fewer {
+                            return Err(self.error_at(
+                                self.pos - 1,
+                                ParseErrorKind::Invalid(
+                                    "`together` only applies to winning more tricks".to_string(),
+                                ),
+                            ));
+                        }
+                        Ok(Box::new(move |d| {
+                            TaskWinMoreTricks::new(d, together, fewer).into()
+                        }))
+                    }
+                } else {
+                    self.more_cards_color(false)
+                }
+            }
+            "same" => {
+                self.pos += 1;
+                self.keyword("tricks")?;
+                self.keyword("as")?;
+                self.keyword("captain")?;
+                Ok(Box::new(|d| {
+                    TaskWinNbTricksComparedCaptain::new(d, Ordering::Equal).into()
+                }))
+            }
+            "as" => {
+                self.pos += 1;
+                self.keyword("many")?;
+                self.more_cards_color(true)
+            }
+            "all" => {
+                self.pos += 1;
+                self.keyword("of")?;
+                self.keyword("a")?;
+                self.keyword("color")?;
+                Ok(Box::new(|d| TaskWinAllCardsColor::new(d).into()))
+            }
+            "submarine" => {
+                self.pos += 1;
+                let v_pos = self.pos;
+                let v = self.number()?;
+                if !Card::Submarine(v).is_valid() {
+                    return Err(
+                        self.error_at(v_pos, ParseErrorKind::InvalidCard(format!("S{}", v)))
+                    );
+                }
+                self.keyword("only")?;
+                let redeal = self.redeal()?;
+                Ok(Box::new(move |d| {
+                    TaskWinSpecificSubmarine::new(d, v)
+                        .with_redeal_if(redeal)
+                        .into()
+                }))
+            }
+            "trick" => {
+                self.pos += 1;
+                if self.one_of(&["with", "where"])? == 0 {
+                    self.keyword("value")?;
+                    let value = self.value()?;
+                    let containing = if self.eat("containing") {
+                        Some(self.value()?)
+                    } else {
+                        None
+                    };
+                    Ok(Box::new(move |d| {
+                        TaskWinTrickWith::new(d, value, containing).into()
+                    }))
+                } else {
+                    self.condition()
+                }
+            }
+            "first" | "tricks" | "last" => self.tricks(),
+            _ if self.consecutive_follows() => self.consecutive(false),
+            _ => {
+                let Ok(card) = self.card() else {
+                    self.pos -= 1;
+                    return Err(self.expected("what to win"));
+                };
+                if self.eat("with") {
+                    self.keyword("submarine")?;
+                    Ok(Box::new(move |d| {
+                        TaskWinTrickWithPred::new_win_card_with_submarine(d, card).into()
+                    }))
+                } else {
+                    self.keyword("in")?;
+                    self.keyword("last")?;
+                    self.keyword("trick")?;
+                    let n_players = self.n_players;
+                    Ok(Box::new(move |d| {
+                        TaskWinTrickWithPred::new_win_card_last_trick(d, card, n_players).into()
+                    }))
+                }
+            }
+        }
+    }
+
+    fn consecutive_follows(&self) -> bool {
+        self.tokens
+            .get(self.pos + 1)
+            .is_some_and(|t| t.is("consecutive"))
+    }
+
+    fn consecutive(&mut self, exactly: bool) -> Result<Build, ParseError> {
+        let amount = self.n_tricks(true)?;
+        self.keyword("consecutive")?;
+        self.keyword("tricks")?;
+        Ok(Box::new(move |d| {
+            TaskWinConsecutiveTricks::new(d, amount, exactly).into()
+        }))
+    }
+
+    /// Amounts of cards of some colors (`2 pink`) or of some values (`3 of 9`).
+    fn amounts(&mut self, exactly: bool) -> Result<Build, ParseError> {
+        let start = self.pos;
+        let mut colors: Vec<(Color, usize)> = vec![];
+        let mut values = vec![];
+        loop {
+            let n_pos = self.pos;
+            let n = self.number()?;
+            let of = self.eat("of");
+            let pos = self.pos;
+            // Cards of the amount in the deck (submarines have no value)
+            let available = if of {
+                let v = self.value()?;
+                if values.iter().any(|&(w, _)| w == v) {
+                    return Err(self.repeated(pos));
+                }
+                values.push((v, n));
+                COLORS.len()
+            } else {
+                let color = self.color()?;
+                if colors.iter().any(|(c, _)| c(1).same_color(&color(1))) {
+                    return Err(self.repeated(pos));
+                }
+                colors.push((color, n));
+                deck().iter().filter(|c| c.same_color(&color(1))).count()
+            };
+            if n > available {
+                return Err(self.error_at(
+                    n_pos,
+                    ParseErrorKind::Invalid(format!(
+                        "there are only {} `{}` cards",
+                        available, self.tokens[pos].text
+                    )),
+                ));
+            }
+            if !self.eat(",") {
+                break;
+            }
+        }
+        if !colors.is_empty() && !values.is_empty() {
+            return Err(self.error_at(
+                start,
+                ParseErrorKind::Invalid("cannot mix amounts of colors and of values".to_string()),
+            ));
+        }
+        if values.is_empty() {
+            let redeal = self.redeal()?;
+            Ok(Box::new(move |d| {
+                TaskWinCardsAmountColor::new(d, exactly, colors)
+                    .with_redeal_if(redeal)
+                    .into()
+            }))
+        } else {
+            Ok(Box::new(move |d| {
+                TaskWinCardsAmountNumber::new(d, exactly, values).into()
+            }))
+        }
+    }
+
+    fn redeal(&mut self) -> Result<Vec<Vec<Card>>, ParseError> {
+        let mut sets = vec![];
+        if self.eat("redeal") {
+            self.keyword("if")?;
+            sets.push(self.cards()?);
+            while self.eat("or") {
+                sets.push(self.cards()?);
+            }
+        }
+        Ok(sets)
+    }
+
+    fn more_cards_color(&mut self, equal: bool) -> Result<Build, ParseError> {
+        let start = self.pos;
+        let more_of = self.color()?;
+        self.keyword(if equal { "as" } else { "than" })?;
+        let fewer_of = self.color()?;
+        if more_of(1).same_color(&fewer_of(1)) {
+            return Err(self.error_at(
+                start,
+                ParseErrorKind::Invalid("colors must be different".to_string()),
+            ));
+        }
+        Ok(Box::new(move |d| {
+            TaskWinMoreCardsColor::new(d, more_of, fewer_of, equal).into()
+        }))
+    }
+
+    fn condition(&mut self) -> Result<Build, ParseError> {
+        match self.one_of(&["all", "total", "same"])? {
+            0 => match self.one_of(&["even", "odd", ">", "<"])? {
+                0 => Ok(Box::new(|d| TaskWinTrickWithPred::new_even(d).into())),
+                1 => Ok(Box::new(|d| TaskWinTrickWithPred::new_odd(d).into())),
+                2 => {
+                    let v = self.number()?;
+                    if v >= 9 {
+                        return Err(self.error_at(
+                            self.pos - 1,
+                            ParseErrorKind::Invalid("no card is greater than 9".to_string()),
+                        ));
+                    }
+                    Ok(Box::new(move |d| {
+                        TaskWinTrickWithPred::new_greater(d, v).into()
+                    }))
+                }
+                _ => {
+                    let v = self.number()?;
+                    if v <= 1 {
+                        return Err(self.error_at(
+                            self.pos - 1,
+                            ParseErrorKind::Invalid("no card is lower than 1".to_string()),
+                        ));
+                    }
+                    Ok(Box::new(move |d| {
+                        TaskWinTrickWithPred::new_lower(d, v).into()
+                    }))
+                }
+            },
+            1 => match self.one_of(&[">", "<", "in"])? {
+                0 => {
+                    let (min, max) = self.total_range();
+                    let v = self.total(min, max - 1)?;
+                    Ok(Box::new(move |d| {
+                        TaskWinTrickWithPred::new_total_greater(d, v).into()
+                    }))
+                }
+                1 => {
+                    let (min, max) = self.total_range();
+                    let v = self.total(min + 1, max)?;
+                    Ok(Box::new(move |d| {
+                        TaskWinTrickWithPred::new_total_lower(d, v).into()
+                    }))
+                }
+                _ => {
+                    let (min, max) = self.total_range();
+                    let values = self.unique_list(|p| p.total(min, max), |v| *v)?;
+                    Ok(Box::new(|d| {
+                        TaskWinTrickWithPred::new_total_in(d, values).into()
+                    }))
+                }
+            },
+            _ => {
+                let start = self.pos;
+                let c1 = self.color()?;
+                self.keyword("and")?;
+                let c2 = self.color()?;
+                if c1(1).same_color(&c2(1)) {
+                    return Err(self.error_at(
+                        start,
+                        ParseErrorKind::Invalid("colors must be different".to_string()),
+                    ));
+                }
+                Ok(Box::new(move |d| {
+                    TaskWinTrickWithPred::new_same_nb_of_colors(d, c1, c2).into()
+                }))
+            }
+        }
+    }
+
+    /// Tricks to win, numbered from 1.
+    fn tricks(&mut self) -> Result<Build, ParseError> {
+        let mut indexes = vec![];
+        let mut last = false;
+        match self.one_of(&["first", "tricks", "last"])? {
+            0 => {
+                let n = if self.eat("trick") {
+                    1
+                } else {
+                    let n = self.n_tricks(true)?;
+                    self.keyword("tricks")?;
+                    n
+                };
+                indexes.extend(0..n);
+            }
+            1 => {
+                let numbers = self.unique_list(|p| Ok((p.pos, p.number()?)), |(_, i)| *i)?;
+                for (pos, i) in numbers {
+                    if i == 0 {
+                        return Err(self.error_at(
+                            pos,
+                            ParseErrorKind::Invalid("tricks are numbered from 1".to_string()),
+                        ));
+                    }
+                    self.check_n_tricks(pos, i)?;
+                    indexes.push(i - 1);
+                }
+            }
+            _ => {
+                self.keyword("trick")?;
+                last = true;
+            }
+        }
+        if !last && self.eat("and") {
+            self.keyword("last")?;
+            self.keyword("trick")?;
+            last = true;
+        }
+        let strict = self.eat("only");
+        Ok(Box::new(move |d| {
+            TaskWinTricks::new(d, indexes, last, strict).into()
+        }))
+    }
+}
+
+/// Cards as a list between brackets, in the given order (which descriptions may depend on).
+pub(crate) fn render_cards<'a, I>(cards: I) -> String
+where
+    I: IntoIterator<Item = &'a Card>,
+{
+    let cards: Vec<String> = cards.into_iter().map(Card::to_string).collect();
+    format!("[{}]", cards.join(" "))
+}
+
+/// Numbers as a list between brackets, in increasing order.
+pub(crate) fn render_numbers<I>(values: I) -> String
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut values: Vec<usize> = values.into_iter().collect();
+    values.sort();
+    let values: Vec<String> = values.iter().map(usize::to_string).collect();
+    format!("[{}]", values.join(" "))
+}
+
+/// Name of the color of `card`.
+pub(crate) fn render_color(card: &Card) -> &'static str {
+    match card {
+        Card::Pink(_) => "pink",
+        Card::Green(_) => "green",
+        Card::Blue(_) => "blue",
+        Card::Yellow(_) => "yellow",
+        Card::Submarine(_) => "submarine",
+    }
+}
+
+/// Redeal condition, to be appended to the task.
+pub(crate) fn render_redeal(sets: &[Vec<Card>]) -> String {
+    let sets: Vec<String> = sets.iter().map(render_cards).collect();
+    if sets.is_empty() {
+        String::new()
+    } else {
+        format!(" redeal if {}", sets.join(" or "))
+    }
+}
+
+/// Tasks of a combinator, between parentheses.
+pub(crate) fn render_group(tasks: &[Task]) -> String {
+    let tasks: Vec<String> = tasks.iter().map(|t| t.to_dsl()).collect();
+    format!("({})", tasks.join("; "))
+}
+
+/// Appends the difficulty suffix, if any.
+pub(crate) fn render_difficulty(text: String, difficulty: Option<TaskDifficulty>) -> String {
+    match difficulty {
+        None => text,
+        Some(d) if d.get(3) == d.get(4) && d.get(4) == d.get(5) => {
+            format!("{} @ {}", text, d.get(3))
+        }
+        Some(d) => format!("{} @ {}/{}/{}", text, d.get(3), d.get(4), d.get(5)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{catalog, locale::Locale};
+
+    use super::*;
+
+    fn text(src: &str) -> String {
+        parse_task(src, 4).unwrap().describe(Locale::English)
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(text("win cards [B1 B2 B3]"), "Win the blue 1, 2 and 3");
+        assert_eq!(
+            text("dont win color pink,blue"),
+            "Don't win any pink or blue cards"
+        );
+        assert_eq!(
+            text("win trick with value 6 containing 5"),
+            "Win a 5 with a 6"
+        );
+        assert_eq!(text("win exactly 2 tricks"), "Win exactly two tricks");
+        assert_eq!(
+            text("win trick where all odd"),
+            "Win a trick that has only odd numbers"
+        );
+        assert_eq!(
+            text("any of (win first trick; NOT (win last trick only)) @ 2/3/3"),
+            "One of the following: Win the first trick; Do not complete: Win only the last trick"
+        );
+    }
+
+    #[test]
+    fn same_as_catalog() {
+        let tasks = [
+            ("win_green_3_yellow_4_5", "win cards G3, Y4, Y5 @ 3/4/4"),
+            ("dont_win_8_9", "don't win value [8 9] @ 3/3/2"),
+            (
+                "win_exactly_1_pink_1_green",
+                "win exactly 1 pink, 1 green @ 4",
+            ),
+            ("win_at_least_three_9s", "win at least 3 of 9 @ 3/4/5"),
+            (
+                "win_submarine_only_2",
+                "win submarine 2 only redeal if [S2 S4] or [S1 S2 S3] @ 3",
+            ),
+            ("win_green_2_in_last_trick", "win G2 in last trick @ 3/4/5"),
+            (
+                "win_first_and_last_trick",
+                "win first trick and last trick @ 3/4/4",
+            ),
+            (
+                "win_more_pink_than_green",
+                "win more pink than green @ 1/1/1",
+            ),
+            (
+                "win_less_tricks_than_captain",
+                "win fewer tricks than captain @ 2",
+            ),
+            ("win_x_tricks_hidden", "win x tricks hidden @ 4/3/3"),
+        ];
+        for (id, src) in tasks {
+            let expected = catalog::get(id).unwrap().build(4);
+            let task = parse_task(src, 4).unwrap();
+            assert_eq!(
+                task.describe(Locale::English),
+                expected.describe(Locale::English)
+            );
+            assert_eq!(task.difficulty().is_some(), expected.difficulty().is_some());
+        }
+        assert_eq!(
+            parse_task("win exactly 1 pink, 1 green @ 4", 4)
+                .unwrap()
+                .get_difficulty(5),
+            Some(4)
+        );
+        assert!(
+            parse_task("win more tricks than captain", 3)
+                .unwrap()
+                .captain_forbidden()
+        );
+    }
+
+    #[test]
+    fn render() {
+        for n_players in 3..=5 {
+            for entry in catalog::entries() {
+                let task = entry.build(n_players);
+                let parsed = parse_task(&task.to_dsl(), n_players).unwrap();
+                for locale in [Locale::English, Locale::French] {
+                    assert_eq!(
+                        parsed.describe(locale),
+                        task.describe(locale),
+                        "{}",
+                        entry.id
+                    );
+                }
+                assert_eq!(parsed, task, "{}", entry.id);
+            }
+        }
+        for src in [
+            "win x tricks hidden predicted 3 @ 4/3/3",
+            "win tricks [2 5] and last trick only",
+            "dont win cards [P1 G2]",
+            "win cards [Y9 B7]",
+            "win no cards [P1 P2 P3 P4 P5 P6 P7 P8 P9]",
+            "at least 2 of (win trick where total in [4 9] @ 1; not (win P7 with submarine); win more blue than yellow)",
+            "win exactly 2 pink, 0 submarine redeal if [S1 S2]",
+        ] {
+            assert_eq!(parse_task(src, 4).unwrap().to_dsl(), src);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |src| parse_task(src, 4).unwrap_err();
+        assert_eq!(
+            error("win cards [B1 B10]"),
+            ParseError {
+                line: 1,
+                column: 15,
+                kind: ParseErrorKind::InvalidCard("B10".to_string())
+            }
+        );
+        assert_eq!(error("win exactly 2 trucks").column, 15);
+        assert_eq!(
+            error("win trick where all").kind,
+            ParseErrorKind::Expected {
+                expected: "`even` or `odd` or `>` or `<`".to_string(),
+                found: "end of input".to_string()
+            }
+        );
+        assert_eq!(error("at least 3 of (win last trick)").column, 10);
+        assert!(matches!(
+            error("win more pink than pinks").kind,
+            ParseErrorKind::Invalid(_)
+        ));
+        assert_eq!(error("win last trick foo").column, 16);
+        assert_eq!(error("dont win value [0]").column, 17);
+        assert_eq!(error("dont win value [9 10]").column, 19);
+        assert_eq!(
+            error("win trick with value 0").kind,
+            ParseErrorKind::Invalid("expected a card value from 1 to 9".to_string())
+        );
+        assert_eq!(error("win trick with value 6 containing 10").column, 35);
+        assert_eq!(error("win at least 3 of 10").column, 19);
+        assert_eq!(error("win fewer tricks than everyone together").column, 32);
+
+        let invalid = |src| match error(src).kind {
+            ParseErrorKind::Invalid(message) => (error(src).column, message),
+            kind => panic!("{}: {:?}", src, kind),
+        };
+        assert_eq!(
+            invalid("win cards [B1 B1]"),
+            (15, "`B1` is listed more than once".to_string())
+        );
+        assert_eq!(invalid("dont win cards [S4, S4]").0, 21);
+        assert_eq!(invalid("dont win color pink, pinks").0, 22);
+        assert_eq!(invalid("win exactly 2 pink, 3 pink").0, 23);
+        assert_eq!(invalid("win at least 2 of 9, 3 of 9").0, 27);
+        assert_eq!(invalid("win tricks [2 2]").0, 15);
+        assert_eq!(invalid("win at least 3 pink redeal if [S1 S1]").0, 35);
+        assert_eq!(
+            invalid("dont win first 20 tricks"),
+            (16, "a game of 4 players has only 10 tricks".to_string())
+        );
+        assert_eq!(invalid("win tricks [20]").0, 13);
+        assert_eq!(invalid("win first 11 tricks").0, 11);
+        assert_eq!(invalid("win 20 consecutive tricks").0, 5);
+        assert_eq!(invalid("win exactly 99 tricks").0, 13);
+        assert_eq!(invalid("win x tricks predicted 99").0, 24);
+        assert!(parse_task("win exactly 13 tricks", 3).is_ok());
+        assert_eq!(
+            invalid("win exactly 20 submarine"),
+            (13, "there are only 4 `submarine` cards".to_string())
+        );
+        assert!(parse_task("win exactly 9 pink", 4).is_ok());
+        assert_eq!(invalid("win at least 5 of 3").0, 14);
+        assert_eq!(invalid("win trick where all > 100").0, 23);
+        assert_eq!(invalid("win trick where all > 9").0, 23);
+        assert_eq!(invalid("win trick where all < 1").0, 23);
+        assert!(parse_task("win trick where total > 35", 4).is_ok());
+        assert_eq!(invalid("win trick where total > 36").0, 25);
+        assert_eq!(invalid("win trick where total > 1000").0, 25);
+        assert_eq!(invalid("win trick where total < 0").0, 25);
+        assert_eq!(invalid("win trick where total < 4").0, 25);
+        assert!(parse_task("win trick where total < 5", 4).is_ok());
+        assert_eq!(invalid("win trick where total in [0 1000]").0, 27);
+
+        let e = parse_tasks("# tasks\nwin first trick\n\ndont win purple", 4).unwrap_err();
+        assert_eq!((e.line, e.column), (4, 10));
+        assert_eq!(
+            e.to_string(),
+            "line 4, column 10: expected `cards` or `color` or `value` or `tricks` or `first` or `consecutive`, found `purple`"
+        );
+    }
+}
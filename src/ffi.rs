@@ -0,0 +1,481 @@
+//! C API of the solver, exported by the `cdylib` build of the crate.
+//!
+//! The header `ffi/crew_solver.h` is generated from this module with
+//! `cbindgen --config ffi/cbindgen.toml --output ffi/crew_solver.h`, and the test program
+//! `ffi/test.c` shows how to use it.
+//!
+//! A game is created from its [text description](crate::game_file) or, with the `serde`
+//! feature, from the JSON `Game` of the `api` module, and lives behind an opaque
+//! [`CrewGame`] handle until [`crew_game_free`]. Every function returns a [`CrewStatus`]; the
+//! message of the last error of the calling thread is given by [`crew_last_error`]. Strings
+//! returned to C are owned by the handle and remain valid until it is solved again or freed.
+//!
+//! Panics are caught and reported as [`CrewStatus::Panic`], as they cannot unwind into C.
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString, c_char},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{
+    card::Card,
+    game_file::{self, GameFileErrorKind},
+    state::{GameError, State},
+};
+
+/// Result of a call, `CREW_STATUS_OK` (0) on success.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrewStatus {
+    Ok = 0,
+    /// A pointer given was null.
+    NullPointer = 1,
+    /// A string given is not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// The description of the game cannot be read.
+    Parse = 3,
+    /// The library was built without the feature needed by the call.
+    Unsupported = 4,
+    /// The game described cannot happen: wrong number of players, duplicate cards, no captain...
+    InvalidGame = 5,
+    /// A card played in the history of the game breaks the rules.
+    IllegalPlay = 6,
+    /// A communication or a distress signal of the game breaks the rules.
+    IllegalAction = 7,
+    /// The mission cannot be completed.
+    NoSolution = 8,
+    /// The solver gave up before finding a solution or proving there is none.
+    BudgetExceeded = 9,
+    Panic = 10,
+}
+
+impl From<&GameError> for CrewStatus {
+    fn from(e: &GameError) -> Self {
+        match e {
+            GameError::NoSolutionFound => CrewStatus::NoSolution,
+            GameError::BudgetExceeded(_) => CrewStatus::BudgetExceeded,
+            GameError::CardNotFound(..)
+            | GameError::CardNotHeld(..)
+            | GameError::MustFollowColor(..)
+            | GameError::PlayAfterGameOver(_)
+            | GameError::NotYourTurn(_) => CrewStatus::IllegalPlay,
+            GameError::AlreadyCommunicated(_)
+            | GameError::CommunicationDuringTrick
+            | GameError::CannotCommunicateSubmarine(_)
+            | GameError::FalseCommunication(..)
+            | GameError::DistressAfterStart
+            | GameError::InvalidDistressSize(..)
+            | GameError::CannotPassSubmarine(_) => CrewStatus::IllegalAction,
+            GameError::MissingCaptain
+            | GameError::InvalidTrickSize(..)
+            | GameError::NonIncreasingTrickIdx
+            | GameError::CaptainForbiddenTask(_)
+            | GameError::NotAPrediction(..)
+            | GameError::InvalidNPlayers(_)
+            | GameError::InvalidFirstPlayer(_)
+            | GameError::CurrentTrickFull(_)
+            | GameError::DuplicateCard(_)
+            | GameError::InvalidTaskAssignment(..)
+            | GameError::MissingTrick(_)
+            | GameError::TooManyRedeals(_)
+            | GameError::InvalidTask(_)
+            | GameError::UnevenHands(..)
+            | GameError::DuplicateTrick(_)
+            | GameError::InvalidTrickLeader(..)
+            | GameError::TrickNotHeldByWinner(..)
+            | GameError::InvalidSeat(_) => CrewStatus::InvalidGame,
+        }
+    }
+}
+
+/// A solution found by [`crew_game_solve`].
+struct Solution {
+    line: CString,
+    text: CString,
+}
+
+/// A game, created by [`crew_game_from_text`] or [`crew_game_from_json`].
+pub struct CrewGame {
+    state: State,
+    solution: Option<Solution>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// C strings cannot hold a NUL byte, which may come from a JSON escape.
+fn c_string(s: impl Into<String>) -> CString {
+    let s: String = s.into();
+    CString::new(s.replace('\0', "\u{fffd}")).expect("NUL bytes were replaced")
+}
+
+fn fail(status: CrewStatus, message: impl ToString) -> CrewStatus {
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(c_string(message.to_string())));
+    status
+}
+
+/// Runs `f`, catching its panics and clearing the last error before.
+fn guard<F>(f: F) -> CrewStatus
+where
+    F: FnOnce() -> CrewStatus,
+{
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| fail(CrewStatus::Panic, "The solver panicked."))
+}
+
+/// Reads a string given by C.
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn read_str<'a>(s: *const c_char) -> Result<&'a str, CrewStatus> {
+    if s.is_null() {
+        return Err(fail(CrewStatus::NullPointer, "A string given is null."));
+    }
+    // SAFETY: checked non-null, NUL-terminated by the contract of the caller.
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| fail(CrewStatus::InvalidUtf8, e))
+}
+
+/// Gives `state` to C in `*game`.
+///
+/// # Safety
+///
+/// `game` must be null or valid for writes.
+unsafe fn create(state: State, game: *mut *mut CrewGame) -> CrewStatus {
+    if game.is_null() {
+        return fail(CrewStatus::NullPointer, "The output handle is null.");
+    }
+    let handle = Box::new(CrewGame {
+        state,
+        solution: None,
+    });
+    // SAFETY: checked non-null, valid for writes by the contract of the caller.
+    unsafe { *game = Box::into_raw(handle) };
+    CrewStatus::Ok
+}
+
+/// Cards played from `start` to reach `solution`, in order.
+fn line(start: &State, solution: &State) -> Vec<Card> {
+    let first = start.get_current_trick();
+    let mut tricks: Vec<_> = solution
+        .get_players()
+        .iter()
+        .flat_map(|p| p.get_tricks())
+        .filter(|t| t.idx() >= first.idx())
+        .collect();
+    tricks.sort_by_key(|t| t.idx());
+    tricks.push(solution.get_current_trick());
+
+    let mut cards = vec![];
+    for trick in tricks {
+        let skip = if trick.idx() == first.idx() {
+            first.len()
+        } else {
+            0
+        };
+        cards.extend(trick.iter().skip(skip));
+    }
+    cards
+}
+
+/// Creates a game from its text description (see the `game_file` module of the crate).
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string, and `game` must be null or valid
+/// for writes. On success, `*game` must be freed with [`crew_game_free`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crew_game_from_text(
+    text: *const c_char,
+    game: *mut *mut CrewGame,
+) -> CrewStatus {
+    guard(|| {
+        // SAFETY: same contract as this function.
+        let text = match unsafe { read_str(text) } {
+            Ok(text) => text,
+            Err(status) => return status,
+        };
+        match game_file::parse_game(text) {
+            // SAFETY: same contract as this function.
+            Ok(state) => unsafe { create(state, game) },
+            Err(e) => match &e.kind {
+                GameFileErrorKind::Game(error) => fail(CrewStatus::from(error), e),
+                _ => fail(CrewStatus::Parse, e),
+            },
+        }
+    })
+}
+
+/// Creates a game from the JSON description of the API: the hands dealt, the tasks by catalog
+/// ID and the cards played. Returns `CREW_STATUS_UNSUPPORTED` without the `serde` feature.
+///
+/// # Safety
+///
+/// Same as [`crew_game_from_text`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crew_game_from_json(
+    json: *const c_char,
+    game: *mut *mut CrewGame,
+) -> CrewStatus {
+    guard(|| {
+        // SAFETY: same contract as this function.
+        let json = match unsafe { read_str(json) } {
+            Ok(json) => json,
+            Err(status) => return status,
+        };
+        #[cfg(feature = "serde")]
+        {
+            let description: crate::api::Game = match serde_json::from_str(json) {
+                Ok(description) => description,
+                Err(e) => return fail(CrewStatus::Parse, e),
+            };
+            let state = match description.to_state() {
+                Ok(state) => state,
+                Err(e) => {
+                    let status = e
+                        .game_error
+                        .as_ref()
+                        .map_or(CrewStatus::InvalidGame, From::from);
+                    return fail(status, e);
+                }
+            };
+            // SAFETY: same contract as this function.
+            unsafe { create(state, game) }
+        }
+        #[cfg(not(feature = "serde"))]
+        {
+            let _ = (json, game);
+            fail(
+                CrewStatus::Unsupported,
+                "JSON games need the `serde` feature.",
+            )
+        }
+    })
+}
+
+/// Looks for a way to complete the mission, exploring at most `budget` states (or without
+/// limit if `budget` is 0). On success, the solution is given by [`crew_game_solution_line`]
+/// and [`crew_game_solution_text`]; the game itself is left as it was.
+///
+/// # Safety
+///
+/// `game` must be null or a handle which was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crew_game_solve(game: *mut CrewGame, budget: usize) -> CrewStatus {
+    guard(|| {
+        // SAFETY: a live handle by the contract of the caller.
+        let Some(game) = (unsafe { game.as_mut() }) else {
+            return fail(CrewStatus::NullPointer, "The game handle is null.");
+        };
+        game.solution = None;
+        let mut state = game.state.clone();
+        let result = if budget == 0 {
+            state.play()
+        } else {
+            state.play_with_budget(budget)
+        };
+        match result {
+            Ok(solution) => {
+                let cards = line(&game.state, &solution);
+                let line: Vec<String> = cards.iter().map(Card::to_string).collect();
+                game.solution = Some(Solution {
+                    line: c_string(line.join(" ")),
+                    text: c_string(game_file::write_game(&solution)),
+                });
+                CrewStatus::Ok
+            }
+            Err(e) => fail(CrewStatus::from(&e), e),
+        }
+    })
+}
+
+/// The cards to play to complete the mission, in order and separated by spaces (as in
+/// `P1 S4 B2`), or null if the game is not solved.
+///
+/// # Safety
+///
+/// `game` must be null or a handle which was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crew_game_solution_line(game: *const CrewGame) -> *const c_char {
+    // SAFETY: a live handle by the contract of the caller.
+    match unsafe { game.as_ref() }.and_then(|g| g.solution.as_ref()) {
+        Some(solution) => solution.line.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// The text description of the game once the mission is completed, or null if the game is
+/// not solved.
+///
+/// # Safety
+///
+/// `game` must be null or a handle which was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crew_game_solution_text(game: *const CrewGame) -> *const c_char {
+    // SAFETY: a live handle by the contract of the caller.
+    match unsafe { game.as_ref() }.and_then(|g| g.solution.as_ref()) {
+        Some(solution) => solution.text.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Frees a game. Does nothing if `game` is null.
+///
+/// # Safety
+///
+/// `game` must be null or a handle which was not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn crew_game_free(game: *mut CrewGame) {
+    if !game.is_null() {
+        // SAFETY: created by `Box::into_raw` and not freed, by the contract of the caller.
+        drop(unsafe { Box::from_raw(game) });
+    }
+}
+
+/// The message of the last error of the calling thread, or null if its last call succeeded.
+/// The string remains valid until the next call of this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn crew_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        card::{blue, pink},
+        task::{self, TaskStatus},
+    };
+    use rand::{SeedableRng, rngs::StdRng};
+
+    const GAME: &str =
+        "seat 0\nhand: P1 B2\nseat 1\nhand: S4 P3\ntask: win_pink_1\nseat 2\nhand: P5 B1\n";
+
+    fn from_text(text: &str) -> (CrewStatus, *mut CrewGame) {
+        let text = CString::new(text).unwrap();
+        let mut game = ptr::null_mut();
+        let status = unsafe { crew_game_from_text(text.as_ptr(), &mut game) };
+        (status, game)
+    }
+
+    fn read(s: *const c_char) -> Option<String> {
+        (!s.is_null()).then(|| unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn solve() {
+        let (status, game) = from_text(GAME);
+        assert_eq!(status, CrewStatus::Ok);
+        assert_eq!(read(unsafe { crew_game_solution_line(game) }), None);
+        assert_eq!(unsafe { crew_game_solve(game, 1000) }, CrewStatus::Ok);
+        assert_eq!(read(crew_last_error()), None);
+        assert_eq!(
+            read(unsafe { crew_game_solution_line(game) }).unwrap(),
+            "S4 B1 P1"
+        );
+        let text = read(unsafe { crew_game_solution_text(game) }).unwrap();
+        let solution = game_file::parse_game(&text).unwrap();
+        assert_eq!(solution.game_status(), TaskStatus::Done);
+        unsafe { crew_game_free(game) };
+    }
+
+    #[test]
+    fn line_from_position() {
+        let text = GAME.replace("S4 P3", "P3") + "current: #0 @1: S4\n";
+        let (status, game) = from_text(&text);
+        assert_eq!(status, CrewStatus::Ok);
+        assert_eq!(unsafe { crew_game_solve(game, 0) }, CrewStatus::Ok);
+        assert_eq!(
+            read(unsafe { crew_game_solution_line(game) }).unwrap(),
+            "B1 P1"
+        );
+        unsafe { crew_game_free(game) };
+    }
+
+    #[test]
+    fn errors() {
+        let (status, game) = from_text("seat 0\nhand: P12\n");
+        assert_eq!((status, game), (CrewStatus::Parse, ptr::null_mut()));
+        assert!(read(crew_last_error()).unwrap().contains("P12"));
+        assert_eq!(
+            unsafe { crew_game_from_text(ptr::null(), &mut ptr::null_mut()) },
+            CrewStatus::NullPointer
+        );
+        let text = CString::new(GAME).unwrap();
+        assert_eq!(
+            unsafe { crew_game_from_text(text.as_ptr(), ptr::null_mut()) },
+            CrewStatus::NullPointer
+        );
+        let invalid = CString::new([0xff, 0xfe]).unwrap();
+        assert_eq!(
+            unsafe { crew_game_from_text(invalid.as_ptr(), &mut ptr::null_mut()) },
+            CrewStatus::InvalidUtf8
+        );
+        assert_eq!(
+            unsafe { crew_game_solve(ptr::null_mut(), 0) },
+            CrewStatus::NullPointer
+        );
+        unsafe { crew_game_free(ptr::null_mut()) };
+
+        let (_, game) = from_text(&format!("{}task: win_pink_1\n", GAME));
+        assert_eq!(unsafe { crew_game_solve(game, 0) }, CrewStatus::NoSolution);
+        assert_eq!(
+            read(crew_last_error()).unwrap(),
+            GameError::NoSolutionFound.to_string()
+        );
+        assert_eq!(read(unsafe { crew_game_solution_line(game) }), None);
+        unsafe { crew_game_free(game) };
+
+        // A full game cannot be solved within a few states
+        let mut state = State::new_random_with_rng(3, &mut StdRng::seed_from_u64(2));
+        state
+            .get_mut_player(0)
+            .add_task(task::win_all_3s())
+            .unwrap();
+        let (_, game) = from_text(&game_file::write_game(&state));
+        assert_eq!(
+            unsafe { crew_game_solve(game, 1) },
+            CrewStatus::BudgetExceeded
+        );
+        unsafe { crew_game_free(game) };
+    }
+
+    #[test]
+    fn status() {
+        assert_eq!(
+            CrewStatus::from(&GameError::MustFollowColor(0, 0, pink(1), blue(1))),
+            CrewStatus::IllegalPlay
+        );
+        assert_eq!(
+            CrewStatus::from(&GameError::DistressAfterStart),
+            CrewStatus::IllegalAction
+        );
+        assert_eq!(
+            CrewStatus::from(&GameError::DuplicateCard(pink(1))),
+            CrewStatus::InvalidGame
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json = CString::new(
+            r#"{"hands": [["P1", "B2"], ["S4", "P3"], ["P5", "B1"]],
+                "tasks": [[], [{"id": "win_pink_1"}], []]}"#,
+        )
+        .unwrap();
+        let mut game = ptr::null_mut();
+        let status = unsafe { crew_game_from_json(json.as_ptr(), &mut game) };
+        if cfg!(feature = "serde") {
+            assert_eq!(status, CrewStatus::Ok);
+            assert_eq!(unsafe { crew_game_solve(game, 0) }, CrewStatus::Ok);
+            unsafe { crew_game_free(game) };
+        } else {
+            assert_eq!((status, game), (CrewStatus::Unsupported, ptr::null_mut()));
+        }
+    }
+}
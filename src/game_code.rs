@@ -0,0 +1,471 @@
+//! Short game codes, to share a position as a link.
+//!
+//! A code holds the deal, the tasks and the cards played since the start of the game, packed
+//! as a single number in base64url (without padding), after a version byte. The deal is the
+//! index of the permutation of the 40 cards giving the hands in seat order, tasks are catalog
+//! entries (see [`catalog`]), and each card played is its index among the cards the player was
+//! allowed to play, so that a forced card takes no room at all. A four-player game takes
+//! around 40 characters.
+//!
+//! Only games dealing the whole deck can be shared, with catalog tasks. Decoding replays the
+//! game, so that a code always gives a valid state.
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use thiserror::Error;
+
+use crate::{
+    card::{self, Card, NB_CARDS},
+    catalog,
+    communication::{Communication, CommunicationKind},
+    player::{self, Player},
+    state::State,
+    task::Task,
+};
+
+/// Version of the game codes, to be increased whenever their layout changes.
+pub const CODE_VERSION: u8 = 1;
+
+const KINDS: [CommunicationKind; 3] = [
+    CommunicationKind::Highest,
+    CommunicationKind::Only,
+    CommunicationKind::Lowest,
+];
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum GameCodeError {
+    #[error("Only games dealing the whole deck can be shared, got {0} cards.")]
+    IncompleteDeck(usize),
+    #[error("Hands of {0:?} cards are not a deal of the game.")]
+    InvalidDeal(Vec<usize>),
+    #[error("Task {1:?} of player {0} is not in the catalog.")]
+    NotInCatalog(usize, Task),
+    #[error("The game cannot be replayed: {0}")]
+    InvalidHistory(String),
+    #[error("The game code is not valid base64url.")]
+    InvalidBase64,
+    #[error("Unsupported game code version {0}, expected {CODE_VERSION}.")]
+    UnsupportedVersion(u8),
+    #[error("The game code is malformed: {0}")]
+    Malformed(String),
+}
+
+/// A non-negative integer of arbitrary size, as little-endian 32-bit limbs.
+#[derive(Debug, Default)]
+struct Number(Vec<u32>);
+
+impl Number {
+    fn from_be_bytes(bytes: &[u8]) -> Self {
+        let mut n = Number::default();
+        for &b in bytes {
+            n.mul_add(256, b as u64);
+        }
+        n
+    }
+
+    fn to_be_bytes(&self) -> Vec<u8> {
+        let bytes = self.0.iter().rev().flat_map(|l| l.to_be_bytes());
+        bytes.skip_while(|&b| b == 0).collect()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|&l| l == 0)
+    }
+
+    /// `self = self * m + a`
+    fn mul_add(&mut self, m: u64, a: u64) {
+        let mut carry = a;
+        for limb in &mut self.0 {
+            let x = *limb as u64 * m + carry;
+            *limb = x as u32;
+            carry = x >> 32;
+        }
+        if carry > 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    /// Divides `self` by `d` in place, returning the remainder.
+    fn div_rem(&mut self, d: u64) -> u64 {
+        let mut rem = 0;
+        for limb in self.0.iter_mut().rev() {
+            let x = (rem << 32) | *limb as u64;
+            *limb = (x / d) as u32;
+            rem = x % d;
+        }
+        rem
+    }
+}
+
+/// Digits in mixed radix, the first one pushed being the least significant, so that they are
+/// read back in the same order.
+#[derive(Default)]
+struct Writer(Vec<(usize, usize)>);
+
+impl Writer {
+    fn push(&mut self, digit: usize, radix: usize) {
+        debug_assert!(digit < radix);
+        self.0.push((digit, radix));
+    }
+
+    fn finish(self) -> Number {
+        let mut n = Number::default();
+        for &(digit, radix) in self.0.iter().rev() {
+            n.mul_add(radix as u64, digit as u64);
+        }
+        n
+    }
+}
+
+struct Reader(Number);
+
+impl Reader {
+    fn pop(&mut self, radix: usize) -> usize {
+        self.0.div_rem(radix as u64) as usize
+    }
+}
+
+/// Index of `card` in [`card::deck`].
+fn deck_index(deck: &[Card], card: &Card) -> usize {
+    deck.iter().position(|c| c == card).expect("valid card")
+}
+
+/// The cards `state` allows the current player to play, in deck order.
+fn legal_moves(state: &State, deck: &[Card]) -> Vec<Card> {
+    let mut cards = state
+        .get_current_player()
+        .get_hand()
+        .playable_cards(state.get_current_trick().first());
+    cards.sort_by_key(|c| deck_index(deck, c));
+    cards
+}
+
+/// Index of the catalog entry giving `task`, and its prediction digit for the "Win X tricks"
+/// tasks (0 before the prediction, `x + 1` after it).
+fn catalog_digits(task: &Task, n_players: usize) -> Option<(usize, Option<usize>)> {
+    catalog::entries()
+        .iter()
+        .enumerate()
+        .find_map(|(i, entry)| match (entry.build(n_players), task) {
+            (Task::WinNbTricks(mut built), Task::WinNbTricks(t))
+                if built.prediction().is_none() =>
+            {
+                if let Some(x) = t.prediction() {
+                    built.predict(x);
+                }
+                (built == *t).then_some((i, Some(t.prediction().map_or(0, |x| x + 1))))
+            }
+            (built, _) => (built == *task).then_some((i, None)),
+        })
+}
+
+/// Encodes `state` as a short URL-safe code, to be read back with [`decode_game`].
+pub fn encode_game(state: &State) -> Result<String, GameCodeError> {
+    let n_players = state.n_players();
+    state
+        .validate()
+        .map_err(|e| GameCodeError::InvalidHistory(e.to_string()))?;
+    let (hands, plays) = state
+        .history()
+        .map_err(|e| GameCodeError::InvalidHistory(e.to_string()))?;
+    let n_cards = hands.iter().map(|h| h.len()).sum();
+    if n_cards != NB_CARDS {
+        return Err(GameCodeError::IncompleteDeck(n_cards));
+    }
+
+    let mut w = Writer::default();
+    w.push(n_players - 3, 3);
+    let base_size = NB_CARDS / n_players;
+    if !NB_CARDS.is_multiple_of(n_players) {
+        for hand in &hands {
+            match hand.len().checked_sub(base_size) {
+                Some(extra @ (0 | 1)) => w.push(extra, 2),
+                _ => {
+                    let sizes = hands.iter().map(|h| h.len()).collect();
+                    return Err(GameCodeError::InvalidDeal(sizes));
+                }
+            }
+        }
+    } else if hands.iter().any(|h| h.len() != base_size) {
+        let sizes = hands.iter().map(|h| h.len()).collect();
+        return Err(GameCodeError::InvalidDeal(sizes));
+    }
+
+    let deck = card::deck();
+    let mut remaining = deck.clone();
+    for card in hands.iter().flat_map(|h| h.iter()) {
+        let i = deck_index(&remaining, card);
+        w.push(i, remaining.len());
+        remaining.remove(i);
+    }
+
+    let n_entries = catalog::entries().len();
+    let n_predictions = player::n_tricks_total(n_players) + 2;
+    for (ip, p) in state.get_players().iter().enumerate() {
+        for task in p.get_tasks() {
+            let Some((entry, prediction)) = catalog_digits(task, n_players) else {
+                return Err(GameCodeError::NotInCatalog(ip, task.clone()));
+            };
+            w.push(entry + 1, n_entries + 1);
+            if let Some(prediction) = prediction {
+                w.push(prediction, n_predictions);
+            }
+        }
+        w.push(0, n_entries + 1);
+    }
+
+    for c in state.get_communications() {
+        w.push(1, 2);
+        w.push(c.seat, n_players);
+        w.push(deck_index(&deck, &c.card), NB_CARDS);
+        w.push(
+            KINDS.iter().position(|&k| k == c.kind).unwrap(),
+            KINDS.len(),
+        );
+    }
+    w.push(0, 2);
+
+    // The cards played are checked by replaying them from the deal
+    let tasks = state
+        .get_players()
+        .iter()
+        .map(|p| p.get_tasks().to_vec())
+        .collect();
+    let mut replay = State::replay(hands, tasks, &[])
+        .map_err(|e| GameCodeError::InvalidHistory(e.to_string()))?;
+    w.push(plays.len(), NB_CARDS + 1);
+    for card in &plays {
+        let legal = legal_moves(&replay, &deck);
+        let Some(i) = legal.iter().position(|c| c == card) else {
+            return Err(GameCodeError::InvalidHistory(format!(
+                "{} cannot be played by player {}",
+                card,
+                replay.get_current_player_idx()
+            )));
+        };
+        w.push(i, legal.len());
+        replay.play_card(card).expect("a legal card can be played");
+    }
+    if replay.get_players() != state.get_players()
+        || replay.get_current_trick() != state.get_current_trick()
+    {
+        return Err(GameCodeError::InvalidHistory(
+            "the tricks are not won by the right players".to_string(),
+        ));
+    }
+
+    let mut bytes = vec![CODE_VERSION];
+    bytes.extend(w.finish().to_be_bytes());
+    Ok(BASE64_URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Reads a code written by [`encode_game`], replaying the game it describes.
+pub fn decode_game(code: &str) -> Result<State, GameCodeError> {
+    let bytes = BASE64_URL_SAFE_NO_PAD
+        .decode(code.trim())
+        .map_err(|_| GameCodeError::InvalidBase64)?;
+    match bytes.first() {
+        Some(&CODE_VERSION) => (),
+        Some(&version) => return Err(GameCodeError::UnsupportedVersion(version)),
+        None => return Err(GameCodeError::Malformed("empty code".to_string())),
+    }
+    let mut r = Reader(Number::from_be_bytes(&bytes[1..]));
+
+    let n_players = r.pop(3) + 3;
+    let base_size = NB_CARDS / n_players;
+    let sizes: Vec<usize> = if !NB_CARDS.is_multiple_of(n_players) {
+        (0..n_players).map(|_| base_size + r.pop(2)).collect()
+    } else {
+        vec![base_size; n_players]
+    };
+    if sizes.iter().sum::<usize>() != NB_CARDS {
+        return Err(GameCodeError::InvalidDeal(sizes));
+    }
+
+    let deck = card::deck();
+    let mut remaining = deck.clone();
+    let mut players = vec![];
+    for size in sizes {
+        let hand: Vec<Card> = (0..size)
+            .map(|_| remaining.remove(r.pop(remaining.len())))
+            .collect();
+        players.push(Player::new(hand.into()));
+    }
+
+    let entries = catalog::entries();
+    let n_predictions = player::n_tricks_total(n_players) + 2;
+    for p in &mut players {
+        loop {
+            let entry = r.pop(entries.len() + 1);
+            if entry == 0 {
+                break;
+            }
+            let mut task = entries[entry - 1].build(n_players);
+            if let Task::WinNbTricks(t) = &mut task
+                && t.prediction().is_none()
+            {
+                let prediction = r.pop(n_predictions);
+                if prediction > 0 {
+                    t.predict(prediction - 1);
+                }
+            }
+            p.add_task(task)
+                .map_err(|e| GameCodeError::Malformed(e.to_string()))?;
+        }
+    }
+    // The whole deck is dealt: there is a captain
+    let mut state = State::new(players);
+
+    let mut communications = vec![];
+    while r.pop(2) == 1 {
+        communications.push(Communication {
+            seat: r.pop(n_players),
+            card: deck[r.pop(NB_CARDS)],
+            kind: KINDS[r.pop(KINDS.len())],
+        });
+    }
+
+    let n_plays = r.pop(NB_CARDS + 1);
+    for _ in 0..n_plays {
+        let legal = legal_moves(&state, &deck);
+        if legal.is_empty() {
+            return Err(GameCodeError::Malformed(
+                "too many cards played".to_string(),
+            ));
+        }
+        let card = legal[r.pop(legal.len())];
+        state.play_card(&card).expect("a legal card can be played");
+    }
+    if !r.0.is_zero() {
+        return Err(GameCodeError::Malformed("trailing data".to_string()));
+    }
+
+    state.get_mut_communications().extend(communications);
+    state
+        .validate()
+        .map_err(|e| GameCodeError::Malformed(e.to_string()))?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    use super::*;
+    use crate::{
+        card::{blue, submarine},
+        task::{self, win_nb_tricks::TaskWinNbTricks},
+    };
+
+    fn random_game(n_players: usize, n_plays: usize, rng: &mut StdRng) -> State {
+        let entries = catalog::entries();
+        let mut state = State::new_random_with_rng(n_players, rng);
+        for ip in 0..n_players {
+            for _ in 0..2 {
+                let task = entries.choose(rng).unwrap().build(n_players);
+                let _ = state.get_mut_player(ip).add_task(task);
+            }
+        }
+        let seat = state.first_player();
+        let hand = state.get_player(seat).get_hand().clone();
+        for c in hand.iter().filter(|c| !c.is_submarine()) {
+            let communication = Communication {
+                seat,
+                card: *c,
+                kind: CommunicationKind::Lowest,
+            };
+            if state.communicate(communication).is_ok() {
+                break;
+            }
+        }
+        for _ in 0..n_plays {
+            let playable = state
+                .get_current_player()
+                .get_hand()
+                .playable_cards(state.get_current_trick().first());
+            let Some(card) = playable.choose(rng) else {
+                break;
+            };
+            state.play_card(card).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(44);
+        for n_players in 3..=5 {
+            for n_plays in [0, 7, 25, NB_CARDS] {
+                let state = random_game(n_players, n_plays, &mut rng);
+                let code = encode_game(&state).unwrap();
+                assert!(code.len() <= 60, "{} is too long", code);
+                assert_eq!(decode_game(&code), Ok(state));
+            }
+        }
+    }
+
+    #[test]
+    fn predictions() {
+        let mut rng = StdRng::seed_from_u64(45);
+        let mut state = State::new_random_with_rng(4, &mut rng);
+        let ip = (state.first_player() + 1) % 4;
+        let mut prediction = task::win_x_tricks_hidden();
+        prediction.predict(3);
+        let player = state.get_mut_player(ip);
+        player.add_task(prediction).unwrap();
+        player.add_task(task::win_x_tricks()).unwrap();
+        let code = encode_game(&state).unwrap();
+        assert_eq!(decode_game(&code), Ok(state));
+    }
+
+    #[test]
+    fn encode_errors() {
+        let mut rng = StdRng::seed_from_u64(46);
+        let mut state = State::new_random_with_rng(3, &mut rng);
+        let ip = (state.first_player() + 1) % 3;
+        state
+            .get_mut_player(ip)
+            .add_task(TaskWinNbTricks::new(None, 2))
+            .unwrap();
+        assert!(matches!(
+            encode_game(&state),
+            Err(GameCodeError::NotInCatalog(i, _)) if i == ip
+        ));
+
+        let small = State::new([
+            Player::new([submarine(4)].into()),
+            Player::new([blue(1)].into()),
+            Player::new([blue(2)].into()),
+        ]);
+        assert_eq!(encode_game(&small), Err(GameCodeError::IncompleteDeck(3)));
+    }
+
+    #[test]
+    fn decode_errors() {
+        let mut rng = StdRng::seed_from_u64(47);
+        let code = encode_game(&random_game(4, 10, &mut rng)).unwrap();
+        let mut bytes = BASE64_URL_SAFE_NO_PAD.decode(&code).unwrap();
+
+        assert_eq!(
+            decode_game("not a code!"),
+            Err(GameCodeError::InvalidBase64)
+        );
+        assert_eq!(
+            decode_game(""),
+            Err(GameCodeError::Malformed("empty code".to_string()))
+        );
+        bytes[0] = CODE_VERSION + 1;
+        assert_eq!(
+            decode_game(&BASE64_URL_SAFE_NO_PAD.encode(&bytes)),
+            Err(GameCodeError::UnsupportedVersion(CODE_VERSION + 1))
+        );
+
+        // Any other number is either rejected or a valid game
+        for len in 1..60 {
+            let mut bytes: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+            bytes[0] = CODE_VERSION;
+            if let Ok(state) = decode_game(&BASE64_URL_SAFE_NO_PAD.encode(&bytes)) {
+                let code = encode_game(&state).unwrap();
+                assert_eq!(decode_game(&code), Ok(state));
+            }
+        }
+    }
+}
@@ -0,0 +1,586 @@
+//! A plain-text description of a game, to write positions by hand and share them.
+//!
+//! Each seat lists its hand, its tasks and the tricks it has won. Tasks are given either by
+//! their [catalog](crate::catalog) ID or in the [task language](crate::dsl). Cards and tricks
+//! use the short notation of [`Card`] and [`Trick`]. Lines starting with `#` are comments.
+//!
+//! ```text
+//! seat 0
+//! hand: S4 Y6 B6 P2 P5
+//! task: win trick where all odd
+//! task: win at least 5 pink
+//! won: #3 @3: G1 G9 G8 G6
+//!
+//! seat 1
+//! hand: B9 B5 G5 G4 G2
+//! task: dont_win_submarine
+//!
+//! communication: 1 B9 highest
+//! current: #5 @0:
+//! ```
+//!
+//! The `current` trick (its index, the seat which opened it and the cards played so far) may
+//! be omitted at the start of a game, in which case the captain opens the first trick.
+//! [`write_game`] prints a game in this format, and [`parse_game`] reads it back.
+use std::{collections::HashMap, fmt::Write};
+
+use thiserror::Error;
+
+use crate::{
+    card::{Card, ParseCardError},
+    catalog,
+    communication::{Communication, CommunicationKind},
+    dsl::{self, ParseErrorKind},
+    player::{self, Player},
+    state::{GameError, State},
+    task::{BaseTask, Task},
+    trick::Trick,
+};
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum GameFileErrorKind {
+    #[error("expected {0}")]
+    Expected(String),
+    #[error("{0}")]
+    Card(#[from] ParseCardError),
+    #[error("card {card} already appears on line {line}")]
+    DuplicateCard { card: Card, line: usize },
+    #[error("{0}")]
+    Task(ParseErrorKind),
+    #[error("{0}")]
+    Invalid(String),
+    /// The game described breaks a rule.
+    #[error("{0}")]
+    Game(GameError),
+}
+
+/// Error with its position in the file (lines and columns start at 1).
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("line {line}, column {column}: {kind}")]
+pub struct GameFileError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: GameFileErrorKind,
+}
+
+/// Words of `s` separated by whitespace or commas, along with their byte offset.
+fn words(s: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        if c.is_whitespace() || c == ',' {
+            if let Some(start) = start.take() {
+                words.push((start, &s[start..i]));
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(start) = start {
+        words.push((start, &s[start..]));
+    }
+    words
+}
+
+/// Position of some text in the file, to report errors found once the whole file is read.
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn error(self, kind: GameFileErrorKind) -> GameFileError {
+        GameFileError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+struct Seat {
+    position: Position,
+    hand: Option<Vec<Card>>,
+    tasks: Vec<(Position, String)>,
+    won: Vec<(Position, Trick)>,
+}
+
+#[derive(Default)]
+struct Parser<'a> {
+    line: usize,
+    text: &'a str,
+    seats: Vec<Seat>,
+    communications: Vec<(Position, Communication)>,
+    current: Option<(Position, Trick)>,
+    /// Line on which each card was seen.
+    cards: HashMap<Card, usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn position(&self, byte: usize) -> Position {
+        Position {
+            line: self.line,
+            column: self.text[..byte].chars().count() + 1,
+        }
+    }
+
+    fn error_at(&self, byte: usize, kind: GameFileErrorKind) -> GameFileError {
+        self.position(byte).error(kind)
+    }
+
+    fn expected(&self, byte: usize, expected: &str) -> GameFileError {
+        self.error_at(byte, GameFileErrorKind::Expected(expected.to_string()))
+    }
+
+    fn parse_line(&mut self, line: usize, text: &'a str) -> Result<(), GameFileError> {
+        self.line = line;
+        self.text = text;
+        let start = text.len() - text.trim_start().len();
+        let content = text.trim();
+        if content.is_empty() || content.starts_with('#') {
+            return Ok(());
+        }
+
+        let Some((key, value)) = content.split_once(':') else {
+            return self.seat(start, content);
+        };
+        let value_start = start + key.len() + 1;
+        match key.trim().to_ascii_lowercase().as_str() {
+            "hand" => {
+                let cards = self.cards(value, value_start)?;
+                let seat = self.current_seat(start, "hand")?;
+                if seat.hand.replace(cards).is_some() {
+                    return Err(self.error_at(
+                        start,
+                        GameFileErrorKind::Invalid("the hand is already given".to_string()),
+                    ));
+                }
+            }
+            "task" => {
+                let position =
+                    self.position(value_start + (value.len() - value.trim_start().len()));
+                self.current_seat(start, "task")?
+                    .tasks
+                    .push((position, value.trim().to_string()));
+            }
+            "won" => {
+                let trick = self.trick(value, value_start)?;
+                let position = self.position(start);
+                self.current_seat(start, "won")?.won.push((position, trick));
+            }
+            "communication" => {
+                let communication = self.communication(value, value_start)?;
+                self.communications
+                    .push((self.position(start), communication));
+            }
+            "current" => {
+                let trick = self.trick(value, value_start)?;
+                if self.current.is_some() {
+                    return Err(self.error_at(
+                        start,
+                        GameFileErrorKind::Invalid(
+                            "the current trick is already given".to_string(),
+                        ),
+                    ));
+                }
+                self.current = Some((self.position(start), trick));
+            }
+            _ => {
+                return Err(
+                    self.expected(start, "`hand`, `task`, `won`, `communication` or `current`")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// A `seat <index>` line, seats being listed in order.
+    fn seat(&mut self, start: usize, content: &str) -> Result<(), GameFileError> {
+        let expected = format!("`seat {}`", self.seats.len());
+        match words(content)[..] {
+            [(_, keyword), (offset, index)] if keyword.eq_ignore_ascii_case("seat") => {
+                if index.parse() != Ok(self.seats.len()) {
+                    return Err(self.expected(start + offset, &expected));
+                }
+            }
+            _ => return Err(self.expected(start, &expected)),
+        }
+        self.seats.push(Seat {
+            position: self.position(start),
+            hand: None,
+            tasks: vec![],
+            won: vec![],
+        });
+        Ok(())
+    }
+
+    fn current_seat(&mut self, start: usize, key: &str) -> Result<&mut Seat, GameFileError> {
+        if self.seats.is_empty() {
+            return Err(self.error_at(
+                start,
+                GameFileErrorKind::Invalid(format!("`{}` must follow a `seat` line", key)),
+            ));
+        }
+        Ok(self.seats.last_mut().unwrap())
+    }
+
+    /// A list of cards starting at byte `base` of the line, each seen for the first time.
+    fn cards(&mut self, s: &str, base: usize) -> Result<Vec<Card>, GameFileError> {
+        let mut cards = vec![];
+        for (offset, word) in words(s) {
+            let card: Card = word
+                .parse()
+                .map_err(|e: ParseCardError| self.error_at(base + offset, e.into()))?;
+            if let Some(&line) = self.cards.get(&card) {
+                return Err(self.error_at(
+                    base + offset,
+                    GameFileErrorKind::DuplicateCard { card, line },
+                ));
+            }
+            self.cards.insert(card, self.line);
+            cards.push(card);
+        }
+        Ok(cards)
+    }
+
+    /// A trick written as `#<index> @<first player>: <cards>`.
+    fn trick(&mut self, s: &str, base: usize) -> Result<Trick, GameFileError> {
+        let header_expected = "`#<index> @<first player>:`";
+        let Some((header, cards)) = s.split_once(':') else {
+            return Err(self.expected(base, header_expected));
+        };
+        let number = |word: &str, prefix| word.strip_prefix(prefix).and_then(|n| n.parse().ok());
+        let (idx, first_player) = match words(header)[..] {
+            [(o1, w1), (o2, w2)] => match (number(w1, '#'), number(w2, '@')) {
+                (Some(idx), Some(first_player)) => (idx, first_player),
+                (None, _) => return Err(self.expected(base + o1, "`#<index>`")),
+                (_, None) => return Err(self.expected(base + o2, "`@<first player>`")),
+            },
+            _ => return Err(self.expected(base, header_expected)),
+        };
+        let cards = self.cards(cards, base + header.len() + 1)?;
+        Ok((idx, first_player, cards).into())
+    }
+
+    /// A communication written as `<seat> <card> <highest|only|lowest>`.
+    fn communication(&mut self, s: &str, base: usize) -> Result<Communication, GameFileError> {
+        let words = words(s);
+        let &[(o1, seat), (o2, card), (o3, kind)] = &words[..] else {
+            return Err(self.expected(base, "`<seat> <card> <highest|only|lowest>`"));
+        };
+        let seat = seat
+            .parse()
+            .map_err(|_| self.expected(base + o1, "a seat"))?;
+        let card = card
+            .parse()
+            .map_err(|e: ParseCardError| self.error_at(base + o2, e.into()))?;
+        let kind = match kind.to_ascii_lowercase().as_str() {
+            "highest" => CommunicationKind::Highest,
+            "only" => CommunicationKind::Only,
+            "lowest" => CommunicationKind::Lowest,
+            _ => return Err(self.expected(base + o3, "`highest`, `only` or `lowest`")),
+        };
+        Ok(Communication { seat, card, kind })
+    }
+
+    /// Builds the game once the whole file is read, as tasks depend on the number of players.
+    fn finish(self, n_lines: usize) -> Result<State, GameFileError> {
+        let end = Position {
+            line: n_lines.max(1),
+            column: 1,
+        };
+        let n_players = self.seats.len();
+        if n_players == 0 {
+            return Err(end.error(GameFileErrorKind::Expected("`seat 0`".to_string())));
+        }
+        let first_seat = self.seats[0].position;
+        if let Err(e) = player::check_valid_n_players(n_players) {
+            return Err(first_seat.error(GameFileErrorKind::Invalid(e)));
+        }
+
+        let mut players = Vec::with_capacity(n_players);
+        let mut won = vec![];
+        for seat in self.seats {
+            let mut player = Player::new(seat.hand.unwrap_or_default().into());
+            for (position, trick) in seat.won {
+                won.push((position, trick.idx()));
+                player
+                    .add_trick(trick)
+                    .map_err(|e| position.error(GameFileErrorKind::Game(e)))?;
+            }
+            for (position, text) in seat.tasks {
+                let task = parse_task(&text, n_players).map_err(|e| GameFileError {
+                    line: position.line,
+                    column: position.column + e.column - 1,
+                    kind: GameFileErrorKind::Task(e.kind),
+                })?;
+                player
+                    .add_task(task)
+                    .map_err(|e| position.error(GameFileErrorKind::Game(e)))?;
+            }
+            players.push(player);
+        }
+
+        let (position, current_trick) = match self.current {
+            Some(current) => current,
+            None if players.iter().all(|p| p.get_tricks().is_empty()) => {
+                let captain = State::retrieve_captain(&players, None)
+                    .map_err(|e| first_seat.error(GameFileErrorKind::Game(e)))?;
+                (first_seat, (0, captain, []).into())
+            }
+            None => {
+                return Err(end.error(GameFileErrorKind::Expected(
+                    "the current trick (`current: #<index> @<first player>: <cards>`)".to_string(),
+                )));
+            }
+        };
+
+        // Errors about won tricks are reported on the `won` line of the trick
+        let mut state = State::from_parts(players, current_trick, vec![]);
+        state.validate().map_err(|e| {
+            let trick = match e {
+                GameError::DuplicateTrick(i)
+                | GameError::InvalidTrickLeader(i, _)
+                | GameError::TrickNotHeldByWinner(i, _) => Some(i),
+                _ => None,
+            };
+            let position = won
+                .iter()
+                .rev()
+                .find(|(_, idx)| Some(*idx) == trick)
+                .map_or(position, |(p, _)| *p);
+            position.error(GameFileErrorKind::Game(e))
+        })?;
+        // Communications are checked one at a time, to report the one which is wrong
+        for (position, communication) in self.communications {
+            state.get_mut_communications().push(communication);
+            state
+                .validate()
+                .map_err(|e| position.error(GameFileErrorKind::Game(e)))?;
+        }
+        Ok(state)
+    }
+}
+
+/// A task given by its catalog ID, or in the task language.
+fn parse_task(text: &str, n_players: usize) -> Result<Task, dsl::ParseError> {
+    match catalog::get(text) {
+        Some(entry) => Ok(entry.build(n_players)),
+        None => dsl::parse_task(text, n_players),
+    }
+}
+
+/// Reads a game in the format described in the [module documentation](self), checking that
+/// it may happen in a game (see [`State::validate`]).
+pub fn parse_game(src: &str) -> Result<State, GameFileError> {
+    let mut parser = Parser::default();
+    let mut n_lines = 0;
+    for (i, line) in src.lines().enumerate() {
+        parser.parse_line(i + 1, line)?;
+        n_lines = i + 1;
+    }
+    parser.finish(n_lines)
+}
+
+/// Writes a game in the format read by [`parse_game`]. Tasks of the catalog are written with
+/// their ID, other tasks in the task language.
+pub fn write_game(state: &State) -> String {
+    let n_players = state.n_players();
+    let mut out = String::new();
+    for (i, p) in state.get_players().iter().enumerate() {
+        writeln!(out, "seat {}", i).unwrap();
+        writeln!(out, "{}", format!("hand: {}", p.get_hand()).trim_end()).unwrap();
+        for task in p.get_tasks() {
+            let text = catalog::entries()
+                .iter()
+                .find(|e| e.build(n_players) == *task)
+                .map_or_else(|| task.to_dsl(), |e| e.id.to_string());
+            writeln!(out, "task: {}", text).unwrap();
+        }
+        for trick in p.get_tricks() {
+            writeln!(out, "won: {}", trick).unwrap();
+        }
+        out.push('\n');
+    }
+    for c in state.get_communications() {
+        writeln!(out, "communication: {} {} {}", c.seat, c.card, c.kind).unwrap();
+    }
+    writeln!(out, "current: {}", state.get_current_trick()).unwrap();
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    use super::*;
+    use crate::{
+        card::{blue, green, submarine},
+        task::{self, win_nb_tricks::TaskWinNbTricks},
+    };
+
+    const GAME: &str = "\
+# Four players, five tricks played
+seat 0
+hand: S4 Y6 B6 P2 P5
+task: win trick where all odd
+task: win at least 5 pink
+won: #3 @3: G1 G9 G8 G6
+won: #4 @0: S2 P8 P9 P7
+
+seat 1
+hand: B9 B5 G5 G4 G2
+task: dont win color submarine
+
+seat 2
+hand: S1 B8 B1 P1 Y8
+task: dont open with green, pink
+
+seat 3
+hand: S3 G7 G3 P4 P6
+task: win_all_3s
+won: #0 @0: Y1 Y4 P3 Y7
+won: #1 @3: Y9 Y2 Y5 Y3
+won: #2 @3: B7 B2 B3 B4
+
+current: #5 @0:
+";
+
+    #[test]
+    fn parse() {
+        let state = parse_game(GAME).unwrap();
+        assert_eq!(state.n_players(), 4);
+        assert_eq!(state.get_player(1).get_hand()[0], blue(9));
+        assert_eq!(state.get_player(0).get_tasks().len(), 2);
+        assert_eq!(
+            state.get_player(3).get_tasks()[0],
+            task::win_all_3s().into()
+        );
+        assert_eq!(state.get_player(3).get_tricks().len(), 3);
+        assert_eq!(state.get_current_trick().idx(), 5);
+        assert_eq!(parse_game(&write_game(&state)), Ok(state));
+    }
+
+    #[test]
+    fn fresh_game() {
+        // Without a current trick, the captain leads the first one
+        let src = "seat 0\nhand: B1 S1\nseat 1\nhand: S4 G2\nseat 2\nhand: B2 G1\n";
+        let players = [
+            vec![blue(1), submarine(1)],
+            vec![submarine(4), green(2)],
+            vec![blue(2), green(1)],
+        ]
+        .map(|h| Player::new(h.into()));
+        assert_eq!(parse_game(src), Ok(State::new(players)));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(43);
+        let entries = catalog::entries();
+        for n_players in 3..=5 {
+            for _ in 0..20 {
+                let mut state = State::new_random_with_rng(n_players, &mut rng);
+                for ip in 0..n_players {
+                    let player = state.get_mut_player(ip);
+                    let task = entries.choose(&mut rng).unwrap().build(n_players);
+                    let _ = player.add_task(task);
+                    let mut prediction = TaskWinNbTricks::new_prediction(None, true);
+                    prediction.predict(ip);
+                    player.add_task(prediction).unwrap();
+                }
+                let seat = state.first_player();
+                let card = *state
+                    .get_player(seat)
+                    .get_hand()
+                    .iter()
+                    .find(|c| !c.is_submarine())
+                    .unwrap();
+                state
+                    .communicate(Communication {
+                        seat,
+                        card,
+                        kind: CommunicationKind::Highest,
+                    })
+                    .ok();
+                for _ in 0..n_players * 2 + 1 {
+                    let playable = state
+                        .get_current_player()
+                        .get_hand()
+                        .playable_cards(state.get_current_trick().first());
+                    state.play_card(playable.choose(&mut rng).unwrap()).unwrap();
+                }
+                assert_eq!(parse_game(&write_game(&state)), Ok(state));
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |src: &str| parse_game(src).unwrap_err();
+        let seats = "seat 0\nhand: S4\nseat 1\nhand: S3\nseat 2\nhand: S2\n";
+
+        let e = error("seat 0\nhand: B1 S4\nseat 1\nhand: B2 b1");
+        assert_eq!((e.line, e.column), (4, 10));
+        assert_eq!(
+            e.kind,
+            GameFileErrorKind::DuplicateCard {
+                card: blue(1),
+                line: 2
+            }
+        );
+
+        let e = error("seat 0\n  hand: B1, S5");
+        assert_eq!((e.line, e.column), (2, 13));
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 13: \"S5\" is not a card of the game."
+        );
+
+        // Task errors are located in the file, not in the task text
+        let e = error(&format!("{}task: win cards [B1 B10]", seats));
+        assert_eq!((e.line, e.column), (7, 21));
+
+        assert_eq!(error("seat 1").column, 6);
+        assert_eq!(error("seat 0\nhands: B1").column, 1);
+        assert_eq!(error("seat 0\nwon: #1 0: B1").column, 9);
+        assert_eq!(
+            error("hand: B1").kind,
+            GameFileErrorKind::Invalid("`hand` must follow a `seat` line".to_string())
+        );
+
+        // The captain may not take this task
+        assert_eq!(
+            error(&format!(
+                "seat 0\nhand: S4\ntask: win more tricks than captain\n{}",
+                &seats[16..]
+            ))
+            .line,
+            3
+        );
+        // Too few cards won for the trick
+        assert_eq!(error(&format!("{}won: #0 @0: B1 B2", seats)).line, 7);
+        // No captain
+        let e = error("seat 0\nseat 1\nseat 2\n");
+        assert_eq!((e.line, e.column), (1, 1));
+        // Each communication is checked on its own line
+        let hands = "seat 0\nhand: S4 B1 B5\nseat 1\nhand: S3 B2 G3\nseat 2\nhand: S2 B3 G4\n";
+        let e = error(&format!(
+            "{}communication: 1 G3 only\ncommunication: 0 B1 highest\n",
+            hands
+        ));
+        assert_eq!((e.line, e.column), (8, 1));
+        assert!(matches!(
+            e.kind,
+            GameFileErrorKind::Game(GameError::FalseCommunication(..))
+        ));
+        // The trick is won by seat 1, not seat 2
+        let e = error(&format!("{}won: #0 @0: B1 B9 B2\ncurrent: #1 @2:\n", seats));
+        assert_eq!(e.line, 7);
+        assert_eq!(
+            e.kind,
+            GameFileErrorKind::Game(GameError::TrickNotHeldByWinner(0, 1))
+        );
+    }
+}
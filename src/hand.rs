@@ -1,10 +1,11 @@
 use std::{
     collections::HashSet,
-    fmt::Debug,
+    fmt::{Debug, Display},
     ops::{Deref, DerefMut},
+    str::FromStr,
 };
 
-use crate::card::Card;
+use crate::card::{self, Card, ParseCardError};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Hand {
@@ -47,6 +48,24 @@ where
     }
 }
 
+/// Reads the cards of the hand in the short notation, separated by whitespace or commas, as in
+/// `P7 G3 B9 Y1 S4`.
+impl FromStr for Hand {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Hand {
+            cards: card::parse_cards(s)?,
+        })
+    }
+}
+
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        card::fmt_cards(&self.cards, f)
+    }
+}
+
 impl Hand {
     pub fn playable_cards(&self, first_card: Option<&Card>) -> Vec<Card> {
         if let Some(first_card) = first_card
@@ -105,4 +124,26 @@ mod test {
         assert_eq!(hand.playable_cards(Some(&Card::Yellow(5))), hand.cards);
         assert_eq!(hand.playable_cards(None), hand.cards);
     }
+
+    #[test]
+    fn test_notation() {
+        let hand: Hand = "P7 g3, pink9 S4".parse().unwrap();
+        assert_eq!(
+            hand,
+            vec![
+                Card::Pink(7),
+                Card::Green(3),
+                Card::Pink(9),
+                Card::Submarine(4)
+            ]
+            .into()
+        );
+        assert_eq!(hand.to_string(), "P7 G3 P9 S4");
+        assert_eq!(hand.to_string().parse(), Ok(hand));
+        assert_eq!("".parse::<Hand>().map(|h| h.len()), Ok(0));
+        assert_eq!(
+            "P7 B10".parse::<Hand>(),
+            Err(ParseCardError::InvalidCard("B10".to_string()))
+        );
+    }
 }
@@ -1,7 +1,30 @@
+pub mod advice;
+#[cfg(feature = "serde")]
+pub mod api;
+pub mod audit;
 pub mod card;
+pub mod catalog;
+pub mod cnf;
+pub mod communication;
+pub mod distress;
+pub mod draft;
+pub mod dsl;
+pub mod ffi;
+pub mod game_code;
+pub mod game_file;
 pub mod hand;
+pub mod locale;
+pub mod mission;
 pub mod player;
+pub mod render;
+pub mod replay;
+pub mod report;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod simulator;
 pub mod solver;
 pub mod state;
+pub mod strategy;
 pub mod task;
 pub mod trick;
+pub mod view;
@@ -0,0 +1,193 @@
+//! Languages in which the texts of the game can be rendered.
+//!
+//! Only the building blocks shared by several texts live here (card names, lists, numbers);
+//! each task renders its own card text in [`crate::task::BaseTask::describe`].
+use crate::card::Card;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+/// Gender of the noun a French word agrees with (ignored in English).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Gender {
+    Masculine,
+    Feminine,
+}
+
+/// Position of the color of `card` in the usual order of the game: pink, green, blue, yellow,
+/// then submarines.
+pub(crate) fn color_order(card: &Card) -> usize {
+    match card {
+        Card::Pink(_) => 0,
+        Card::Green(_) => 1,
+        Card::Blue(_) => 2,
+        Card::Yellow(_) => 3,
+        Card::Submarine(_) => 4,
+    }
+}
+
+/// Returns the cards sorted by color, then by value, so that texts do not depend on the
+/// iteration order of a set.
+pub(crate) fn sorted<'a, I>(cards: I) -> Vec<Card>
+where
+    I: IntoIterator<Item = &'a Card>,
+{
+    let mut cards: Vec<Card> = cards.into_iter().copied().collect();
+    cards.sort_by_key(|c| (color_order(c), c.val()));
+    cards
+}
+
+impl Locale {
+    fn join(self, items: &[String], conjunction: &str) -> String {
+        match items {
+            [] => String::new(),
+            [item] => item.clone(),
+            [init @ .., last] => format!("{} {} {}", init.join(", "), conjunction, last),
+        }
+    }
+
+    /// Joins the items as "a, b and c".
+    pub(crate) fn and(self, items: &[String]) -> String {
+        match self {
+            Locale::English => self.join(items, "and"),
+            Locale::French => self.join(items, "et"),
+        }
+    }
+
+    /// Joins the items as "a, b or c".
+    pub(crate) fn or(self, items: &[String]) -> String {
+        match self {
+            Locale::English => self.join(items, "or"),
+            Locale::French => self.join(items, "ou"),
+        }
+    }
+
+    /// Spells out numbers up to ten, larger ones are written with digits.
+    pub(crate) fn number(self, n: usize, gender: Gender) -> String {
+        const ENGLISH: [&str; 11] = [
+            "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+        ];
+        const FRENCH: [&str; 11] = [
+            "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf", "dix",
+        ];
+        match self {
+            _ if n > 10 => n.to_string(),
+            Locale::English => ENGLISH[n].to_string(),
+            Locale::French if n == 1 && gender == Gender::Feminine => "une".to_string(),
+            Locale::French => FRENCH[n].to_string(),
+        }
+    }
+
+    /// Name of the color of `card`, as an adjective in French.
+    pub(crate) fn color(self, card: &Card, gender: Gender, plural: bool) -> String {
+        let name = match (self, card) {
+            (Locale::English, Card::Pink(_)) => "pink",
+            (Locale::English, Card::Green(_)) => "green",
+            (Locale::English, Card::Blue(_)) => "blue",
+            (Locale::English, Card::Yellow(_)) => "yellow",
+            (Locale::English, Card::Submarine(_)) => "submarine",
+            (Locale::French, Card::Pink(_)) => "rose",
+            (Locale::French, Card::Green(_)) if gender == Gender::Feminine => "verte",
+            (Locale::French, Card::Green(_)) => "vert",
+            (Locale::French, Card::Blue(_)) if gender == Gender::Feminine => "bleue",
+            (Locale::French, Card::Blue(_)) => "bleu",
+            (Locale::French, Card::Yellow(_)) => "jaune",
+            (Locale::French, Card::Submarine(_)) => "sous-marin",
+        };
+        if plural && self == Locale::French {
+            format!("{}s", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Name of a single card, such as "the pink 7" or "the 3 submarine".
+    pub(crate) fn card(self, card: &Card) -> String {
+        match (self, card.is_submarine()) {
+            (Locale::English, false) => format!(
+                "the {} {}",
+                self.color(card, Gender::Masculine, false),
+                card.val()
+            ),
+            (Locale::English, true) => format!("the {} submarine", card.val()),
+            (Locale::French, false) => format!(
+                "le {} {}",
+                card.val(),
+                self.color(card, Gender::Masculine, false)
+            ),
+            (Locale::French, true) => format!("le sous-marin {}", card.val()),
+        }
+    }
+
+    /// Redeal condition of a task, to be appended to its text: cards must be dealt again if a
+    /// player holds all cards of one of the sets.
+    pub(crate) fn redeal(self, sets: &[Vec<Card>]) -> String {
+        if sets.is_empty() {
+            return String::new();
+        }
+        let all_submarines = |set: &Vec<Card>| set.len() == 4 && set.iter().all(Card::is_submarine);
+        let cards = if sets.len() == 1 && all_submarines(&sets[0]) {
+            match self {
+                Locale::English => "all submarines".to_string(),
+                Locale::French => "tous les sous-marins".to_string(),
+            }
+        } else if sets.iter().flatten().all(Card::is_submarine) {
+            let sets: Vec<String> = sets
+                .iter()
+                .map(|set| {
+                    let values: Vec<String> =
+                        sorted(set).iter().map(|c| c.val().to_string()).collect();
+                    self.and(&values)
+                })
+                .collect();
+            match self {
+                Locale::English => format!("submarines {}", self.or(&sets)),
+                Locale::French => format!("les sous-marins {}", self.or(&sets)),
+            }
+        } else {
+            let sets: Vec<String> = sets
+                .iter()
+                .map(|set| {
+                    let names: Vec<String> = sorted(set).iter().map(|c| self.card(c)).collect();
+                    self.and(&names)
+                })
+                .collect();
+            self.or(&sets)
+        };
+        match self {
+            Locale::English => format!(" (deal new cards if someone has {} in hand)", cards),
+            Locale::French => format!(
+                " (redistribuez les cartes si quelqu'un a {} en main)",
+                cards
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lists() {
+        let items: Vec<String> = ["a", "b", "c"].map(String::from).to_vec();
+        assert_eq!(Locale::English.and(&items), "a, b and c");
+        assert_eq!(Locale::French.or(&items[1..]), "b ou c");
+        assert_eq!(Locale::English.or(&items[..1]), "a");
+    }
+
+    #[test]
+    fn test_cards() {
+        assert_eq!(Locale::English.card(&Card::Green(2)), "the green 2");
+        assert_eq!(Locale::French.card(&Card::Green(2)), "le 2 vert");
+        assert_eq!(Locale::French.card(&Card::Submarine(3)), "le sous-marin 3");
+        assert_eq!(
+            Locale::French.color(&Card::Blue(1), Gender::Feminine, true),
+            "bleues"
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use the_crew_solver::{
     card::Card,
+    game_file,
+    locale::Locale,
     player::Player,
+    report::Report,
     state::{GameError, State},
     task::{
         dont_open_trick_with::TaskDontOpenTrickWith, dont_win_cards::TaskDontWinCards,
@@ -8,188 +11,107 @@ use the_crew_solver::{
     },
 };
 
+/// A game stopped after five tricks.
+const REAL_WORLD_GAME: &str = "\
+seat 0
+hand: S4 Y6 B6 P2 P5
+task: win trick where all odd
+task: win at least 5 pink
+won: #3 @3: G1 G9 G8 G6
+won: #4 @0: S2 P8 P9 P7
+
+seat 1
+hand: B9 B5 G5 G4 G2
+task: dont win color submarine
+
+seat 2
+hand: S1 B8 B1 P1 Y8
+task: dont open with green, pink
+
+seat 3
+hand: S3 G7 G3 P4 P6
+task: win cards [B3 P3 G3 Y3]
+won: #0 @0: Y1 Y4 P3 Y7
+won: #1 @3: Y9 Y2 Y5 Y3
+won: #2 @3: B7 B2 B3 B4
+
+current: #5 @0:
+";
+
+fn print_solution(solution: &State) {
+    match Report::new(solution, Locale::English) {
+        Ok(report) => println!("{}", report.to_markdown()),
+        Err(e) => eprintln!("Error encountered: {}", e),
+    }
+}
+
 fn main() {
     // Minimal demo
     let mut p1 = Player::new(vec![Card::Submarine(4), Card::Blue(2)].into());
     let p2 = Player::new(vec![Card::Blue(1), Card::Pink(6)].into());
 
-    p1.add_task(TaskWinCards::new(None, [Card::Blue(1), Card::Pink(6)]));
+    p1.add_task(TaskWinCards::new(None, [Card::Blue(1), Card::Pink(6)]))
+        .unwrap();
 
     let mut s = State::new(vec![p1, p2]);
     match s.play() {
-        Ok(solution) => println!("Found a solution: {:?}", solution),
+        Ok(solution) => print_solution(&solution),
         Err(GameError::NoSolutionFound) => println!("Unfortunately this game is not feasible"),
         Err(e) => eprintln!("Error encountered: {}", e),
     }
 
     // Intermediate demo
     let mut state = State::new_random(4);
-    state.get_mut_player(0).add_task(TaskWinCards::new(
-        None,
-        [
-            Card::Blue(1),
-            Card::Pink(6),
-            Card::Green(9),
-            Card::Yellow(4),
-        ],
-    ));
+    state
+        .get_mut_player(0)
+        .add_task(TaskWinCards::new(
+            None,
+            [
+                Card::Blue(1),
+                Card::Pink(6),
+                Card::Green(9),
+                Card::Yellow(4),
+            ],
+        ))
+        .unwrap();
     state
         .get_mut_player(1)
         .add_task(TaskDontWinCards::new_from_colors(
             None,
             [Card::Blue as fn(usize) -> Card, Card::Green],
-        ));
-    state.get_mut_player(2).add_task(TaskDontOpenTrickWith::new(
-        None,
-        [Card::Blue as fn(usize) -> Card, Card::Pink],
-    ));
+        ))
+        .unwrap();
+    state
+        .get_mut_player(2)
+        .add_task(TaskDontOpenTrickWith::new(
+            None,
+            [Card::Blue as fn(usize) -> Card, Card::Pink],
+        ))
+        .unwrap();
     state
         .get_mut_player(3)
-        .add_task(TaskDontWinCards::new_from_values(None, [5, 8]));
-    state.get_mut_player(3).add_task(TaskDontWinCards::new(
-        None,
-        [Card::Yellow(1), Card::Yellow(2)],
-    ));
+        .add_task(TaskDontWinCards::new_from_values(None, [5, 8]))
+        .unwrap();
+    state
+        .get_mut_player(3)
+        .add_task(TaskDontWinCards::new(
+            None,
+            [Card::Yellow(1), Card::Yellow(2)],
+        ))
+        .unwrap();
     match state.play() {
-        Ok(solution) => println!("Found a solution: {:?}", solution),
+        Ok(solution) => print_solution(&solution),
         Err(GameError::NoSolutionFound) => println!("Unfortunately this game is not feasible"),
         Err(e) => eprintln!("Error encountered: {}", e),
     }
 
     // First real-world usecase
-    // let mut r = Player::new(
-    //     [
-    //         Card::Submarine(1),
-    //         Card::Blue(8),
-    //         Card::Blue(1),
-    //         Card::Pink(1),
-    //         Card::Yellow(8),
-    //     ]
-    //     .into(),
-    // );
-    // r.add_task(TaskDontOpenTrickWith::new(
-    //     None,
-    //     [Card::Green as fn(usize) -> Card, Card::Pink],
-    // ));
-    // let mut m = Player::new(
-    //     [
-    //         Card::Submarine(4),
-    //         Card::Yellow(6),
-    //         Card::Blue(6),
-    //         Card::Pink(2),
-    //         Card::Pink(5),
-    //     ]
-    //     .into(),
-    // );
-    // m.add_task(TaskWinTrickWithPred::new_odd(None));
-    // m.add_task(TaskWinCardsAmountColor::new(
-    //     None,
-    //     false,
-    //     [(Card::Pink as fn(usize) -> Card, 5)],
-    // ));
-    // m.add_trick(
-    //     (
-    //         3,
-    //         0,
-    //         [
-    //             Card::Green(9),
-    //             Card::Green(1),
-    //             Card::Green(8),
-    //             Card::Green(6),
-    //         ],
-    //     )
-    //         .into(),
-    // )
-    // .unwrap();
-    // m.add_trick(
-    //     (
-    //         4,
-    //         0,
-    //         [
-    //             Card::Pink(7),
-    //             Card::Pink(8),
-    //             Card::Pink(9),
-    //             Card::Submarine(2),
-    //         ],
-    //     )
-    //         .into(),
-    // )
-    // .unwrap();
-    // let mut f = Player::new(
-    //     [
-    //         Card::Submarine(3),
-    //         Card::Green(7),
-    //         Card::Green(3),
-    //         Card::Pink(4),
-    //         Card::Pink(6),
-    //     ]
-    //     .into(),
-    // );
-    // f.add_task(TaskWinCards::new(
-    //     None,
-    //     [
-    //         Card::Blue(3),
-    //         Card::Pink(3),
-    //         Card::Green(3),
-    //         Card::Yellow(3),
-    //     ],
-    // ));
-    // f.add_trick(
-    //     (
-    //         0,
-    //         0,
-    //         [
-    //             Card::Yellow(1),
-    //             Card::Yellow(4),
-    //             Card::Yellow(7),
-    //             Card::Pink(3),
-    //         ],
-    //     )
-    //         .into(),
-    // )
-    // .unwrap();
-    // f.add_trick(
-    //     (
-    //         1,
-    //         0,
-    //         [
-    //             Card::Yellow(9),
-    //             Card::Yellow(2),
-    //             Card::Yellow(5),
-    //             Card::Yellow(3),
-    //         ],
-    //     )
-    //         .into(),
-    // )
-    // .unwrap();
-    // f.add_trick(
-    //     (
-    //         2,
-    //         0,
-    //         [Card::Blue(4), Card::Blue(2), Card::Blue(3), Card::Blue(7)],
-    //     )
-    //         .into(),
-    // )
-    // .unwrap();
-    // let mut l = Player::new(
-    //     [
-    //         Card::Blue(9),
-    //         Card::Blue(5),
-    //         Card::Green(5),
-    //         Card::Green(4),
-    //         Card::Green(2),
-    //     ]
-    //     .into(),
-    // );
-    // l.add_task(TaskDontWinCards::new_from_colors(
-    //     None,
-    //     [Card::Submarine as fn(usize) -> Card],
-    // ));
-    // let mut state = State::new([m, l, r, f]);
-    // state.current_trick = (5, 0, []).into(); // You need to make it public to run this example
-    // match state.play() {
-    //     Ok(solution) => println!("Found a solution: {:?}", solution),
-    //     Err(GameError::NoSolutionFound) => println!("Unfortunately this game is not feasible"),
-    //     Err(e) => eprintln!("Error encountered: {}", e),
-    // }
+    match game_file::parse_game(REAL_WORLD_GAME) {
+        Ok(mut state) => match state.play() {
+            Ok(solution) => print_solution(&solution),
+            Err(GameError::NoSolutionFound) => println!("Unfortunately this game is not feasible"),
+            Err(e) => eprintln!("Error encountered: {}", e),
+        },
+        Err(e) => eprintln!("Invalid game file: {}", e),
+    }
 }
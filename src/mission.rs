@@ -0,0 +1,204 @@
+//! Random Deep Sea missions.
+//!
+//! Following the Deep Sea setup rules, task cards are drawn one at a time from the shuffled
+//! catalog, and their difficulty (for the number of players) is added up until the target
+//! difficulty is reached. A task which would exceed the target is put aside. If the target
+//! cannot be reached exactly (for instance if it exceeds the difficulty of the whole catalog),
+//! no mission is generated.
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use crate::{
+    catalog::{self, CatalogEntry},
+    draft::DraftOptimizer,
+    player,
+    state::{GameError, MAX_REDEALS, State},
+    task::{BaseTask, Task},
+};
+
+#[derive(Debug, Clone)]
+pub struct Mission {
+    /// Catalog entries of the drawn tasks, in draw order.
+    pub entries: Vec<&'static CatalogEntry>,
+    pub tasks: Vec<Task>,
+    /// Dealt hands, without any task assigned.
+    pub state: State,
+}
+
+impl Mission {
+    /// Returns the state with tasks assigned in draw order, starting with the captain and going
+    /// clockwise. Fails if the captain gets a task they cannot take.
+    pub fn assign_in_draft_order(&self) -> Result<State, GameError> {
+        let mut state = self.state.clone();
+        let captain = state.first_player();
+        for (i, task) in self.tasks.iter().enumerate() {
+            let ip = (captain + i) % state.n_players();
+            state.get_mut_player(ip).add_task(task.clone())?;
+        }
+        Ok(state)
+    }
+}
+
+pub struct MissionGenerator {
+    n_players: usize,
+    difficulty: usize,
+    rng: StdRng,
+    /// Solver budget and number of attempts, if missions must be feasible.
+    feasibility: Option<(usize, usize)>,
+}
+
+impl MissionGenerator {
+    pub fn new(n_players: usize, difficulty: usize, seed: u64) -> Self {
+        player::check_valid_n_players(n_players).unwrap();
+        assert!(difficulty > 0, "difficulty should be positive");
+        Self {
+            n_players,
+            difficulty,
+            rng: StdRng::seed_from_u64(seed),
+            feasibility: None,
+        }
+    }
+
+    /// Only generates missions which the perfect-information solver proves feasible (exploring
+    /// at most `budget` states per assignment) for at least one draft, trying at most
+    /// `max_attempts` deals. "Win X tricks" tasks are then never drawn, as their prediction is
+    /// only made once the cards are dealt.
+    pub fn require_feasible(mut self, budget: usize, max_attempts: usize) -> Self {
+        self.feasibility = Some((budget, max_attempts));
+        self
+    }
+
+    /// Draws tasks adding up to the target difficulty, or `None` if the whole pool was drawn
+    /// without reaching it exactly.
+    fn draw_tasks(&mut self) -> Option<Vec<&'static CatalogEntry>> {
+        let mut pool: Vec<&'static CatalogEntry> = catalog::entries()
+            .iter()
+            .filter(|e| self.feasibility.is_none() || !needs_prediction(e.build(self.n_players)))
+            .collect();
+        pool.shuffle(&mut self.rng);
+
+        let mut total = 0;
+        let mut drawn = Vec::new();
+        for entry in pool {
+            let d = entry.difficulty().get(self.n_players);
+            if total + d <= self.difficulty {
+                total += d;
+                drawn.push(entry);
+            }
+            if total == self.difficulty {
+                return Some(drawn);
+            }
+        }
+        None
+    }
+
+    /// Deals the cards as many times as the redeal conditions of `tasks` require, giving up
+    /// after [`MAX_REDEALS`] deals.
+    fn deal(&mut self, tasks: &[Task]) -> Option<State> {
+        (0..MAX_REDEALS)
+            .map(|_| State::new_random_with_rng(self.n_players, &mut self.rng))
+            .find(|state| !tasks.iter().any(|t| t.needs_redeal(state)))
+    }
+
+    /// Draws the tasks, then deals the cards.
+    fn draw(&mut self) -> Option<Mission> {
+        let entries = self.draw_tasks()?;
+        let tasks: Vec<Task> = entries.iter().map(|e| e.build(self.n_players)).collect();
+        let state = self.deal(&tasks)?;
+        Some(Mission {
+            tasks,
+            entries,
+            state,
+        })
+    }
+
+    /// Draws a new mission. Returns `None` if the tasks cannot add up to the target difficulty,
+    /// if no deal meets their redeal conditions, or if feasibility is required and no feasible
+    /// mission was found.
+    pub fn generate(&mut self) -> Option<Mission> {
+        let Some((budget, max_attempts)) = self.feasibility else {
+            return self.draw();
+        };
+
+        for _ in 0..max_attempts {
+            let mission = self.draw()?;
+            let feasible =
+                DraftOptimizer::new(mission.state.clone(), mission.tasks.clone(), budget)
+                    .and_then(|optimizer| optimizer.find_feasible());
+            if matches!(feasible, Ok(Some(_))) {
+                return Some(mission);
+            }
+        }
+        None
+    }
+}
+
+fn needs_prediction(task: Task) -> bool {
+    matches!(task, Task::WinNbTricks(t) if t.prediction().is_none())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reaches_difficulty() {
+        let mut generator = MissionGenerator::new(4, 7, 0);
+        for _ in 0..20 {
+            let mission = generator.generate().unwrap();
+            let total: usize = mission
+                .tasks
+                .iter()
+                .map(|t| t.get_difficulty(4).unwrap())
+                .sum();
+            assert_eq!(total, 7);
+            assert_eq!(mission.tasks.len(), mission.entries.len());
+            assert_eq!(mission.state.n_players(), 4);
+        }
+    }
+
+    #[test]
+    fn unreachable_difficulty() {
+        let total: usize = catalog::entries()
+            .iter()
+            .map(|e| e.difficulty().get(3))
+            .sum();
+        let mut generator = MissionGenerator::new(3, total + 1, 0);
+        assert!(generator.generate().is_none());
+
+        // The whole catalog is the only mission of its total difficulty
+        let mut generator = MissionGenerator::new(3, total, 0);
+        assert_eq!(
+            generator.generate().unwrap().tasks.len(),
+            catalog::entries().len()
+        );
+    }
+
+    #[test]
+    fn endless_redeal() {
+        let mut generator = MissionGenerator::new(3, 1, 0);
+        // The captain always holds the 4 submarine
+        let task = crate::dsl::parse_task("win at least 3 pink redeal if [S4]", 3).unwrap();
+        assert!(generator.deal(&[task]).is_none());
+        let task = crate::dsl::parse_task("win at least 3 pink redeal if [S1 S2 S3]", 3).unwrap();
+        assert!(generator.deal(&[task]).is_some());
+    }
+
+    #[test]
+    fn reproducible() {
+        let ids = |seed| {
+            let mut generator = MissionGenerator::new(5, 10, seed);
+            let mission = generator.generate().unwrap();
+            let ids: Vec<&str> = mission.entries.iter().map(|e| e.id).collect();
+            (ids, mission.state.get_player(0).get_hand().clone())
+        };
+        assert_eq!(ids(1), ids(1));
+    }
+
+    #[test]
+    fn feasible() {
+        let mut generator = MissionGenerator::new(4, 2, 0).require_feasible(10_000, 20);
+        let mission = generator.generate().unwrap();
+        let optimizer = DraftOptimizer::new(mission.state, mission.tasks, 10_000).unwrap();
+        assert!(matches!(optimizer.find_feasible(), Ok(Some(_))));
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
 };
 
 pub fn check_valid_n_players(n_players: usize) -> Result<(), String> {
-    if n_players < 3 || n_players > 5 {
+    if !(3..=5).contains(&n_players) {
         Err(format!(
             "Invalid number of players: expected between 3 and 5 (inclusive), found {}",
             n_players
@@ -32,7 +32,8 @@ pub fn n_tricks_total(n_players: usize) -> usize {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     hand: Hand,
     tricks: Vec<Trick>,
@@ -62,30 +63,48 @@ impl Player {
         }
     }
 
+    pub fn add_card_to_hand(&mut self, card: Card) {
+        self.hand.push(card);
+    }
+
     pub fn get_tricks(&self) -> &[Trick] {
         &self.tricks
     }
 
     pub fn add_trick(&mut self, trick: Trick) -> Result<(), GameError> {
-        if let Some(t0) = self.tricks.get(0) {
-            if t0.len() != trick.len() {
-                return Err(GameError::InvalidTrickSize(t0.len(), trick.len()));
-            }
+        if let Some(t0) = self.tricks.first()
+            && t0.len() != trick.len()
+        {
+            return Err(GameError::InvalidTrickSize(t0.len(), trick.len()));
         }
-        if let Some(lt) = self.tricks.last() {
-            if lt.idx() >= trick.idx() {
-                return Err(GameError::NonIncreasingTrickIdx);
-            }
+        if let Some(lt) = self.tricks.last()
+            && lt.idx() >= trick.idx()
+        {
+            return Err(GameError::NonIncreasingTrickIdx);
         }
         self.tricks.push(trick);
         Ok(())
     }
 
-    pub fn add_task<T>(&mut self, task: T)
+    pub fn get_tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub(crate) fn get_mut_tasks(&mut self) -> &mut [Task] {
+        &mut self.tasks
+    }
+
+    /// Gives a task to the player, checking that the captain is allowed to take it.
+    pub fn add_task<T>(&mut self, task: T) -> Result<(), GameError>
     where
         T: Into<Task>,
     {
-        self.tasks.push(task.into());
+        let task = task.into();
+        if task.captain_forbidden() && self.is_captain(None) {
+            return Err(GameError::CaptainForbiddenTask(task));
+        }
+        self.tasks.push(task);
+        Ok(())
     }
 
     /// To determine wether the current player is the captain, we might also need the current trick,
@@ -148,4 +167,21 @@ mod test {
         assert!(p.remove_card_from_hand(&Card::Pink(4)).is_err());
         assert_eq!(*p.get_hand(), vec![Card::Pink(2)].into());
     }
+
+    #[test]
+    fn test_add_captain_forbidden_task() {
+        let mut captain = Player::new(vec![Card::Green(1), Card::Submarine(4)].into());
+        assert!(matches!(
+            captain.add_task(crate::task::win_more_tricks_than_captain()),
+            Err(GameError::CaptainForbiddenTask(_))
+        ));
+        assert!(captain.get_tasks().is_empty());
+
+        let mut other = Player::new(vec![Card::Yellow(4), Card::Green(1)].into());
+        assert!(
+            other
+                .add_task(crate::task::win_more_tricks_than_captain())
+                .is_ok()
+        );
+    }
 }
@@ -0,0 +1,313 @@
+//! Rendering of the game in a terminal.
+//!
+//! [`State`] and [`Player`] implement [`Display`] with a readable multi-line layout: hands
+//! sorted by color and value, the current trick and the seat to play, the tricks won by every
+//! seat, the captain, and the tasks with their status. [`State::display`] and
+//! [`Player::display`] give the same output, with optional ANSI colors.
+use std::fmt::{self, Display};
+
+use crate::{
+    card::Card,
+    communication::Communication,
+    locale::{self, Locale},
+    player::Player,
+    state::State,
+    task::{BaseTask, TaskStatus},
+    trick::Trick,
+};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const MAGENTA: &str = "\x1b[35m";
+
+/// Writes `text`, within the ANSI `code` if `ansi`.
+fn paint(f: &mut fmt::Formatter<'_>, text: impl Display, code: &str, ansi: bool) -> fmt::Result {
+    if ansi {
+        write!(f, "{}{}{}", code, text, RESET)
+    } else {
+        write!(f, "{}", text)
+    }
+}
+
+fn write_cards<'a, I>(f: &mut fmt::Formatter<'_>, cards: I, ansi: bool) -> fmt::Result
+where
+    I: IntoIterator<Item = &'a Card>,
+{
+    for (i, card) in cards.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        let code = match card {
+            Card::Pink(_) => MAGENTA,
+            Card::Green(_) => GREEN,
+            Card::Blue(_) => BLUE,
+            Card::Yellow(_) => YELLOW,
+            Card::Submarine(_) => BOLD,
+        };
+        paint(f, card, code, ansi)?;
+    }
+    Ok(())
+}
+
+fn write_trick(f: &mut fmt::Formatter<'_>, trick: &Trick, ansi: bool) -> fmt::Result {
+    write!(f, "#{} @{}:", trick.idx(), trick.get_first_player())?;
+    if !trick.is_empty() {
+        write!(f, " ")?;
+    }
+    write_cards(f, trick.iter(), ansi)
+}
+
+/// What is written about a seat, besides the player itself.
+struct Seat<'a> {
+    header: String,
+    /// The status of every task, if known.
+    statuses: Option<Vec<TaskStatus>>,
+    communication: Option<&'a Communication>,
+}
+
+fn write_seat(f: &mut fmt::Formatter<'_>, player: &Player, seat: Seat, ansi: bool) -> fmt::Result {
+    paint(f, seat.header, BOLD, ansi)?;
+    write!(f, "\n  Hand: ")?;
+    write_cards(f, &locale::sorted(player.get_hand().iter()), ansi)?;
+    writeln!(f)?;
+    if let Some(c) = seat.communication {
+        write!(f, "  Communicated: ")?;
+        write_cards(f, [&c.card], ansi)?;
+        writeln!(f, " ({})", c.kind)?;
+    }
+
+    let tricks = player.get_tricks();
+    match tricks.len() {
+        0 => writeln!(f, "  Won no trick")?,
+        1 => writeln!(f, "  Won 1 trick:")?,
+        n => writeln!(f, "  Won {} tricks:", n)?,
+    }
+    for trick in tricks {
+        write!(f, "    ")?;
+        write_trick(f, trick, ansi)?;
+        writeln!(f)?;
+    }
+
+    let tasks = player.get_tasks();
+    if tasks.is_empty() {
+        return writeln!(f, "  No task");
+    }
+    writeln!(f, "  Tasks:")?;
+    for (i, task) in tasks.iter().enumerate() {
+        write!(f, "    ")?;
+        match seat.statuses.as_ref().map(|s| s[i]) {
+            Some(TaskStatus::Done) => paint(f, "[done]    ", GREEN, ansi)?,
+            Some(TaskStatus::Unknown) => paint(f, "[pending] ", YELLOW, ansi)?,
+            Some(TaskStatus::Failed) => paint(f, "[failed]  ", RED, ansi)?,
+            None => write!(f, "- ")?,
+        }
+        writeln!(f, "{}", task.describe(Locale::default()))?;
+    }
+    Ok(())
+}
+
+/// A [`State`] rendered for a terminal, see [`State::display`].
+pub struct StateDisplay<'a> {
+    state: &'a State,
+    ansi: bool,
+}
+
+impl StateDisplay<'_> {
+    /// Colors the cards, the task statuses and the headers with ANSI escape codes.
+    pub fn with_ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+}
+
+impl Display for StateDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (state, ansi) = (self.state, self.ansi);
+        let current_trick = state.get_current_trick();
+        let captain = State::retrieve_captain(state.get_players(), Some(current_trick)).ok();
+
+        match state.game_status() {
+            TaskStatus::Done => paint(f, "Mission completed", GREEN, ansi)?,
+            TaskStatus::Failed => paint(f, "Mission failed", RED, ansi)?,
+            TaskStatus::Unknown => write!(f, "Mission in progress")?,
+        }
+        writeln!(f)?;
+        if state.n_tricks_left() == 0 {
+            writeln!(f, "No trick left")?;
+        } else {
+            write!(f, "Current trick ")?;
+            write_trick(f, current_trick, ansi)?;
+            writeln!(f, " (seat {} to play)", state.get_current_player_idx())?;
+        }
+
+        for (ip, player) in state.get_players().iter().enumerate() {
+            let mut header = format!("Seat {}", ip);
+            if Some(ip) == captain {
+                header += " (captain)";
+            }
+            let statuses = player.get_tasks().iter().map(|t| t.eval(state, ip));
+            let seat = Seat {
+                header,
+                statuses: Some(statuses.collect()),
+                communication: state.get_communications().iter().find(|c| c.seat == ip),
+            };
+            writeln!(f)?;
+            write_seat(f, player, seat, ansi)?;
+        }
+        Ok(())
+    }
+}
+
+impl State {
+    /// Renders the state for a terminal, without colors unless asked with
+    /// [`StateDisplay::with_ansi`].
+    pub fn display(&self) -> StateDisplay<'_> {
+        StateDisplay {
+            state: self,
+            ansi: false,
+        }
+    }
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display().fmt(f)
+    }
+}
+
+/// A [`Player`] rendered for a terminal, see [`Player::display`]. Without the rest of the
+/// game, task statuses are unknown and not shown.
+pub struct PlayerDisplay<'a> {
+    player: &'a Player,
+    ansi: bool,
+}
+
+impl PlayerDisplay<'_> {
+    /// Colors the cards and the header with ANSI escape codes.
+    pub fn with_ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+}
+
+impl Display for PlayerDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = if self.player.is_captain(None) {
+            "Player (captain)"
+        } else {
+            "Player"
+        };
+        let seat = Seat {
+            header: header.to_string(),
+            statuses: None,
+            communication: None,
+        };
+        write_seat(f, self.player, seat, self.ansi)
+    }
+}
+
+impl Player {
+    /// Renders the player for a terminal, without colors unless asked with
+    /// [`PlayerDisplay::with_ansi`].
+    pub fn display(&self) -> PlayerDisplay<'_> {
+        PlayerDisplay {
+            player: self,
+            ansi: false,
+        }
+    }
+}
+
+impl Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        card::{blue, green, pink, submarine},
+        communication::CommunicationKind,
+        task::win_cards::TaskWinCards,
+    };
+
+    fn game() -> State {
+        let p0 = Player::new([pink(2), blue(1), green(4)].into());
+        let mut p1 = Player::new([blue(3), submarine(4), pink(5)].into());
+        p1.add_task(TaskWinCards::new(None, [blue(1)])).unwrap();
+        p1.add_task(TaskWinCards::new(None, [green(5)])).unwrap();
+        let p2 = Player::new([green(5), blue(2), pink(9)].into());
+        let mut state = State::new([p0, p1, p2]);
+        state
+            .communicate(Communication {
+                seat: 2,
+                card: pink(9),
+                kind: CommunicationKind::Only,
+            })
+            .unwrap();
+        for card in [blue(3), blue(2), blue(1), pink(5)] {
+            state.play_card(&card).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn state() {
+        let expected = "\
+Mission in progress
+Current trick #1 @1: P5 (seat 2 to play)
+
+Seat 0
+  Hand: P2 G4
+  Won no trick
+  No task
+
+Seat 1 (captain)
+  Hand: S4
+  Won 1 trick:
+    #0 @1: B3 B2 B1
+  Tasks:
+    [done]    Win the blue 1
+    [pending] Win the green 5
+
+Seat 2
+  Hand: P9 G5
+  Communicated: P9 (only)
+  Won no trick
+  No task
+";
+        assert_eq!(game().to_string(), expected);
+    }
+
+    #[test]
+    fn ansi() {
+        let state = game();
+        let plain = state.display().to_string();
+        let colored = state.display().with_ansi(true).to_string();
+        assert!(!plain.contains('\x1b'));
+        assert!(colored.contains("\x1b[35mP9\x1b[0m"));
+        assert!(colored.contains("\x1b[32m[done]    \x1b[0m"));
+
+        // Without escape codes, the text is the same
+        let mut stripped = colored.clone();
+        for code in [RESET, BOLD, RED, GREEN, YELLOW, BLUE, MAGENTA] {
+            stripped = stripped.replace(code, "");
+        }
+        assert_eq!(stripped, plain);
+    }
+
+    #[test]
+    fn player() {
+        let state = game();
+        assert_eq!(
+            state.get_player(1).to_string(),
+            "Player (captain)\n  Hand: S4\n  Won 1 trick:\n    #0 @1: B3 B2 B1\n  Tasks:\n    \
+             - Win the blue 1\n    - Win the green 5\n"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Resuming a game from its history.
+//!
+//! Rather than building every won trick by hand, a game in progress can be described by the
+//! hands dealt, the tasks of each player and the cards played since the start, in order: the
+//! tricks, their winners and the current trick are then found by replaying the game.
+use crate::{
+    card::Card,
+    hand::Hand,
+    player::{self, Player},
+    state::{GameError, State},
+    task::Task,
+    trick::Trick,
+};
+
+impl State {
+    /// Deals `hands`, gives `tasks[i]` to player `i`, then plays `plays` in order. Every card
+    /// is checked against the rules: it must be played by the player whose turn it is, follow
+    /// the color of the trick if possible, and no card may be played once every trick is won.
+    /// The error of the first illegal card names its index in `plays`.
+    pub fn replay(
+        hands: Vec<Hand>,
+        tasks: Vec<Vec<Task>>,
+        plays: &[Card],
+    ) -> Result<State, GameError> {
+        let n_players = hands.len();
+        if player::check_valid_n_players(n_players).is_err() {
+            return Err(GameError::InvalidNPlayers(n_players));
+        }
+        if tasks.len() != n_players {
+            return Err(GameError::InvalidTaskAssignment(n_players, tasks.len()));
+        }
+
+        let mut players = Vec::with_capacity(n_players);
+        for (hand, tasks) in hands.into_iter().zip(tasks) {
+            let mut player = Player::new(hand);
+            for task in tasks {
+                player.add_task(task)?;
+            }
+            players.push(player);
+        }
+        State::retrieve_captain(&players, None)?;
+        let mut state = State::new(players);
+        state.validate()?;
+
+        for (i, card) in plays.iter().enumerate() {
+            if state.n_tricks_left() == 0 {
+                return Err(GameError::PlayAfterGameOver(i));
+            }
+            let ip = state.get_current_player_idx();
+            let hand = state.get_current_player().get_hand();
+            if !hand.contains(card) {
+                return Err(GameError::CardNotHeld(i, ip, *card));
+            }
+            let first = state.get_current_trick().first();
+            if !hand.playable_cards(first).contains(card) {
+                let first = *first.expect("any card may open a trick");
+                return Err(GameError::MustFollowColor(i, ip, *card, first));
+            }
+            state.play_card(card)?;
+        }
+        Ok(state)
+    }
+
+    /// Returns the hands dealt and the cards played since the start of the game, in order, so
+    /// that [`State::replay`] gives back the state.
+    pub fn history(&self) -> Result<(Vec<Hand>, Vec<Card>), GameError> {
+        let n_players = self.n_players();
+        let mut tricks: Vec<&Trick> = self
+            .get_players()
+            .iter()
+            .flat_map(|p| p.get_tricks())
+            .collect();
+        tricks.sort_by_key(|t| t.idx());
+        tricks.push(self.get_current_trick());
+        if let Some(i) = tricks.iter().enumerate().position(|(i, t)| t.idx() != i) {
+            return Err(GameError::MissingTrick(i));
+        }
+
+        let mut hands: Vec<Vec<Card>> = self
+            .get_players()
+            .iter()
+            .map(|p| p.get_hand().to_vec())
+            .collect();
+        let mut plays = vec![];
+        for trick in tricks {
+            for (j, card) in trick.iter().enumerate() {
+                hands[(trick.get_first_player() + j) % n_players].push(*card);
+                plays.push(*card);
+            }
+        }
+        Ok((hands.into_iter().map(Hand::from).collect(), plays))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    use super::*;
+    use crate::{
+        card::{blue, green, pink, submarine},
+        task,
+    };
+
+    /// Three players with two cards each, player 1 being the captain.
+    fn hands() -> Vec<Hand> {
+        vec![
+            [blue(1), pink(2)].into(),
+            [submarine(4), blue(3)].into(),
+            [green(5), blue(2)].into(),
+        ]
+    }
+
+    #[test]
+    fn replay() {
+        let tasks = vec![vec![], vec![task::win_all_3s().into()], vec![]];
+        let state =
+            State::replay(hands(), tasks, &[blue(3), blue(2), blue(1), submarine(4)]).unwrap();
+
+        // Player 1 won the first trick and leads the second one
+        let tricks = state.get_player(1).get_tricks();
+        assert_eq!(tricks, [(0, 1, [blue(3), blue(2), blue(1)]).into()]);
+        assert_eq!(state.get_current_trick(), &(1, 1, [submarine(4)]).into());
+        assert_eq!(state.get_current_player_idx(), 2);
+        assert_eq!(state.get_player(1).get_tasks().len(), 1);
+    }
+
+    #[test]
+    fn same_as_playing() {
+        let mut rng = StdRng::seed_from_u64(45);
+        for n_players in 3..=5 {
+            let mut state = State::new_random_with_rng(n_players, &mut rng);
+            let hands = state.get_players().iter().map(|p| p.get_hand().clone());
+            let hands = hands.collect();
+            let mut plays = vec![];
+            for _ in 0..17 {
+                let playable = state
+                    .get_current_player()
+                    .get_hand()
+                    .playable_cards(state.get_current_trick().first());
+                let card = *playable.choose(&mut rng).unwrap();
+                state.play_card(&card).unwrap();
+                plays.push(card);
+            }
+            let tasks = vec![vec![]; n_players];
+            assert_eq!(State::replay(hands, tasks, &plays).unwrap(), state);
+
+            let (hands, plays) = state.history().unwrap();
+            let tasks = vec![vec![]; n_players];
+            assert_eq!(State::replay(hands, tasks, &plays).unwrap(), state);
+        }
+    }
+
+    #[test]
+    fn illegal_plays() {
+        let replay = |plays: &[Card]| State::replay(hands(), vec![vec![]; 3], plays).unwrap_err();
+
+        // Not the turn of player 0
+        assert!(matches!(
+            replay(&[blue(1)]),
+            GameError::CardNotHeld(0, 1, c) if c == blue(1)
+        ));
+        // Player 2 holds a blue card
+        assert!(matches!(
+            replay(&[blue(3), green(5)]),
+            GameError::MustFollowColor(1, 2, c, f) if c == green(5) && f == blue(3)
+        ));
+        assert!(matches!(
+            replay(&[
+                blue(3),
+                blue(2),
+                blue(1),
+                submarine(4),
+                green(5),
+                pink(2),
+                blue(2)
+            ]),
+            GameError::PlayAfterGameOver(6)
+        ));
+    }
+
+    #[test]
+    fn invalid_setup() {
+        let e = State::replay(hands(), vec![vec![]; 2], &[]).unwrap_err();
+        assert!(matches!(e, GameError::InvalidTaskAssignment(3, 2)));
+
+        let mut hands = hands();
+        hands[1] = [blue(1)].into();
+        let e = State::replay(hands.clone(), vec![vec![]; 3], &[]).unwrap_err();
+        assert!(matches!(e, GameError::MissingCaptain));
+        hands[1] = [submarine(4), pink(2)].into();
+        let e = State::replay(hands, vec![vec![]; 3], &[]).unwrap_err();
+        assert!(matches!(e, GameError::DuplicateCard(c) if c == pink(2)));
+
+        let forbidden = vec![
+            vec![],
+            vec![task::win_more_tricks_than_captain().into()],
+            vec![],
+        ];
+        let e = State::replay(super::test::hands(), forbidden, &[]).unwrap_err();
+        assert!(matches!(e, GameError::CaptainForbiddenTask(_)));
+    }
+}
@@ -0,0 +1,437 @@
+//! Readable reports of a solution.
+//!
+//! A [`Report`] replays a solution found by the solver (see [`State::history`]) and renders it
+//! as Markdown or as a standalone HTML page: the hands dealt and the tasks of every seat, then
+//! one row per trick with its leader, the cards in seat order, the winning card highlighted and
+//! the tasks completed on that trick. Neither output needs any external resource.
+use std::fmt::Write;
+
+use crate::{
+    card::Card,
+    locale::{self, Locale},
+    player::Player,
+    state::{GameError, State},
+    task::{BaseTask, TaskStatus},
+    trick::Trick,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct SeatReport {
+    hand: Vec<Card>,
+    tasks: Vec<String>,
+    captain: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TrickReport {
+    idx: usize,
+    leader: usize,
+    /// Card played by each seat, if any.
+    cards: Vec<Option<Card>>,
+    /// `None` for a trick still being played.
+    winner: Option<usize>,
+    /// Seat and description of the tasks completed during the trick.
+    completed: Vec<(usize, String)>,
+}
+
+impl TrickReport {
+    fn new(trick: &Trick, n_players: usize, winner: Option<usize>) -> Self {
+        let mut cards = vec![None; n_players];
+        for (j, card) in trick.iter().enumerate() {
+            cards[(trick.get_first_player() + j) % n_players] = Some(*card);
+        }
+        TrickReport {
+            idx: trick.idx(),
+            leader: trick.get_first_player(),
+            cards,
+            winner,
+            completed: vec![],
+        }
+    }
+}
+
+/// Texts of the report which do not come from the cards or the tasks.
+struct Labels {
+    title: &'static str,
+    seats: &'static str,
+    seat: &'static str,
+    captain: &'static str,
+    hand: &'static str,
+    no_task: &'static str,
+    tricks: &'static str,
+    trick: &'static str,
+    leader: &'static str,
+    completed: &'static str,
+}
+
+const ENGLISH: Labels = Labels {
+    title: "Solution",
+    seats: "Seats",
+    seat: "Seat",
+    captain: "captain",
+    hand: "Hand",
+    no_task: "No task",
+    tricks: "Tricks",
+    trick: "Trick",
+    leader: "Leader",
+    completed: "Tasks completed",
+};
+
+const FRENCH: Labels = Labels {
+    title: "Solution",
+    seats: "Joueurs",
+    seat: "Joueur",
+    captain: "capitaine",
+    hand: "Main",
+    no_task: "Aucune tâche",
+    tricks: "Plis",
+    trick: "Pli",
+    leader: "Entame",
+    completed: "Tâches accomplies",
+};
+
+/// For every task of every player, whether it is done.
+fn done_tasks(state: &State) -> Vec<Vec<bool>> {
+    let players = state.get_players().iter().enumerate();
+    players
+        .map(|(ip, p)| {
+            let statuses = p.get_tasks().iter().map(|t| t.eval(state, ip));
+            statuses.map(|s| s == TaskStatus::Done).collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    locale: Locale,
+    seats: Vec<SeatReport>,
+    tricks: Vec<TrickReport>,
+}
+
+impl Report {
+    /// Replays `solution` from the deal, with task descriptions in `locale`. Tricks are shown
+    /// as they were recorded, each one won by the player holding it.
+    pub fn new(solution: &State, locale: Locale) -> Result<Self, GameError> {
+        let n_players = solution.n_players();
+        let (hands, _) = solution.history()?;
+        let mut players = Vec::with_capacity(n_players);
+        for (hand, p) in hands.into_iter().zip(solution.get_players()) {
+            let mut player = Player::new(hand);
+            for task in p.get_tasks() {
+                player.add_task(task.clone())?;
+            }
+            players.push(player);
+        }
+        let captain = State::retrieve_captain(&players, None).ok();
+        let seats = players
+            .iter()
+            .enumerate()
+            .map(|(ip, p)| SeatReport {
+                hand: locale::sorted(p.get_hand().iter()),
+                tasks: p.get_tasks().iter().map(|t| t.describe(locale)).collect(),
+                captain: Some(ip) == captain,
+            })
+            .collect();
+
+        // Won tricks in order, along with the player who won them
+        let mut history: Vec<(Option<usize>, &Trick)> = solution
+            .get_players()
+            .iter()
+            .enumerate()
+            .flat_map(|(ip, p)| p.get_tricks().iter().map(move |t| (Some(ip), t)))
+            .collect();
+        history.sort_by_key(|(_, t)| t.idx());
+        history.push((None, solution.get_current_trick()));
+
+        let first = history[0].1;
+        let current = (first.idx(), first.get_first_player(), []).into();
+        let mut state = State::from_parts(players, current, vec![]);
+        let mut done = done_tasks(&state);
+        let mut tricks = vec![];
+        for (winner, trick) in history {
+            *state.get_mut_current_trick() = (trick.idx(), trick.get_first_player(), []).into();
+            let mut row = TrickReport::new(trick, n_players, winner);
+            for (j, card) in trick.iter().enumerate() {
+                let ip = (trick.get_first_player() + j) % n_players;
+                state.get_mut_player(ip).remove_card_from_hand(card)?;
+                if j + 1 == n_players {
+                    state.get_mut_current_trick().clear();
+                    state
+                        .get_mut_player(winner.expect("only won tricks are full"))
+                        .add_trick(trick.clone())?;
+                } else {
+                    state.get_mut_current_trick().push(*card);
+                }
+
+                let now_done = done_tasks(&state);
+                for (ip, (before, after)) in done.iter().zip(&now_done).enumerate() {
+                    for (it, (&before, &after)) in before.iter().zip(after).enumerate() {
+                        if !before && after {
+                            let task = &state.get_player(ip).get_tasks()[it];
+                            row.completed.push((ip, task.describe(locale)));
+                        }
+                    }
+                }
+                done = now_done;
+            }
+            if !trick.is_empty() || !row.completed.is_empty() {
+                tricks.push(row);
+            }
+        }
+
+        Ok(Report {
+            locale,
+            seats,
+            tricks,
+        })
+    }
+
+    fn labels(&self) -> &'static Labels {
+        match self.locale {
+            Locale::English => &ENGLISH,
+            Locale::French => &FRENCH,
+        }
+    }
+
+    fn seat_name(&self, ip: usize) -> String {
+        let labels = self.labels();
+        if self.seats[ip].captain {
+            format!("{} {} ({})", labels.seat, ip, labels.captain)
+        } else {
+            format!("{} {}", labels.seat, ip)
+        }
+    }
+
+    /// Renders the report as Markdown, the winning card of each trick in bold.
+    pub fn to_markdown(&self) -> String {
+        let labels = self.labels();
+        let mut out = format!("# {}\n\n## {}\n", labels.title, labels.seats);
+        for (ip, seat) in self.seats.iter().enumerate() {
+            let hand: Vec<String> = seat.hand.iter().map(Card::to_string).collect();
+            write!(
+                out,
+                "\n### {}\n\n{}: {}\n\n",
+                self.seat_name(ip),
+                labels.hand,
+                hand.join(" ")
+            )
+            .unwrap();
+            if seat.tasks.is_empty() {
+                writeln!(out, "{}", labels.no_task).unwrap();
+            }
+            for task in &seat.tasks {
+                writeln!(out, "- {}", task).unwrap();
+            }
+        }
+
+        write!(
+            out,
+            "\n## {}\n\n| {} | {} |",
+            labels.tricks, labels.trick, labels.leader
+        )
+        .unwrap();
+        for ip in 0..self.seats.len() {
+            write!(out, " {} {} |", labels.seat, ip).unwrap();
+        }
+        writeln!(out, " {} |", labels.completed).unwrap();
+        out += "| ---: | ---: |";
+        out += &" --- |".repeat(self.seats.len() + 1);
+        out += "\n";
+        for trick in &self.tricks {
+            write!(out, "| {} | {} |", trick.idx + 1, trick.leader).unwrap();
+            for (ip, card) in trick.cards.iter().enumerate() {
+                match card {
+                    Some(card) if trick.winner == Some(ip) => {
+                        write!(out, " **{}** |", card).unwrap();
+                    }
+                    Some(card) => {
+                        write!(out, " {} |", card).unwrap();
+                    }
+                    None => out += " |",
+                }
+            }
+            let completed: Vec<String> = trick
+                .completed
+                .iter()
+                .map(|(ip, task)| format!("{} {}: {}", labels.seat, ip, task.replace('|', "\\|")))
+                .collect();
+            writeln!(out, " {} |", completed.join("<br>")).unwrap();
+        }
+        out
+    }
+
+    /// Renders the report as a standalone HTML page, cards being colored and the winning card
+    /// of each trick highlighted.
+    pub fn to_html(&self) -> String {
+        let labels = self.labels();
+        let lang = match self.locale {
+            Locale::English => "en",
+            Locale::French => "fr",
+        };
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n<h2>{}</h2>\n",
+            lang, labels.title, STYLE, labels.title, labels.seats
+        );
+        for (ip, seat) in self.seats.iter().enumerate() {
+            let hand: Vec<String> = seat.hand.iter().map(html_card).collect();
+            write!(
+                out,
+                "<section>\n<h3>{}</h3>\n<p>{}: {}</p>\n",
+                escape(&self.seat_name(ip)),
+                labels.hand,
+                hand.join(" ")
+            )
+            .unwrap();
+            if seat.tasks.is_empty() {
+                writeln!(out, "<p>{}</p>", labels.no_task).unwrap();
+            } else {
+                out += "<ul>\n";
+                for task in &seat.tasks {
+                    writeln!(out, "<li>{}</li>", escape(task)).unwrap();
+                }
+                out += "</ul>\n";
+            }
+            out += "</section>\n";
+        }
+
+        write!(
+            out,
+            "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th>",
+            labels.tricks, labels.trick, labels.leader
+        )
+        .unwrap();
+        for ip in 0..self.seats.len() {
+            write!(out, "<th>{} {}</th>", labels.seat, ip).unwrap();
+        }
+        writeln!(out, "<th>{}</th></tr>", labels.completed).unwrap();
+        for trick in &self.tricks {
+            write!(
+                out,
+                "<tr><td>{}</td><td>{}</td>",
+                trick.idx + 1,
+                trick.leader
+            )
+            .unwrap();
+            for (ip, card) in trick.cards.iter().enumerate() {
+                match card {
+                    Some(card) if trick.winner == Some(ip) => {
+                        write!(out, "<td class=\"winner\">{}</td>", html_card(card)).unwrap();
+                    }
+                    Some(card) => {
+                        write!(out, "<td>{}</td>", html_card(card)).unwrap();
+                    }
+                    None => out += "<td></td>",
+                }
+            }
+            let completed: Vec<String> = trick
+                .completed
+                .iter()
+                .map(|(ip, task)| format!("{} {}: {}", labels.seat, ip, escape(task)))
+                .collect();
+            writeln!(out, "<td>{}</td></tr>", completed.join("<br>")).unwrap();
+        }
+        out += "</table>\n</body>\n</html>\n";
+        out
+    }
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: center; }
+td.winner { background: #fff3b0; font-weight: bold; }
+.card { font-family: monospace; font-weight: bold; }
+.pink { color: #d6336c; }
+.green { color: #2b8a3e; }
+.blue { color: #1c7ed6; }
+.yellow { color: #b08900; }
+.submarine { color: #343a40; }
+";
+
+fn html_card(card: &Card) -> String {
+    let color = match card {
+        Card::Pink(_) => "pink",
+        Card::Green(_) => "green",
+        Card::Blue(_) => "blue",
+        Card::Yellow(_) => "yellow",
+        Card::Submarine(_) => "submarine",
+    };
+    format!("<span class=\"card {}\">{}</span>", color, card)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        card::{blue, green, pink, submarine},
+        player::Player,
+        task::win_cards::TaskWinCards,
+    };
+
+    /// Player 1 must win the blue 1, which player 0 cannot keep from it.
+    fn solution() -> State {
+        let p0 = Player::new([blue(1), pink(2)].into());
+        let mut p1 = Player::new([submarine(4), blue(3)].into());
+        p1.add_task(TaskWinCards::new(None, [blue(1)])).unwrap();
+        let p2 = Player::new([green(5), blue(2)].into());
+        State::new([p0, p1, p2]).play().unwrap()
+    }
+
+    #[test]
+    fn replays_solution() {
+        let report = Report::new(&solution(), Locale::English).unwrap();
+        assert_eq!(report.seats[1].hand, [blue(3), submarine(4)]);
+        assert!(report.seats[1].captain);
+        assert_eq!(report.tricks.len(), 1);
+
+        let trick = &report.tricks[0];
+        assert_eq!(trick.leader, 1);
+        assert_eq!(trick.winner, Some(1));
+        assert_eq!(trick.cards[1], Some(blue(3)));
+        assert_eq!(trick.completed, [(1, "Win the blue 1".to_string())]);
+    }
+
+    #[test]
+    fn unfinished_trick() {
+        let mut state = solution();
+        let (hands, _) = state.history().unwrap();
+        state = State::replay(hands, vec![vec![]; 3], &[blue(3), blue(2)]).unwrap();
+        let report = Report::new(&state, Locale::English).unwrap();
+        assert_eq!(report.tricks.len(), 1);
+        assert_eq!(report.tricks[0].winner, None);
+        assert_eq!(report.tricks[0].cards, [None, Some(blue(3)), Some(blue(2))]);
+        assert!(report.to_markdown().contains("| 1 | 1 | | B3 | B2 |  |"));
+    }
+
+    #[test]
+    fn markdown() {
+        let md = Report::new(&solution(), Locale::English)
+            .unwrap()
+            .to_markdown();
+        assert!(md.contains("### Seat 1 (captain)\n\nHand: B3 S4\n\n- Win the blue 1\n"));
+        assert!(md.contains("| Trick | Leader | Seat 0 | Seat 1 | Seat 2 | Tasks completed |"));
+        assert!(md.contains("| 1 | 1 | B1 | **B3** | B2 | Seat 1: Win the blue 1 |"));
+
+        let md = Report::new(&solution(), Locale::French)
+            .unwrap()
+            .to_markdown();
+        assert!(md.contains("### Joueur 1 (capitaine)"));
+    }
+
+    #[test]
+    fn html() {
+        let html = Report::new(&solution(), Locale::English).unwrap().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<td class=\"winner\"><span class=\"card blue\">B3</span></td>"));
+        assert!(!html.contains("http"));
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
@@ -0,0 +1,371 @@
+//! Serialization of the game model with [serde], behind the `serde` feature.
+//!
+//! Cards are written in their short notation (`"P7"`, `"S4"`), so that hands and tricks stay
+//! readable in JSON. A [`State`] is written along with [`FORMAT_VERSION`], and is validated with
+//! [`State::validate`] when read, so that a game coming from a file or a front end can be
+//! played safely.
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+use crate::{
+    card::Card, communication::Communication, hand::Hand, player::Player, state::State,
+    trick::Trick,
+};
+
+/// Version of the serialized form of [`State`], to be increased whenever it changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Hand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cards = Vec::<Card>::deserialize(deserializer)?;
+        for (i, card) in cards.iter().enumerate() {
+            if cards[..i].contains(card) {
+                return Err(D::Error::custom(format!("card {} appears twice", card)));
+            }
+        }
+        Ok(cards.into())
+    }
+}
+
+/// Serialized form of [`State`].
+#[derive(Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct StateRepr {
+    version: u32,
+    players: Vec<Player>,
+    current_trick: Trick,
+    #[serde(default)]
+    communications: Vec<Communication>,
+}
+
+impl From<State> for StateRepr {
+    fn from(state: State) -> Self {
+        StateRepr {
+            version: FORMAT_VERSION,
+            players: state.get_players().to_vec(),
+            current_trick: state.get_current_trick().clone(),
+            communications: state.get_communications().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<StateRepr> for State {
+    type Error = String;
+
+    fn try_from(repr: StateRepr) -> Result<Self, Self::Error> {
+        if repr.version != FORMAT_VERSION {
+            return Err(format!(
+                "unsupported format version {} (expected {})",
+                repr.version, FORMAT_VERSION
+            ));
+        }
+        let state = State::from_parts(repr.players, repr.current_trick, repr.communications);
+        state.validate().map_err(|e| e.to_string())?;
+        Ok(state)
+    }
+}
+
+/// [`std::cmp::Ordering`] as `"less"`, `"equal"` or `"greater"`, to be used with
+/// `#[serde(with = "...")]`.
+pub(crate) mod ordering {
+    use std::cmp::Ordering;
+
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(ordering: &Ordering, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match ordering {
+            Ordering::Less => "less",
+            Ordering::Equal => "equal",
+            Ordering::Greater => "greater",
+        })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Ordering, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "less" => Ok(Ordering::Less),
+            "equal" => Ok(Ordering::Equal),
+            "greater" => Ok(Ordering::Greater),
+            other => Err(D::Error::unknown_variant(
+                other,
+                &["less", "equal", "greater"],
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::{
+        card::{blue, pink, submarine},
+        catalog,
+        communication::CommunicationKind,
+        dsl,
+        state::GameError,
+        task::{self, Task, all_of::TaskAllOf, not::TaskNot},
+    };
+
+    fn round_trip(state: &State) -> State {
+        let json = serde_json::to_string(state).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn cards_are_readable() {
+        assert_eq!(serde_json::to_string(&pink(7)).unwrap(), "\"P7\"");
+        let hand: Hand = serde_json::from_str("[\"B1\", \"s4\"]").unwrap();
+        assert_eq!(hand, vec![blue(1), submarine(4)].into());
+        assert!(serde_json::from_str::<Card>("\"S5\"").is_err());
+        assert!(serde_json::from_str::<Hand>("[\"B1\", \"B1\"]").is_err());
+    }
+
+    #[test]
+    fn every_task_round_trips() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for entry in catalog::entries() {
+            let mut state = State::new_random_with_rng(4, &mut rng);
+            let ip = (state.first_player() + 1) % 4;
+            state.get_mut_player(ip).add_task(entry.build(4)).unwrap();
+            for _ in 0..6 {
+                let playable = state
+                    .get_current_player()
+                    .get_hand()
+                    .playable_cards(state.get_current_trick().first());
+                state.play_card(playable.choose(&mut rng).unwrap()).unwrap();
+            }
+            assert_eq!(round_trip(&state), state, "{}", entry.id);
+        }
+
+        let combined: Task = TaskAllOf::new(
+            None,
+            [
+                task::win_trick_with_same_amount_pink_and_blue().into(),
+                TaskNot::new(None, task::win_more_yellow_than_blue()).into(),
+            ],
+        )
+        .into();
+        let json = serde_json::to_string(&combined).unwrap();
+        assert_eq!(serde_json::from_str::<Task>(&json).unwrap(), combined);
+    }
+
+    #[test]
+    fn tasks_serialize_the_same_way() {
+        let json = |src| serde_json::to_string(&dsl::parse_task(src, 4).unwrap()).unwrap();
+        let colors = "win exactly 1 yellow, 2 blue, 1 green, 3 pink, 1 submarine";
+        assert_eq!(json(colors), json(colors));
+        assert!(json(colors).contains(r#"[["P1",3],["G1",1],["B1",2],["Y1",1],["S1",1]]"#));
+        let values = "win at least 1 of 9, 2 of 3, 1 of 5, 1 of 1";
+        assert_eq!(json(values), json(values));
+        let tricks = "win tricks [7 2 5 1 9] and last trick";
+        assert!(json(tricks).contains("[0,1,4,6,8]"));
+        assert!(json("dont win first 6 tricks").contains("[0,1,2,3,4,5]"));
+    }
+
+    #[test]
+    fn invalid_games_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let state = State::new_random_with_rng(3, &mut rng);
+        let mut json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["version"], FORMAT_VERSION);
+
+        let mut wrong_version = json.clone();
+        wrong_version["version"] = 2.into();
+        let err = serde_json::from_value::<State>(wrong_version).unwrap_err();
+        assert!(err.to_string().contains("unsupported format version 2"));
+
+        // Moving a card of player 1 to player 0 without removing it
+        let mut duplicate = json.clone();
+        let card = json["players"][1]["hand"][0].clone();
+        duplicate["players"][0]["hand"]
+            .as_array_mut()
+            .unwrap()
+            .push(card.clone());
+        let err = serde_json::from_value::<State>(duplicate).unwrap_err();
+        let card: Card = serde_json::from_value(card).unwrap();
+        assert!(
+            err.to_string()
+                .contains(&GameError::DuplicateCard(card).to_string())
+        );
+
+        // Removing the submarine 4
+        for p in json["players"].as_array_mut().unwrap() {
+            p["hand"].as_array_mut().unwrap().retain(|c| c != "S4");
+        }
+        let err = serde_json::from_value::<State>(json).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&GameError::MissingCaptain.to_string())
+        );
+    }
+
+    /// Checks that `change` makes the game fail to load with `error`.
+    fn assert_rejected<F: Fn(&mut Value)>(json: &Value, change: F, error: GameError) {
+        let mut json = json.clone();
+        change(&mut json);
+        let err = serde_json::from_value::<State>(json).unwrap_err();
+        assert!(err.to_string().contains(&error.to_string()), "{}", err);
+    }
+
+    #[test]
+    fn inconsistent_tricks_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut state = State::new_random_with_rng(3, &mut rng);
+        for _ in 0..6 {
+            let hand = state.get_current_player().get_hand();
+            let card = hand.playable_cards(state.get_current_trick().first())[0];
+            state.play_card(&card).unwrap();
+        }
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(
+            serde_json::from_value::<State>(json.clone()).unwrap(),
+            state
+        );
+
+        let holder = (0..3)
+            .find(|&i| state.get_player(i).get_tricks()[..].first().map(Trick::idx) == Some(0))
+            .unwrap();
+        let other = (holder + 1) % 3;
+        assert_rejected(
+            &json,
+            |j| j["players"][holder]["tricks"][0]["first_player"] = 7.into(),
+            GameError::InvalidTrickLeader(0, 7),
+        );
+        assert_rejected(
+            &json,
+            |j| j["current_trick"]["idx"] = 3.into(),
+            GameError::MissingTrick(2),
+        );
+        assert_rejected(
+            &json,
+            |j| j["current_trick"]["idx"] = 1.into(),
+            GameError::DuplicateTrick(1),
+        );
+        let leader = state.first_player();
+        assert_rejected(
+            &json,
+            |j| j["current_trick"]["first_player"] = ((leader + 1) % 3).into(),
+            GameError::InvalidTrickLeader(2, (leader + 1) % 3),
+        );
+        assert_rejected(
+            &json,
+            |j| {
+                let trick = j["players"][holder]["tricks"]
+                    .as_array_mut()
+                    .unwrap()
+                    .remove(0);
+                j["players"][other]["tricks"]
+                    .as_array_mut()
+                    .unwrap()
+                    .push(trick);
+            },
+            GameError::TrickNotHeldByWinner(0, holder),
+        );
+    }
+
+    #[test]
+    fn invalid_communications_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let state = State::new_random_with_rng(3, &mut rng);
+        let hand = state.get_player(0).get_hand();
+        // The highest card of a color of which player 0 holds several cards
+        let card = *hand
+            .iter()
+            .filter(|c| !c.is_submarine())
+            .filter(|c| hand.iter().filter(|o| o.same_color(c)).count() > 1)
+            .max_by_key(|c| c.val())
+            .unwrap();
+        let communication =
+            |seat, card, kind| serde_json::to_value(Communication { seat, card, kind }).unwrap();
+        let highest = communication(0, card, CommunicationKind::Highest);
+        let mut json = serde_json::to_value(&state).unwrap();
+        json["communications"] = json!([highest]);
+        assert!(serde_json::from_value::<State>(json.clone()).is_ok());
+
+        assert_rejected(
+            &json,
+            |j| j["communications"][0]["seat"] = 5.into(),
+            GameError::InvalidSeat(5),
+        );
+        assert_rejected(
+            &json,
+            |j| j["communications"] = json!([highest, highest]),
+            GameError::AlreadyCommunicated(0),
+        );
+        let only = communication(0, card, CommunicationKind::Only);
+        assert_rejected(
+            &json,
+            |j| j["communications"] = json!([only]),
+            GameError::FalseCommunication(card, hand.to_vec()),
+        );
+        let other_card = state.get_player(1).get_hand()[0];
+        assert_rejected(
+            &json,
+            |j| j["communications"][0]["card"] = json!(other_card),
+            GameError::CardNotFound(other_card, hand.to_vec()),
+        );
+        let on_submarine = communication(0, submarine(4), CommunicationKind::Only);
+        assert_rejected(
+            &json,
+            |j| j["communications"] = json!([on_submarine]),
+            GameError::CannotCommunicateSubmarine(submarine(4)),
+        );
+    }
+
+    #[test]
+    fn invalid_tasks_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let state = State::new_random_with_rng(3, &mut rng);
+        let ip = (state.first_player() + 1) % 3;
+        let last_trick = serde_json::to_value(Task::from(task::win_last_trick())).unwrap();
+        let tasks = [
+            json!({"WinMoreTricks": {
+                "difficulty": null, "everyone_else_together": true, "fewer": true
+            }}),
+            json!({"AtLeast": {"difficulty": null, "k": 0, "tasks": [last_trick]}}),
+            json!({"AtLeast": {"difficulty": null, "k": 2, "tasks": [last_trick]}}),
+            json!({"AnyOf": {"difficulty": null, "tasks": []}}),
+            json!({"WinCards": {"difficulty": null, "cards": ["B1", "B1"]}}),
+            json!({"Not": {"difficulty": null, "task": {"AllOf": {"difficulty": null, "tasks": []}}}}),
+            json!({"WinTrickWithPred": {
+                "difficulty": null, "condition": {"SameNbOfColors": ["P1", "P5"]}
+            }}),
+        ];
+        for task in tasks {
+            let mut json = serde_json::to_value(&state).unwrap();
+            json["players"][ip]["tasks"] = vec![task.clone()].into();
+            let err = serde_json::from_value::<State>(json).unwrap_err();
+            assert!(err.to_string().contains("Invalid task"), "{}", task);
+        }
+    }
+
+    #[test]
+    fn captain_forbidden_task_is_rejected() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let state = State::new_random_with_rng(3, &mut rng);
+        let mut json = serde_json::to_value(&state).unwrap();
+        let task = serde_json::to_value(Task::from(task::win_more_tricks_than_captain())).unwrap();
+        json["players"][state.first_player()]["tasks"] = vec![task].into();
+        assert!(serde_json::from_value::<State>(json).is_err());
+    }
+}
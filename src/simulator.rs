@@ -0,0 +1,223 @@
+//! Self-play simulation of hidden-information games.
+//!
+//! Each game is dealt randomly, a mission is drawn, and every seat is played by a [`Strategy`]
+//! only seeing its own [`crate::view::PlayerView`]. Results are aggregated over many games.
+use std::collections::HashMap;
+
+use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+use crate::{
+    locale::Locale,
+    state::{GameError, MAX_REDEALS, State},
+    strategy::Strategy,
+    task::{BaseTask, Task, TaskStatus},
+};
+
+/// Aggregated results of a simulation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationReport {
+    pub n_games: usize,
+    pub n_successes: usize,
+    /// Number of games in which each task (identified by its description in the default
+    /// locale) failed.
+    pub task_failures: HashMap<String, usize>,
+    /// Total number of tricks played (the last one may be incomplete if the game ended early).
+    pub n_tricks: usize,
+}
+
+impl SimulationReport {
+    /// Fraction of games won, 0 if no game was played.
+    pub fn success_rate(&self) -> f64 {
+        self.per_game(self.n_successes)
+    }
+
+    /// Average number of tricks played in a game, 0 if no game was played.
+    pub fn average_length(&self) -> f64 {
+        self.per_game(self.n_tricks)
+    }
+
+    fn per_game(&self, total: usize) -> f64 {
+        if self.n_games == 0 {
+            0.
+        } else {
+            total as f64 / self.n_games as f64
+        }
+    }
+}
+
+/// Returns a mission generator drawing `n_tasks` distinct tasks from `pool`. Tasks are handed
+/// out one at a time, starting with the captain and going clockwise; a task the captain cannot
+/// take goes to the next player.
+pub fn draw_from_pool(
+    pool: Vec<Task>,
+    n_tasks: usize,
+) -> impl FnMut(&State, &mut StdRng) -> Vec<(usize, Task)> {
+    assert!(n_tasks <= pool.len(), "not enough tasks in the pool");
+    move |state, rng| {
+        pool.choose_multiple(rng, n_tasks)
+            .enumerate()
+            .map(|(i, task)| {
+                let mut ip = (state.first_player() + i) % state.n_players();
+                if task.captain_forbidden() && ip == state.first_player() {
+                    ip = (ip + 1) % state.n_players();
+                }
+                (ip, task.clone())
+            })
+            .collect()
+    }
+}
+
+pub struct Simulator<F> {
+    n_players: usize,
+    rng: StdRng,
+    mission: F,
+}
+
+impl<F> Simulator<F>
+where
+    F: FnMut(&State, &mut StdRng) -> Vec<(usize, Task)>,
+{
+    /// `mission` draws the tasks of a freshly dealt game, along with the seat holding each one.
+    /// Cards are dealt again as long as a task requires it, at most [`MAX_REDEALS`] times.
+    pub fn new(n_players: usize, seed: u64, mission: F) -> Self {
+        Self {
+            n_players,
+            rng: StdRng::seed_from_u64(seed),
+            mission,
+        }
+    }
+
+    /// Plays `n_games` games, seat `i` being played by `strategies[i]`. Fails if the mission
+    /// gives the captain a task they cannot take, or if no deal meets its redeal conditions.
+    pub fn run(
+        &mut self,
+        n_games: usize,
+        strategies: &mut [Box<dyn Strategy>],
+    ) -> Result<SimulationReport, GameError> {
+        assert_eq!(strategies.len(), self.n_players, "one strategy per seat");
+        let mut report = SimulationReport::default();
+        for _ in 0..n_games {
+            self.play_game(strategies, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    fn play_game(
+        &mut self,
+        strategies: &mut [Box<dyn Strategy>],
+        report: &mut SimulationReport,
+    ) -> Result<(), GameError> {
+        let mut dealt = None;
+        for _ in 0..MAX_REDEALS {
+            let mut state = State::new_random_with_rng(self.n_players, &mut self.rng);
+            for (ip, task) in (self.mission)(&state, &mut self.rng) {
+                state.get_mut_player(ip).add_task(task)?;
+            }
+            if !state.needs_redeal() {
+                dealt = Some(state);
+                break;
+            }
+        }
+        let mut state = dealt.ok_or(GameError::TooManyRedeals(MAX_REDEALS))?;
+
+        while state.game_status() == TaskStatus::Unknown && !state.game_is_over() {
+            let ip = state.get_current_player_idx();
+            let view = state.view(ip);
+            let card = strategies[ip].choose_card(&view, &view.legal_cards());
+            state
+                .play_card(&card)
+                .expect("strategies should play a card from their hand");
+        }
+
+        report.n_games += 1;
+        if state.game_status() == TaskStatus::Done {
+            report.n_successes += 1;
+        }
+        report.n_tricks += state.get_current_trick().idx();
+        if !state.get_current_trick().is_empty() {
+            report.n_tricks += 1;
+        }
+        for (ip, p) in state.get_players().iter().enumerate() {
+            for task in p.get_tasks() {
+                if task.eval(&state, ip) == TaskStatus::Failed {
+                    *report
+                        .task_failures
+                        .entry(task.describe(Locale::default()))
+                        .or_default() += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dsl,
+        strategy::random::RandomStrategy,
+        task::{win_first_trick, win_more_tricks_than_captain, win_pink_1},
+    };
+
+    use super::*;
+
+    fn simulate(seed: u64) -> SimulationReport {
+        let pool = vec![win_pink_1().into(), win_first_trick().into()];
+        let mut simulator = Simulator::new(4, seed, draw_from_pool(pool, 2));
+        let mut strategies: Vec<Box<dyn Strategy>> = (0..4)
+            .map(|i| Box::new(RandomStrategy::new(seed + i)) as Box<dyn Strategy>)
+            .collect();
+        simulator.run(20, &mut strategies).unwrap()
+    }
+
+    #[test]
+    fn reproducible() {
+        let report = simulate(42);
+        assert_eq!(report.n_games, 20);
+        assert!(report.average_length() >= 1. && report.average_length() <= 10.);
+        let n_failures: usize = report.task_failures.values().sum();
+        assert_eq!(n_failures > 0, report.n_successes < 20);
+        assert_eq!(report, simulate(42));
+        assert_ne!(report, simulate(43));
+        for description in report.task_failures.keys() {
+            assert!(["Win the pink 1", "Win the first trick"].contains(&description.as_str()));
+        }
+    }
+
+    #[test]
+    fn empty_report() {
+        let report = SimulationReport::default();
+        assert_eq!(report.success_rate(), 0.);
+        assert_eq!(report.average_length(), 0.);
+    }
+
+    #[test]
+    fn endless_redeal() {
+        // The captain always holds the 4 submarine
+        let task = dsl::parse_task("win at least 3 pink redeal if [S4]", 3).unwrap();
+        let mission = move |_: &State, _: &mut StdRng| vec![(1, task.clone())];
+        let mut simulator = Simulator::new(3, 0, mission);
+        let mut strategies: Vec<Box<dyn Strategy>> = (0..3)
+            .map(|i| Box::new(RandomStrategy::new(i)) as Box<dyn Strategy>)
+            .collect();
+        assert!(matches!(
+            simulator.run(1, &mut strategies),
+            Err(GameError::TooManyRedeals(MAX_REDEALS))
+        ));
+    }
+
+    #[test]
+    fn captain_forbidden_task() {
+        let captain = |state: &State, _: &mut StdRng| {
+            vec![(state.first_player(), win_more_tricks_than_captain().into())]
+        };
+        let mut simulator = Simulator::new(3, 0, captain);
+        let mut strategies: Vec<Box<dyn Strategy>> = (0..3)
+            .map(|i| Box::new(RandomStrategy::new(i)) as Box<dyn Strategy>)
+            .collect();
+        assert!(matches!(
+            simulator.run(1, &mut strategies),
+            Err(GameError::CaptainForbiddenTask(_))
+        ));
+    }
+}
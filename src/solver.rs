@@ -1,13 +1,129 @@
 use crate::{
+    player,
     state::{GameError, State},
-    task::TaskStatus,
+    task::{Task, TaskStatus},
 };
 
+/// Result of [`State::count_solutions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SolutionCount {
+    /// Number of winning lines found.
+    pub lines: usize,
+    /// False if the budget was exceeded, in which case `lines` is only a lower bound.
+    pub exhaustive: bool,
+}
+
 impl State {
     pub fn play(&mut self) -> Result<Self, GameError> {
+        self.search(None)
+    }
+
+    /// Same as [`State::play`], but gives up with [`GameError::BudgetExceeded`] once `budget`
+    /// states have been explored. Useful when the solver is called many times (by bots for
+    /// example) and a slow answer is as bad as no answer.
+    pub fn play_with_budget(&mut self, budget: usize) -> Result<Self, GameError> {
+        self.search(Some(budget))
+    }
+
+    /// Counts the winning lines, that is the sequences of cards after which the mission is
+    /// completed, exploring at most `budget` states.
+    pub fn count_solutions(&self, budget: usize) -> SolutionCount {
+        let mut stack = vec![self.clone()];
+        let mut count = SolutionCount {
+            lines: 0,
+            exhaustive: true,
+        };
+        let mut explored = 0;
+
+        while let Some(state) = stack.pop() {
+            if explored >= budget {
+                count.exhaustive = false;
+                break;
+            }
+            explored += 1;
+
+            match state.game_status() {
+                TaskStatus::Done => {
+                    count.lines += 1;
+                    continue;
+                }
+                TaskStatus::Failed => continue,
+                TaskStatus::Unknown => (),
+            };
+
+            for card in state
+                .get_current_player()
+                .get_hand()
+                .playable_cards(state.get_current_trick().first())
+            {
+                let mut new_state = state.clone();
+                if new_state.play_card(&card).is_ok() {
+                    stack.push(new_state);
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Returns every prediction `x` for which the mission is feasible, for the "Win X tricks"
+    /// task `task_idx` of player `ip`, so that the task holder can choose its prediction.
+    ///
+    /// The searches for all the predictions share `budget` explored states, after which the
+    /// search gives up with [`GameError::BudgetExceeded`].
+    pub fn feasible_predictions(
+        &self,
+        ip: usize,
+        task_idx: usize,
+        budget: usize,
+    ) -> Result<Vec<usize>, GameError> {
+        if !matches!(
+            self.get_player(ip).get_tasks().get(task_idx),
+            Some(Task::WinNbTricks(t)) if t.is_prediction()
+        ) {
+            return Err(GameError::NotAPrediction(ip, task_idx));
+        }
+
+        let mut remaining = budget;
+        let mut feasible = Vec::new();
+        for x in 0..=player::n_tricks_total(self.n_players()) {
+            let mut state = self.clone();
+            if let Task::WinNbTricks(task) = &mut state.get_mut_player(ip).get_mut_tasks()[task_idx]
+            {
+                task.predict(x);
+            }
+            match state.search_within(&mut remaining) {
+                Ok(_) => feasible.push(x),
+                Err(GameError::NoSolutionFound) => (),
+                Err(GameError::BudgetExceeded(_)) => {
+                    return Err(GameError::BudgetExceeded(budget));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(feasible)
+    }
+
+    fn search(&self, budget: Option<usize>) -> Result<Self, GameError> {
+        let mut remaining = budget.unwrap_or(usize::MAX);
+        self.search_within(&mut remaining)
+            .map_err(|e| match (e, budget) {
+                (GameError::BudgetExceeded(_), Some(budget)) => GameError::BudgetExceeded(budget),
+                (e, _) => e,
+            })
+    }
+
+    /// Same as [`State::play_with_budget`], taking the states explored from `remaining`, so
+    /// that several searches can share a budget.
+    pub(crate) fn search_within(&self, remaining: &mut usize) -> Result<Self, GameError> {
         let mut stack = vec![self.clone()];
 
         while let Some(state) = stack.pop() {
+            if *remaining == 0 {
+                return Err(GameError::BudgetExceeded(0));
+            }
+            *remaining -= 1;
+
             // Checking if the game status can be determined, in which case we early exit.
             match state.game_status() {
                 TaskStatus::Done => return Ok(state),
@@ -19,7 +135,7 @@ impl State {
             for card in state
                 .get_current_player()
                 .get_hand()
-                .playable_cards(state.get_current_trick().get(0))
+                .playable_cards(state.get_current_trick().first())
             {
                 let mut new_state = state.clone();
                 new_state.play_card(&card)?;
@@ -28,6 +144,6 @@ impl State {
         }
 
         // If we end up here, we've tried everything and it didn't work.
-        return Err(GameError::NoSolutionFound);
+        Err(GameError::NoSolutionFound)
     }
 }
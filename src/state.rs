@@ -1,20 +1,35 @@
-use rand::seq::SliceRandom;
+use std::collections::HashSet;
+
+use rand::{Rng, seq::SliceRandom};
 use thiserror::Error;
 
 use crate::{
-    card::{self, COLOR_RANGE, COLORS, Card, NB_CARDS, SUBMARINE_RANGE},
+    card::{self, Card, NB_CARDS},
+    communication::Communication,
     player::{self, Player},
-    task::TaskStatus,
+    task::{BaseTask, Task, TaskStatus},
     trick::Trick,
 };
 
-#[derive(Debug, Clone)]
+/// Number of deals tried before giving up on redeal conditions which are (almost) always met.
+pub const MAX_REDEALS: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialization::StateRepr",
+        try_from = "crate::serialization::StateRepr"
+    )
+)]
 pub struct State {
     players: Vec<Player>,
     current_trick: Trick,
+    communications: Vec<Communication>,
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, PartialEq, Error)]
 pub enum GameError {
     #[error("Captain was not found - is there a 4 of submarine in the game?")]
     MissingCaptain,
@@ -26,6 +41,60 @@ pub enum GameError {
     NoSolutionFound,
     #[error("Card {0:?} was not found in {1:?}")]
     CardNotFound(Card, Vec<Card>),
+    #[error("The solver budget of {0} explored states was exceeded.")]
+    BudgetExceeded(usize),
+    #[error("Player {0} has already communicated a card.")]
+    AlreadyCommunicated(usize),
+    #[error("Communication is only allowed before the first card of a trick.")]
+    CommunicationDuringTrick,
+    #[error("Submarine {0:?} cannot be communicated.")]
+    CannotCommunicateSubmarine(Card),
+    #[error("Communication of {0:?} does not match the hand {1:?}.")]
+    FalseCommunication(Card, Vec<Card>),
+    #[error("A distress signal can only be called before the first trick.")]
+    DistressAfterStart,
+    #[error("Invalid distress signal: expected one card per player ({0}), got {1}.")]
+    InvalidDistressSize(usize, usize),
+    #[error("Submarine {0:?} cannot be passed during a distress signal.")]
+    CannotPassSubmarine(Card),
+    #[error("The captain cannot take task {0:?}.")]
+    CaptainForbiddenTask(Task),
+    #[error("Task {1} of player {0} is not a prediction of the number of tricks.")]
+    NotAPrediction(usize, usize),
+    #[error("Invalid number of players: {0}.")]
+    InvalidNPlayers(usize),
+    #[error("Invalid first player {0} for the current trick.")]
+    InvalidFirstPlayer(usize),
+    #[error("The current trick already has {0} cards: it should have been won.")]
+    CurrentTrickFull(usize),
+    #[error("Card {0:?} appears more than once in the game.")]
+    DuplicateCard(Card),
+    #[error("Tasks are given for {1} players, expected {0}.")]
+    InvalidTaskAssignment(usize, usize),
+    #[error("Play {0}: player {1} does not hold {2:?}.")]
+    CardNotHeld(usize, usize, Card),
+    #[error("Play {0}: player {1} cannot play {2:?} as it must follow the color of {3:?}.")]
+    MustFollowColor(usize, usize, Card, Card),
+    #[error("Play {0}: every trick is already won.")]
+    PlayAfterGameOver(usize),
+    #[error("Trick {0} is missing from the history of the game.")]
+    MissingTrick(usize),
+    #[error("It is not the turn of player {0}.")]
+    NotYourTurn(usize),
+    #[error("No deal met the redeal conditions of the tasks in {0} attempts.")]
+    TooManyRedeals(usize),
+    #[error("Invalid task: {0}.")]
+    InvalidTask(String),
+    #[error("Hands of {0} and {1} cards cannot be dealt in the same game.")]
+    UnevenHands(usize, usize),
+    #[error("Trick {0} appears more than once in the game.")]
+    DuplicateTrick(usize),
+    #[error("Trick {0} cannot be opened by player {1}.")]
+    InvalidTrickLeader(usize, usize),
+    #[error("Trick {0} is won by player {1}, who does not hold it.")]
+    TrickNotHeldByWinner(usize, usize),
+    #[error("There is no seat {0}.")]
+    InvalidSeat(usize),
 }
 
 impl State {
@@ -55,24 +124,20 @@ impl State {
         State {
             players,
             current_trick: (0, captain, vec![]).into(),
+            communications: Vec::new(),
         }
     }
 
     pub fn new_random(n_players: usize) -> Self {
-        player::check_valid_n_players(n_players).unwrap();
+        State::new_random_with_rng(n_players, &mut rand::rng())
+    }
 
-        let mut cards = Vec::new();
-        for i in COLOR_RANGE {
-            for variant in COLORS {
-                cards.push(variant(i));
-            }
-        }
-        for i in SUBMARINE_RANGE {
-            cards.push(Card::Submarine(i));
-        }
+    /// Same as [`State::new_random`], using the given random generator (to get reproducible deals).
+    pub fn new_random_with_rng<R: Rng>(n_players: usize, rng: &mut R) -> Self {
+        player::check_valid_n_players(n_players).unwrap();
 
-        let mut rng = rand::rng();
-        cards.shuffle(&mut rng);
+        let mut cards = card::deck();
+        cards.shuffle(rng);
 
         let mut players = vec![];
         let cards_per_player = NB_CARDS / n_players;
@@ -90,6 +155,155 @@ impl State {
         State::new(players)
     }
 
+    /// Builds a state from its raw parts, without any check.
+    pub(crate) fn from_parts(
+        players: Vec<Player>,
+        current_trick: Trick,
+        communications: Vec<Communication>,
+    ) -> Self {
+        State {
+            players,
+            current_trick,
+            communications,
+        }
+    }
+
+    /// Checks that the state may happen in a game: a valid number of players, hands whose sizes
+    /// differ by at most one card, complete won tricks, a current trick opened by a player and not
+    /// yet won, no card seen twice, tricks numbered in order and each opened by the winner of the
+    /// previous one, communications following the rules, valid tasks and a captain without any
+    /// task forbidden to the captain.
+    pub fn validate(&self) -> Result<(), GameError> {
+        let n_players = self.n_players();
+        if player::check_valid_n_players(n_players).is_err() {
+            return Err(GameError::InvalidNPlayers(n_players));
+        }
+        if self.current_trick.get_first_player() >= n_players {
+            return Err(GameError::InvalidFirstPlayer(
+                self.current_trick.get_first_player(),
+            ));
+        }
+        if self.current_trick.len() >= n_players {
+            return Err(GameError::CurrentTrickFull(self.current_trick.len()));
+        }
+
+        let mut seen = HashSet::new();
+        for p in &self.players {
+            for trick in p.get_tricks() {
+                if trick.len() != n_players {
+                    return Err(GameError::InvalidTrickSize(n_players, trick.len()));
+                }
+            }
+            let won = p.get_tricks().iter().flat_map(|t| t.iter());
+            for card in p.get_hand().iter().chain(won) {
+                if !seen.insert(*card) {
+                    return Err(GameError::DuplicateCard(*card));
+                }
+            }
+        }
+        for card in self.current_trick.iter() {
+            if !seen.insert(*card) {
+                return Err(GameError::DuplicateCard(*card));
+            }
+        }
+
+        // Won tricks are numbered from 0 and held by their winner, who opens the next trick
+        let mut tricks: Vec<(usize, &Trick)> = self
+            .players
+            .iter()
+            .enumerate()
+            .flat_map(|(i, p)| p.get_tricks().iter().map(move |t| (i, t)))
+            .collect();
+        tricks.sort_by_key(|(_, t)| t.idx());
+        let mut leader = None;
+        let all_tricks = tricks.iter().map(|(_, t)| *t).chain([&self.current_trick]);
+        for (i, trick) in all_tricks.enumerate() {
+            if trick.idx() < i {
+                return Err(GameError::DuplicateTrick(trick.idx()));
+            }
+            if trick.idx() > i {
+                return Err(GameError::MissingTrick(i));
+            }
+            let first = trick.get_first_player();
+            if first >= n_players || leader.is_some_and(|l| l != first) {
+                return Err(GameError::InvalidTrickLeader(i, first));
+            }
+            if let Some(&(holder, _)) = tricks.get(i) {
+                let winner = trick.winner();
+                if winner != holder {
+                    return Err(GameError::TrickNotHeldByWinner(i, winner));
+                }
+                leader = Some(winner);
+            }
+        }
+
+        // A communication reveals a card the player holds or has played, and tells the truth
+        let played_by = |seat: usize, card: &Card| {
+            tricks
+                .iter()
+                .map(|(_, t)| *t)
+                .chain([&self.current_trick])
+                .any(|t| {
+                    let pos = t.iter().position(|c| c == card);
+                    pos.is_some_and(|pos| (t.get_first_player() + pos) % n_players == seat)
+                })
+        };
+        let mut communicated = HashSet::new();
+        for c in &self.communications {
+            if c.seat >= n_players {
+                return Err(GameError::InvalidSeat(c.seat));
+            }
+            if !communicated.insert(c.seat) {
+                return Err(GameError::AlreadyCommunicated(c.seat));
+            }
+            if c.card.is_submarine() {
+                return Err(GameError::CannotCommunicateSubmarine(c.card));
+            }
+            let hand = self.players[c.seat].get_hand();
+            if !hand.contains(&c.card) && !played_by(c.seat, &c.card) {
+                return Err(GameError::CardNotFound(c.card, hand.to_vec()));
+            }
+            if !c.allows(hand) {
+                return Err(GameError::FalseCommunication(c.card, hand.to_vec()));
+            }
+        }
+
+        // Every player has played a card in each trick won, and maybe in the current one
+        let n_won: usize = self.players.iter().map(|p| p.get_tricks().len()).sum();
+        let first = self.current_trick.get_first_player();
+        let dealt = (0..n_players).map(|i| {
+            let in_current_trick = (i + n_players - first) % n_players < self.current_trick.len();
+            self.players[i].get_hand().len() + n_won + usize::from(in_current_trick)
+        });
+        let (min, max) = (dealt.clone().min().unwrap(), dealt.max().unwrap());
+        if max > min + 1 {
+            return Err(GameError::UnevenHands(min, max));
+        }
+
+        for task in self.players.iter().flat_map(|p| p.get_tasks()) {
+            task.validate().map_err(GameError::InvalidTask)?;
+        }
+
+        let captain = State::retrieve_captain(&self.players, Some(&self.current_trick))?;
+        if let Some(task) = self.players[captain]
+            .get_tasks()
+            .iter()
+            .find(|t| t.captain_forbidden())
+        {
+            return Err(GameError::CaptainForbiddenTask(task.clone()));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the cards must be dealt again, because of the redeal condition of one of
+    /// the tasks.
+    pub fn needs_redeal(&self) -> bool {
+        self.players
+            .iter()
+            .flat_map(|p| p.get_tasks())
+            .any(|t| t.needs_redeal(self))
+    }
+
     pub fn first_player(&self) -> usize {
         self.current_trick.get_first_player()
     }
@@ -127,6 +341,25 @@ impl State {
         &self.current_trick
     }
 
+    /// Card already played by player `ip` in the current trick, if any.
+    pub fn get_card_in_current_trick(&self, ip: usize) -> Option<&Card> {
+        let n_players = self.n_players();
+        let idx = (ip + n_players - self.current_trick.get_first_player()) % n_players;
+        self.current_trick.get(idx)
+    }
+
+    pub(crate) fn get_mut_current_trick(&mut self) -> &mut Trick {
+        &mut self.current_trick
+    }
+
+    pub fn get_communications(&self) -> &[Communication] {
+        &self.communications
+    }
+
+    pub(crate) fn get_mut_communications(&mut self) -> &mut Vec<Communication> {
+        &mut self.communications
+    }
+
     pub fn n_tricks_left(&self) -> usize {
         let cards_left: usize = self.players.iter().map(|p| p.get_hand().len()).sum();
         let cards_left_before_trick = cards_left + self.current_trick.len();
@@ -0,0 +1,15 @@
+//! Seat-level play policies.
+//!
+//! A [`Strategy`] decides which card a seat plays, only knowing what this seat can see
+//! (its [`PlayerView`]). This allows simulating hidden-information games, where the
+//! perfect-information solver of [`crate::solver`] cannot be used directly.
+use crate::{card::Card, view::PlayerView};
+
+pub trait Strategy {
+    /// Picks one of the `legal` cards (never empty) for the seat of `view`.
+    fn choose_card(&mut self, view: &PlayerView, legal: &[Card]) -> Card;
+}
+
+pub mod greedy;
+pub mod random;
+pub mod sampling;
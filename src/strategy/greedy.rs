@@ -0,0 +1,196 @@
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{
+    card::Card,
+    state::State,
+    strategy::Strategy,
+    task::{BaseTask, TaskProgress, TaskStatus},
+    view::PlayerView,
+};
+
+/// Points of a task making progress, a done task being worth twice as much.
+const SCALE: usize = 100;
+
+/// Looks at the end of the current trick: each legal card is played on a few sampled deals,
+/// the other seats completing the trick in the way best for the team, and the card leading
+/// to the best tasks is chosen. A card is rated first by the status of the mission, then by
+/// the progress of every task (see [`BaseTask::progress`]), so that needed cards are won and
+/// forbidden ones ducked. Ties are broken by playing the lowest card, keeping strong cards
+/// (and submarines) for later.
+#[derive(Debug, Clone)]
+pub struct GreedyStrategy {
+    rng: StdRng,
+    samples: usize,
+}
+
+impl GreedyStrategy {
+    pub fn new(seed: u64, samples: usize) -> Self {
+        assert!(samples > 0, "at least one sample is required");
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            samples,
+        }
+    }
+
+    fn progress_score(progress: TaskProgress) -> usize {
+        match (progress.status, progress.fraction) {
+            (TaskStatus::Failed, _) => 0,
+            (TaskStatus::Done, _) => 2 * SCALE,
+            (TaskStatus::Unknown, Some((achieved, required))) if required > 0 => {
+                SCALE + (SCALE - 1) * achieved.min(required) / required
+            }
+            (TaskStatus::Unknown, _) => SCALE,
+        }
+    }
+
+    /// Rates `state`: the status of the mission, then the progress of all tasks.
+    fn score(state: &State) -> (usize, usize) {
+        let mission = match state.game_status() {
+            TaskStatus::Failed => 0,
+            TaskStatus::Unknown => 1,
+            TaskStatus::Done => 2,
+        };
+        let progress = state
+            .get_players()
+            .iter()
+            .enumerate()
+            .flat_map(|(ip, p)| p.get_tasks().iter().map(move |t| t.progress(state, ip)))
+            .map(GreedyStrategy::progress_score)
+            .sum();
+        (mission, progress)
+    }
+
+    /// Best score reachable by completing the current trick.
+    fn best_completion(state: &State) -> (usize, usize) {
+        if state.get_current_trick().is_empty()
+            || state.n_tricks_left() == 0
+            || state.game_status() != TaskStatus::Unknown
+        {
+            return GreedyStrategy::score(state);
+        }
+        let hand = state.get_current_player().get_hand();
+        hand.playable_cards(state.get_current_trick().first())
+            .iter()
+            .map(|card| {
+                let mut next = state.clone();
+                next.play_card(card).expect("playable cards are in hand");
+                GreedyStrategy::best_completion(&next)
+            })
+            .max()
+            .unwrap_or_else(|| GreedyStrategy::score(state))
+    }
+}
+
+impl Strategy for GreedyStrategy {
+    fn choose_card(&mut self, view: &PlayerView, legal: &[Card]) -> Card {
+        let samples: Vec<State> = (0..self.samples)
+            .filter_map(|_| view.sample(&mut self.rng))
+            .collect();
+
+        let mut best: Option<((usize, usize), Card)> = None;
+        for &card in legal {
+            let score = samples
+                .iter()
+                .map(|s| {
+                    let mut s = s.clone();
+                    s.play_card(&card).expect("legal cards are in hand");
+                    GreedyStrategy::best_completion(&s)
+                })
+                .fold((0, 0), |acc, score| (acc.0 + score.0, acc.1 + score.1));
+            let better = match best {
+                None => true,
+                Some((best_score, best_card)) => {
+                    score > best_score
+                        || (score == best_score
+                            && (best_card.is_submarine(), best_card.val())
+                                > (card.is_submarine(), card.val()))
+                }
+            };
+            if better {
+                best = Some((score, card));
+            }
+        }
+
+        best.expect("no legal card to play").1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{player::Player, task::win_cards::TaskWinCards};
+
+    use super::*;
+
+    #[test]
+    fn wins_required_card() {
+        // Player 2 closes the trick and must win the blue 5: only the blue 7 does it.
+        let p0 = Player::new([Card::Submarine(4), Card::Blue(2)].into());
+        let p1 = Player::new([Card::Blue(5), Card::Pink(2)].into());
+        let mut p2 = Player::new([Card::Blue(7), Card::Blue(1)].into());
+        p2.add_task(TaskWinCards::new(None, [Card::Blue(5)]))
+            .unwrap();
+        let mut state = State::new([p0, p1, p2]);
+        state.play_card(&Card::Blue(2)).unwrap();
+        state.play_card(&Card::Blue(5)).unwrap();
+
+        let view = state.view(2);
+        let mut strategy = GreedyStrategy::new(0, 4);
+        assert_eq!(
+            strategy.choose_card(&view, &view.legal_cards()),
+            Card::Blue(7)
+        );
+    }
+
+    #[test]
+    fn leads_to_win_required_card() {
+        // Player 0 must win the green 5: leading the green 1 lets its holder win it.
+        let mut p0 = Player::new([Card::Submarine(4), Card::Green(9), Card::Green(1)].into());
+        p0.add_task(TaskWinCards::new(None, [Card::Green(5)]))
+            .unwrap();
+        let p1 = Player::new([Card::Blue(1), Card::Blue(2), Card::Blue(3)].into());
+        let p2 = Player::new([Card::Green(5), Card::Blue(4), Card::Blue(6)].into());
+        let state = State::new([p0, p1, p2]);
+
+        let view = state.view(0);
+        let mut strategy = GreedyStrategy::new(0, 4);
+        assert_eq!(
+            strategy.choose_card(&view, &view.legal_cards()),
+            Card::Green(9)
+        );
+    }
+
+    #[test]
+    fn makes_progress() {
+        // Whatever the lead, player 0 can still win both cards: leading the green 9 wins one.
+        let mut p0 = Player::new([Card::Submarine(4), Card::Green(9), Card::Green(1)].into());
+        p0.add_task(TaskWinCards::new(None, [Card::Green(5), Card::Blue(5)]))
+            .unwrap();
+        let p1 = Player::new([Card::Blue(1), Card::Blue(2), Card::Blue(3)].into());
+        let p2 = Player::new([Card::Green(5), Card::Green(3), Card::Blue(5)].into());
+        let state = State::new([p0, p1, p2]);
+
+        let view = state.view(0);
+        let mut strategy = GreedyStrategy::new(0, 4);
+        assert_eq!(
+            strategy.choose_card(&view, &view.legal_cards()),
+            Card::Green(9)
+        );
+    }
+
+    #[test]
+    fn keeps_strong_cards() {
+        let p0 = Player::new([Card::Submarine(4), Card::Blue(2)].into());
+        let p1 = Player::new([Card::Blue(5), Card::Pink(2)].into());
+        let p2 = Player::new([Card::Blue(7), Card::Blue(1)].into());
+        let mut state = State::new([p0, p1, p2]);
+        state.play_card(&Card::Blue(2)).unwrap();
+        state.play_card(&Card::Blue(5)).unwrap();
+
+        let view = state.view(2);
+        let mut strategy = GreedyStrategy::new(0, 4);
+        assert_eq!(
+            strategy.choose_card(&view, &view.legal_cards()),
+            Card::Blue(1)
+        );
+    }
+}
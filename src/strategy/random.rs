@@ -0,0 +1,44 @@
+use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+use crate::{card::Card, strategy::Strategy, view::PlayerView};
+
+/// Plays a uniformly random legal card.
+#[derive(Debug, Clone)]
+pub struct RandomStrategy {
+    rng: StdRng,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn choose_card(&mut self, _view: &PlayerView, legal: &[Card]) -> Card {
+        *legal.choose(&mut self.rng).expect("no legal card to play")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::state::State;
+
+    use super::*;
+
+    #[test]
+    fn plays_full_game() {
+        let mut state = State::new_random_with_rng(4, &mut StdRng::seed_from_u64(27));
+        let mut strategy = RandomStrategy::new(0);
+        for _ in 0..40 {
+            let view = state.view(state.get_current_player_idx());
+            let legal = view.legal_cards();
+            let card = strategy.choose_card(&view, &legal);
+            assert!(legal.contains(&card));
+            state.play_card(&card).unwrap();
+        }
+        assert!(state.game_is_over());
+    }
+}
@@ -0,0 +1,95 @@
+use rand::{SeedableRng, rngs::StdRng};
+
+use crate::{card::Card, state::State, strategy::Strategy, view::PlayerView};
+
+/// Determinized sampling: hidden hands are sampled several times, and each legal card is
+/// evaluated by running the perfect-information solver on every sample. The card which leads
+/// to a solution on the largest number of samples is chosen.
+#[derive(Debug, Clone)]
+pub struct SamplingStrategy {
+    rng: StdRng,
+    samples: usize,
+    budget: usize,
+}
+
+impl SamplingStrategy {
+    /// `budget` is the number of states the solver may explore per sample and card; a sample
+    /// for which the solver runs out of budget is counted as a failure.
+    pub fn new(seed: u64, samples: usize, budget: usize) -> Self {
+        assert!(samples > 0, "at least one sample is required");
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            samples,
+            budget,
+        }
+    }
+
+    /// Returns, for each legal card, the fraction of samples in which playing it still allows
+    /// the team to complete the mission.
+    pub fn evaluate(&mut self, view: &PlayerView, legal: &[Card]) -> Vec<(Card, f64)> {
+        let samples: Vec<State> = (0..self.samples)
+            .filter_map(|_| view.sample(&mut self.rng))
+            .collect();
+        if samples.is_empty() {
+            return legal.iter().map(|&c| (c, 0.)).collect();
+        }
+
+        legal
+            .iter()
+            .map(|&card| {
+                let successes = samples
+                    .iter()
+                    .filter(|s| {
+                        let mut s = (*s).clone();
+                        s.play_card(&card).expect("legal cards are in hand");
+                        s.play_with_budget(self.budget).is_ok()
+                    })
+                    .count();
+                (card, successes as f64 / samples.len() as f64)
+            })
+            .collect()
+    }
+}
+
+impl Strategy for SamplingStrategy {
+    fn choose_card(&mut self, view: &PlayerView, legal: &[Card]) -> Card {
+        let scores = self.evaluate(view, legal);
+        let mut best = scores[0];
+        for &(card, score) in &scores[1..] {
+            if score > best.1 {
+                best = (card, score);
+            }
+        }
+        best.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{player::Player, task::win_cards::TaskWinCards};
+
+    use super::*;
+
+    #[test]
+    fn wins_required_card() {
+        // Player 2 closes the trick and must win the pink 9: only the submarine does it.
+        let p0 = Player::new([Card::Submarine(4), Card::Pink(1)].into());
+        let p1 = Player::new([Card::Pink(9), Card::Blue(2)].into());
+        let mut p2 = Player::new([Card::Blue(1), Card::Submarine(1)].into());
+        p2.add_task(TaskWinCards::new(None, [Card::Pink(9)]))
+            .unwrap();
+        let mut state = State::new([p0, p1, p2]);
+        state.play_card(&Card::Pink(1)).unwrap();
+        state.play_card(&Card::Pink(9)).unwrap();
+
+        let view = state.view(2);
+        let mut strategy = SamplingStrategy::new(0, 8, 1000);
+        let scores = strategy.evaluate(&view, &view.legal_cards());
+        assert!(scores.contains(&(Card::Submarine(1), 1.)));
+        assert!(scores.contains(&(Card::Blue(1), 0.)));
+        assert_eq!(
+            strategy.choose_card(&view, &view.legal_cards()),
+            Card::Submarine(1)
+        );
+    }
+}
@@ -1,22 +1,75 @@
-use std::fmt::Debug;
+use std::{collections::HashSet, fmt::Debug};
 
 use enum_dispatch::enum_dispatch;
 
 use crate::{
-    card::{BLUE, GREEN, PINK, SUBMARINE, YELLOW, blue, green, pink, submarine, yellow},
+    card::{BLUE, Card, GREEN, PINK, SUBMARINE, YELLOW, blue, green, pink, submarine, yellow},
+    cnf::{CnfError, Encoder, Lit},
+    locale::Locale,
     state::State,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TaskStatus {
     Done,
     Unknown,
     Failed,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Where a player stands on a task: its status and, for tasks counting something, how much is
+/// already achieved out of what is required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaskProgress {
+    pub status: TaskStatus,
+    /// Amount achieved and amount required, e.g. `(2, 3)` when 2 of the 3 cards are won.
+    pub fraction: Option<(usize, usize)>,
+    /// Tricks still to be played, for tasks where they bound what can still be achieved.
+    pub tricks_left: Option<usize>,
+}
+
+impl From<TaskStatus> for TaskProgress {
+    fn from(status: TaskStatus) -> Self {
+        Self {
+            status,
+            fraction: None,
+            tricks_left: None,
+        }
+    }
+}
+
+impl TaskProgress {
+    pub fn with_fraction(self, achieved: usize, required: usize) -> Self {
+        Self {
+            fraction: Some((achieved, required)),
+            ..self
+        }
+    }
+
+    pub fn with_tricks_left(self, tricks_left: usize) -> Self {
+        Self {
+            tricks_left: Some(tricks_left),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaskDifficulty(usize, usize, usize);
 
+impl TaskDifficulty {
+    pub fn get(&self, n_players: usize) -> usize {
+        match n_players {
+            3 => self.0,
+            4 => self.1,
+            5 => self.2,
+            _ => panic!("invalid n_players"),
+        }
+    }
+}
+
 impl From<[usize; 3]> for TaskDifficulty {
     fn from(value: [usize; 3]) -> Self {
         TaskDifficulty(value[0], value[1], value[2])
@@ -30,19 +83,114 @@ pub trait BaseTask: Debug + Clone {
     fn difficulty(&self) -> Option<TaskDifficulty>;
 
     fn get_difficulty(&self, n_players: usize) -> Option<usize> {
-        assert!(3 <= n_players && n_players <= 5);
-        self.difficulty().map(|d| match n_players {
-            3 => d.0,
-            4 => d.1,
-            5 => d.2,
-            _ => panic!("invalid n_players"),
-        })
+        assert!((3..=5).contains(&n_players));
+        self.difficulty().map(|d| d.get(n_players))
+    }
+
+    /// Returns the status of the task along with how far player `ip` is from completing it.
+    /// Tasks which do not count anything only report their status.
+    fn progress(&self, state: &State, ip: usize) -> TaskProgress {
+        self.eval(state, ip).into()
+    }
+
+    /// Renders the text of the task card in the given language.
+    fn describe(&self, locale: Locale) -> String;
+
+    /// Writes the task in the [`crate::dsl`] language: parsing the text for the same number of
+    /// players gives back an equal task.
+    fn to_dsl(&self) -> String;
+
+    /// Returns a literal of `encoder` which is true if and only if player `ip` has done the
+    /// task once every trick is won, see [`crate::cnf`]. Only called while the status of the
+    /// task is unknown.
+    fn to_cnf(&self, _encoder: &mut Encoder, _ip: usize) -> Result<Lit, CnfError> {
+        Err(CnfError::UnsupportedTask(self.to_dsl()))
+    }
+
+    /// Returns true if the captain is not allowed to take this task.
+    fn captain_forbidden(&self) -> bool {
+        false
+    }
+
+    /// Returns true if cards must be dealt again before playing a mission with this task.
+    fn needs_redeal(&self, _state: &State) -> bool {
+        false
+    }
+
+    /// Checks the invariants ensured by the constructors of the task, which a deserialized task
+    /// may break.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Panics if the task breaks one of its invariants, see [`BaseTask::validate`].
+pub(crate) fn assert_valid<T: BaseTask>(task: T) -> T {
+    if let Err(e) = task.validate() {
+        panic!("{}", e);
+    }
+    task
+}
+
+/// Removes repeated cards, keeping the order in which they are first given - the order in which
+/// the task describes them.
+pub(crate) fn unique_cards<I>(cards: I) -> Vec<Card>
+where
+    I: IntoIterator<Item = Card>,
+{
+    let mut seen = HashSet::new();
+    cards.into_iter().filter(|c| seen.insert(*c)).collect()
+}
+
+/// Checks that no card is listed twice.
+pub(crate) fn check_unique_cards(cards: &[Card]) -> Result<(), String> {
+    match unique_cards(cards.iter().copied()).len() == cards.len() {
+        true => Ok(()),
+        false => Err("a card is listed more than once".to_string()),
     }
 }
 
-#[derive(Debug, Clone)]
+/// Player 0 has won the blue 1, player 1 the blue 2, and the blue 3 is still in a hand. Shared
+/// by the tests of the combinators.
+#[cfg(test)]
+pub(crate) fn combinator_state() -> State {
+    use crate::player::Player;
+
+    let mut p0 = Player::new(vec![Card::Submarine(4)].into());
+    p0.add_trick((0, 0, vec![Card::Blue(1), Card::Pink(1)]).into())
+        .unwrap();
+    let mut p1 = Player::new(vec![Card::Blue(3)].into());
+    p1.add_trick((1, 1, vec![Card::Blue(2), Card::Pink(2)]).into())
+        .unwrap();
+    State::new(vec![p0, p1])
+}
+
+/// Collects the sets of cards of a redeal condition, see [`someone_holds_all`].
+pub(crate) fn redeal_sets<I, J>(sets: I) -> Vec<Vec<Card>>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator<Item = Card>,
+{
+    sets.into_iter().map(|s| s.into_iter().collect()).collect()
+}
+
+/// Returns true if a single player holds all cards of one of the given sets - the usual
+/// redeal condition of tasks involving submarines.
+pub(crate) fn someone_holds_all(state: &State, sets: &[Vec<Card>]) -> bool {
+    state.get_players().iter().any(|p| {
+        sets.iter()
+            .any(|set| set.iter().all(|c| p.get_hand().contains(c)))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
 #[enum_dispatch(BaseTask)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Task {
+    AllOf(all_of::TaskAllOf),
+    AnyOf(any_of::TaskAnyOf),
+    AtLeast(at_least::TaskAtLeast),
+    Not(not::TaskNot),
     DontOpenTrickWith(dont_open_trick_with::TaskDontOpenTrickWith),
     DontWinCards(dont_win_cards::TaskDontWinCards),
     DontWinConsecutiveTricks(dont_win_consecutive_tricks::TaskDontWinConsecutiveTricks),
@@ -75,10 +223,14 @@ macro_rules! impl_difficulty {
 
 // Add your task module here.
 
+pub mod all_of;
+pub mod any_of;
+pub mod at_least;
 pub mod dont_open_trick_with;
 pub mod dont_win_cards;
 pub mod dont_win_consecutive_tricks;
 pub mod dont_win_tricks;
+pub mod not;
 pub mod win_all_cards_color;
 pub mod win_cards;
 pub mod win_cards_amount_color;
@@ -203,9 +355,10 @@ macro_rules! decl_win_consecutive_tricks {
 }
 
 macro_rules! decl_win_specific_submarine {
-    ($name:ident, $diff:expr, $v:expr) => {
+    ($name:ident, $diff:expr, $v:expr, $($redeal:expr),*) => {
         pub fn $name() -> win_specific_submarine::TaskWinSpecificSubmarine {
             win_specific_submarine::TaskWinSpecificSubmarine::new(Some($diff.into()), $v)
+                .with_redeal_if([$($redeal.to_vec()),*])
         }
     };
 }
@@ -249,13 +402,16 @@ decl_win_cards!(
 );
 decl_win_cards!(win_3_submarine, [1, 1, 1], submarine(3));
 
-decl_dont_win_cards_colors!(dont_win_pink, [2, 2, 2], PINK);
 use dont_win_cards::TaskDontWinCards;
+pub fn dont_win_pink() -> TaskDontWinCards {
+    TaskDontWinCards::new_from_colors(Some([2, 2, 2].into()), [PINK]).worded_win_no()
+}
 pub fn dont_win_submarine() -> TaskDontWinCards {
     TaskDontWinCards::new(
         Some([1, 1, 1].into()),
         [submarine(1), submarine(2), submarine(3), submarine(4)],
     )
+    .worded_win_no()
 }
 decl_dont_win_cards_colors!(dont_win_green, [2, 2, 2], GREEN);
 decl_dont_win_cards_colors!(dont_win_yellow, [2, 2, 2], YELLOW);
@@ -321,16 +477,16 @@ pub fn win_trick_total_value_higher_than(n_players: usize) -> TaskWinTrickWithPr
         5 => 31,
         _ => panic!("invalid n_players"),
     };
-    TaskWinTrickWithPred::new_greater(Some([3, 3, 4].into()), value)
+    TaskWinTrickWithPred::new_total_greater(Some([3, 3, 4].into()), value)
 }
 pub fn win_trick_total_value_lower_than(n_players: usize) -> TaskWinTrickWithPred {
     let value = match n_players {
-        3 => 23,
-        4 => 28,
-        5 => 31,
+        3 => 8,
+        4 => 12,
+        5 => 16,
         _ => panic!("invalid n_players"),
     };
-    TaskWinTrickWithPred::new_lower(Some([3, 3, 4].into()), value)
+    TaskWinTrickWithPred::new_total_lower(Some([3, 3, 4].into()), value)
 }
 pub fn win_trick_with_total_value_22_or_23() -> TaskWinTrickWithPred {
     TaskWinTrickWithPred::new_total_in(Some([3, 3, 4].into()), [22, 23])
@@ -348,7 +504,7 @@ pub fn win_green_9_with_submarine() -> TaskWinTrickWithPred {
     TaskWinTrickWithPred::new_win_card_with_submarine(Some([3, 3, 3].into()), green(9))
 }
 pub fn win_green_2_in_last_trick(n_players: usize) -> TaskWinTrickWithPred {
-    assert!(3 <= n_players && n_players <= 5);
+    assert!((3..=5).contains(&n_players));
     TaskWinTrickWithPred::new_win_card_last_trick(Some([3, 4, 5].into()), green(2), n_players)
 }
 
@@ -370,7 +526,13 @@ decl_win_tricks!(win_first_three_tricks, [2, 3, 4], [0, 1, 2], false, false);
 decl_win_nb_tricks!(win_exactly_one_trick, [3, 2, 2], 1);
 decl_win_nb_tricks!(win_exactly_two_tricks, [2, 2, 2], 2);
 decl_win_nb_tricks!(win_exactly_four_tricks, [2, 3, 5], 4);
-// TODO WIN X TRICKS
+use win_nb_tricks::TaskWinNbTricks;
+pub fn win_x_tricks() -> TaskWinNbTricks {
+    TaskWinNbTricks::new_prediction(Some([3, 2, 2].into()), false)
+}
+pub fn win_x_tricks_hidden() -> TaskWinNbTricks {
+    TaskWinNbTricks::new_prediction(Some([4, 3, 3].into()), true)
+}
 
 decl_win_cards_amount_color!(
     win_exactly_1_pink_1_green,
@@ -393,9 +555,30 @@ decl_win_cards_amount_color!(
     (BLUE, 1),
     (YELLOW, 1)
 );
-decl_win_cards_amount_color!(win_exactly_1_submarine, [3, 3, 3], true, (SUBMARINE, 1));
-decl_win_cards_amount_color!(win_exactly_2_submarines, [3, 3, 4], true, (SUBMARINE, 2));
-decl_win_cards_amount_color!(win_exactly_3_submarines, [3, 4, 4], true, (SUBMARINE, 3));
+use win_cards_amount_color::TaskWinCardsAmountColor;
+pub fn win_exactly_1_submarine() -> TaskWinCardsAmountColor {
+    TaskWinCardsAmountColor::new(Some([3, 3, 3].into()), true, [(SUBMARINE, 1)]).with_redeal_if([[
+        submarine(1),
+        submarine(2),
+        submarine(3),
+        submarine(4),
+    ]])
+}
+pub fn win_exactly_2_submarines() -> TaskWinCardsAmountColor {
+    TaskWinCardsAmountColor::new(Some([3, 3, 4].into()), true, [(SUBMARINE, 2)]).with_redeal_if([[
+        submarine(2),
+        submarine(3),
+        submarine(4),
+    ]])
+}
+pub fn win_exactly_3_submarines() -> TaskWinCardsAmountColor {
+    TaskWinCardsAmountColor::new(Some([3, 4, 4].into()), true, [(SUBMARINE, 3)]).with_redeal_if([[
+        submarine(1),
+        submarine(2),
+        submarine(3),
+        submarine(4),
+    ]])
+}
 
 decl_win_cards_amount_number!(win_at_least_three_5s, [3, 4, 5], false, (5, 3));
 decl_win_cards_amount_number!(win_at_least_three_9s, [3, 4, 5], false, (9, 3));
@@ -413,8 +596,20 @@ pub fn dont_win_consecutive_tricks() -> TaskDontWinConsecutiveTricks {
     TaskDontWinConsecutiveTricks::new(Some([3, 2, 2].into()))
 }
 
-decl_win_specific_submarine!(win_submarine_only_1, [3, 3, 3], 1);
-decl_win_specific_submarine!(win_submarine_only_2, [3, 3, 3], 2);
+decl_win_specific_submarine!(
+    win_submarine_only_1,
+    [3, 3, 3],
+    1,
+    [submarine(1), submarine(4)],
+    [submarine(1), submarine(2), submarine(3)]
+);
+decl_win_specific_submarine!(
+    win_submarine_only_2,
+    [3, 3, 3],
+    2,
+    [submarine(2), submarine(4)],
+    [submarine(1), submarine(2), submarine(3)]
+);
 
 use win_all_cards_color::TaskWinAllCardsColor;
 pub fn win_all_cards_color() -> TaskWinAllCardsColor {
@@ -439,3 +634,32 @@ decl_win_more_tricks!(
     false
 );
 decl_win_more_tricks!(win_fewer_tricks_than_everyone_else, [2, 2, 3], false, true);
+
+#[cfg(test)]
+mod test {
+    use crate::{card::Card, player::Player};
+
+    use super::*;
+
+    fn won_trick(cards: Vec<Card>) -> State {
+        let mut p0 = Player::new(vec![Card::Submarine(4)].into());
+        p0.add_trick((0, 0, cards).into()).unwrap();
+        State::new([p0, Player::new(vec![].into()), Player::new(vec![].into())])
+    }
+
+    #[test]
+    fn trick_total_value() {
+        let higher = win_trick_total_value_higher_than(3);
+        let lower = win_trick_total_value_lower_than(3);
+
+        // A total of 24 is higher than 23
+        let state = won_trick(vec![Card::Pink(9), Card::Pink(8), Card::Pink(7)]);
+        assert_eq!(higher.eval(&state, 0), TaskStatus::Done);
+        assert_ne!(lower.eval(&state, 0), TaskStatus::Done);
+
+        // A total of 6 is lower than 8
+        let state = won_trick(vec![Card::Pink(1), Card::Pink(2), Card::Pink(3)]);
+        assert_eq!(lower.eval(&state, 0), TaskStatus::Done);
+        assert_ne!(higher.eval(&state, 0), TaskStatus::Done);
+    }
+}
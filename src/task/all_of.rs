@@ -0,0 +1,114 @@
+use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
+    task::{
+        BaseTask, Task, TaskDifficulty, TaskProgress, assert_valid,
+        at_least::{describe_list, eval_at_least, to_cnf_at_least, validate_at_least},
+    },
+};
+
+/// Complete all the tasks.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskAllOf {
+    difficulty: Option<TaskDifficulty>,
+    tasks: Vec<Task>,
+}
+
+impl TaskAllOf {
+    pub fn new<I>(difficulty: Option<TaskDifficulty>, tasks: I) -> Self
+    where
+        I: IntoIterator<Item = Task>,
+    {
+        assert_valid(Self {
+            difficulty,
+            tasks: tasks.into_iter().collect(),
+        })
+    }
+}
+
+impl BaseTask for TaskAllOf {
+    fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
+        let n = self.tasks.len();
+        eval_at_least(n, self.tasks.iter().map(|t| t.eval(state, ip))).0
+    }
+
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let n = self.tasks.len();
+        let (status, n_done) = eval_at_least(n, self.tasks.iter().map(|t| t.eval(state, ip)));
+        TaskProgress::from(status).with_fraction(n_done, n)
+    }
+
+    fn captain_forbidden(&self) -> bool {
+        self.tasks.iter().any(|t| t.captain_forbidden())
+    }
+
+    fn needs_redeal(&self, state: &crate::state::State) -> bool {
+        self.tasks.iter().any(|t| t.needs_redeal(state))
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let header = match locale {
+            Locale::English => "All of the following",
+            Locale::French => "Toutes les tâches suivantes",
+        };
+        describe_list(header.to_string(), &self.tasks, locale)
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = format!("all of {}", dsl::render_group(&self.tasks));
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        to_cnf_at_least(self.tasks.len(), &self.tasks, encoder, ip)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validate_at_least(self.tasks.len(), &self.tasks)
+    }
+
+    impl_difficulty!();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        card::Card,
+        task::{TaskStatus, combinator_state as state, win_cards::TaskWinCards},
+    };
+
+    use super::*;
+
+    fn all_of(cards: &[Card]) -> TaskAllOf {
+        let tasks = cards.iter().map(|&c| TaskWinCards::new(None, [c]).into());
+        TaskAllOf::new(None, tasks)
+    }
+
+    #[test]
+    fn three_valued() {
+        let state = state();
+        assert_eq!(all_of(&[Card::Blue(1)]).eval(&state, 0), TaskStatus::Done);
+        assert_eq!(
+            all_of(&[Card::Blue(1), Card::Blue(3)]).eval(&state, 0),
+            TaskStatus::Unknown
+        );
+        assert_eq!(
+            all_of(&[Card::Blue(1), Card::Blue(3), Card::Blue(2)]).eval(&state, 0),
+            TaskStatus::Failed
+        );
+        assert_eq!(
+            all_of(&[Card::Blue(1), Card::Blue(3)])
+                .progress(&state, 0)
+                .fraction,
+            Some((1, 2))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty() {
+        all_of(&[]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
+    task::{
+        BaseTask, Task, TaskDifficulty, assert_valid,
+        at_least::{describe_list, eval_at_least, to_cnf_at_least, validate_at_least},
+    },
+};
+
+/// Complete at least one of the tasks: done as soon as one of them is done, failed only once all
+/// of them are failed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskAnyOf {
+    difficulty: Option<TaskDifficulty>,
+    tasks: Vec<Task>,
+}
+
+impl TaskAnyOf {
+    pub fn new<I>(difficulty: Option<TaskDifficulty>, tasks: I) -> Self
+    where
+        I: IntoIterator<Item = Task>,
+    {
+        assert_valid(Self {
+            difficulty,
+            tasks: tasks.into_iter().collect(),
+        })
+    }
+}
+
+impl BaseTask for TaskAnyOf {
+    fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
+        eval_at_least(1, self.tasks.iter().map(|t| t.eval(state, ip))).0
+    }
+
+    fn captain_forbidden(&self) -> bool {
+        self.tasks.iter().all(|t| t.captain_forbidden())
+    }
+
+    fn needs_redeal(&self, state: &crate::state::State) -> bool {
+        self.tasks.iter().any(|t| t.needs_redeal(state))
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let header = match locale {
+            Locale::English => "One of the following",
+            Locale::French => "Au moins une des tâches suivantes",
+        };
+        describe_list(header.to_string(), &self.tasks, locale)
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = format!("any of {}", dsl::render_group(&self.tasks));
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        to_cnf_at_least(1, &self.tasks, encoder, ip)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validate_at_least(1, &self.tasks)
+    }
+
+    impl_difficulty!();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        card::Card,
+        task::{TaskStatus, combinator_state as state, win_cards::TaskWinCards},
+    };
+
+    use super::*;
+
+    fn any_of(cards: &[Card]) -> TaskAnyOf {
+        let tasks = cards.iter().map(|&c| TaskWinCards::new(None, [c]).into());
+        TaskAnyOf::new(None, tasks)
+    }
+
+    #[test]
+    fn three_valued() {
+        let state = state();
+        assert_eq!(any_of(&[Card::Blue(2)]).eval(&state, 0), TaskStatus::Failed);
+        assert_eq!(
+            any_of(&[Card::Blue(2), Card::Blue(3)]).eval(&state, 0),
+            TaskStatus::Unknown
+        );
+        assert_eq!(
+            any_of(&[Card::Blue(2), Card::Blue(3), Card::Blue(1)]).eval(&state, 0),
+            TaskStatus::Done
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty() {
+        any_of(&[]);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, Task, TaskDifficulty, TaskProgress, TaskStatus, assert_valid},
+};
+
+/// Combines statuses with a three-valued "at least `k` of": done as soon as `k` of them are
+/// done, failed as soon as fewer than `k` of them can still be done.
+/// Also returns the number of statuses done.
+pub(crate) fn eval_at_least<I>(k: usize, statuses: I) -> (TaskStatus, usize)
+where
+    I: IntoIterator<Item = TaskStatus>,
+{
+    let mut n_done = 0;
+    let mut n_unknown = 0;
+    for status in statuses {
+        match status {
+            TaskStatus::Done => n_done += 1,
+            TaskStatus::Unknown => n_unknown += 1,
+            TaskStatus::Failed => (),
+        }
+    }
+    let status = if n_done >= k {
+        TaskStatus::Done
+    } else if n_done + n_unknown < k {
+        TaskStatus::Failed
+    } else {
+        TaskStatus::Unknown
+    };
+    (status, n_done)
+}
+
+/// Encodes the same rule as [`eval_at_least`]: at least `k` of the tasks must be done.
+pub(crate) fn to_cnf_at_least(
+    k: usize,
+    tasks: &[Task],
+    encoder: &mut Encoder,
+    ip: usize,
+) -> Result<Lit, CnfError> {
+    let done = tasks.iter().map(|t| encoder.task(t, ip));
+    let done = done.collect::<Result<Vec<Lit>, CnfError>>()?;
+    Ok(encoder.count(done).at_least(k))
+}
+
+/// Checks that `k` of the tasks can be completed, and that the tasks are valid themselves.
+pub(crate) fn validate_at_least(k: usize, tasks: &[Task]) -> Result<(), String> {
+    if tasks.is_empty() {
+        return Err("at least one task should be provided".to_string());
+    }
+    if !(1..=tasks.len()).contains(&k) {
+        return Err("k should be between 1 and the number of tasks".to_string());
+    }
+    tasks.iter().try_for_each(|t| t.validate())
+}
+
+/// Renders the header of a combinator followed by the texts of its tasks.
+pub(crate) fn describe_list(header: String, tasks: &[Task], locale: Locale) -> String {
+    let tasks: Vec<String> = tasks.iter().map(|t| t.describe(locale)).collect();
+    match locale {
+        Locale::English => format!("{}: {}", header, tasks.join("; ")),
+        Locale::French => format!("{} : {}", header, tasks.join(" ; ")),
+    }
+}
+
+/// Complete at least `k` of the tasks.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskAtLeast {
+    difficulty: Option<TaskDifficulty>,
+    k: usize,
+    tasks: Vec<Task>,
+}
+
+impl TaskAtLeast {
+    pub fn new<I>(difficulty: Option<TaskDifficulty>, k: usize, tasks: I) -> Self
+    where
+        I: IntoIterator<Item = Task>,
+    {
+        assert_valid(Self {
+            difficulty,
+            k,
+            tasks: tasks.into_iter().collect(),
+        })
+    }
+}
+
+impl BaseTask for TaskAtLeast {
+    fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
+        eval_at_least(self.k, self.tasks.iter().map(|t| t.eval(state, ip))).0
+    }
+
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let (status, n_done) = eval_at_least(self.k, self.tasks.iter().map(|t| t.eval(state, ip)));
+        TaskProgress::from(status).with_fraction(n_done.min(self.k), self.k)
+    }
+
+    /// The captain cannot take it if fewer than `k` tasks are allowed to them.
+    fn captain_forbidden(&self) -> bool {
+        self.tasks.iter().filter(|t| !t.captain_forbidden()).count() < self.k
+    }
+
+    fn needs_redeal(&self, state: &crate::state::State) -> bool {
+        self.tasks.iter().any(|t| t.needs_redeal(state))
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let header = match locale {
+            Locale::English => format!(
+                "At least {} of the following",
+                locale.number(self.k, Gender::Feminine)
+            ),
+            Locale::French => format!(
+                "Au moins {} des tâches suivantes",
+                locale.number(self.k, Gender::Feminine)
+            ),
+        };
+        describe_list(header, &self.tasks, locale)
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = format!("at least {} of {}", self.k, dsl::render_group(&self.tasks));
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        to_cnf_at_least(self.k, &self.tasks, encoder, ip)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validate_at_least(self.k, &self.tasks)
+    }
+
+    impl_difficulty!();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        card::Card,
+        task::{combinator_state as state, win_cards::TaskWinCards},
+    };
+
+    use super::*;
+
+    fn win(card: Card) -> Task {
+        TaskWinCards::new(None, [card]).into()
+    }
+
+    #[test]
+    fn three_valued() {
+        use TaskStatus::*;
+        assert_eq!(eval_at_least(2, [Done, Unknown, Done]).0, Done);
+        assert_eq!(eval_at_least(2, [Done, Unknown, Failed]).0, Unknown);
+        assert_eq!(eval_at_least(2, [Failed, Unknown, Failed]).0, Failed);
+        assert_eq!(eval_at_least(1, [Failed, Failed]).0, Failed);
+    }
+
+    #[test]
+    fn at_least_in_game() {
+        let state = state();
+        let tasks = [win(Card::Blue(1)), win(Card::Blue(2)), win(Card::Blue(3))];
+        let task = TaskAtLeast::new(None, 2, tasks.clone());
+        assert_eq!(task.eval(&state, 0), TaskStatus::Unknown);
+        assert_eq!(task.progress(&state, 0).fraction, Some((1, 2)));
+        assert_eq!(
+            TaskAtLeast::new(None, 1, tasks.clone()).eval(&state, 0),
+            TaskStatus::Done
+        );
+        assert_eq!(
+            TaskAtLeast::new(None, 3, tasks).eval(&state, 0),
+            TaskStatus::Failed
+        );
+    }
+}
@@ -1,14 +1,16 @@
-use std::collections::HashSet;
-
 use crate::{
     card::{COLOR_RANGE, Card},
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskStatus, check_unique_cards, unique_cards},
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskDontOpenTrickWith {
     difficulty: Option<TaskDifficulty>,
-    cards: HashSet<Card>,
+    cards: Vec<Card>,
 }
 
 impl TaskDontOpenTrickWith {
@@ -16,25 +18,32 @@ impl TaskDontOpenTrickWith {
     where
         I: IntoIterator<Item = fn(usize) -> Card>,
     {
-        let mut cards = HashSet::new();
-        for color in colors {
-            for i in COLOR_RANGE {
-                cards.insert(color(i));
-            }
+        // Submarines only go up to 4
+        let cards = colors
+            .into_iter()
+            .flat_map(|color| COLOR_RANGE.map(color).filter(Card::is_valid));
+        TaskDontOpenTrickWith {
+            difficulty,
+            cards: unique_cards(cards),
         }
-
-        TaskDontOpenTrickWith { difficulty, cards }
     }
 }
 
 impl BaseTask for TaskDontOpenTrickWith {
     fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
-        // Checking if current trick fails the task
-        if ip == state.first_player()
-            && let Some(first_card) = state.get_current_trick().get(0)
-            && self.cards.contains(first_card)
-        {
-            return TaskStatus::Failed;
+        // Checking if a trick opened by ip, finished or not, fails the task
+        let opened_by_ip = state
+            .get_players()
+            .iter()
+            .flat_map(|p| p.get_tricks())
+            .chain([state.get_current_trick()])
+            .filter(|t| t.get_first_player() == ip);
+        for trick in opened_by_ip {
+            if let Some(first_card) = trick.first()
+                && self.cards.contains(first_card)
+            {
+                return TaskStatus::Failed;
+            }
         }
 
         // Checking if any relevant card is left in the hand
@@ -52,5 +61,75 @@ impl BaseTask for TaskDontOpenTrickWith {
         }
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        let mut colors = self.cards.clone();
+        colors.dedup_by(|a, b| a.same_color(b));
+        let colors: Vec<String> = colors
+            .iter()
+            .map(|c| locale.color(c, Gender::Feminine, false))
+            .collect();
+        match locale {
+            Locale::English => format!("Don't open a trick with a {} card", locale.or(&colors)),
+            Locale::French => format!("N'ouvrez pas de pli avec une carte {}", locale.or(&colors)),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let mut colors = self.cards.clone();
+        colors.dedup_by(|a, b| a.same_color(b));
+        let colors: Vec<&str> = colors.iter().map(dsl::render_color).collect();
+        let text = format!("dont open with {}", colors.join(", "));
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        // Every relevant card in the hand must be played, but never to open a trick
+        let hand = encoder.state().get_player(ip).get_hand();
+        let mut lits = vec![];
+        for card in hand.iter().filter(|c| self.cards.contains(c)) {
+            lits.push(encoder.played(card));
+            let opens: Vec<(Lit, Lit)> = encoder
+                .tricks()
+                .iter()
+                .map(|t| (t.play(card), t.leaders[ip]))
+                .collect();
+            for (play, leads) in opens {
+                lits.push(encoder.or([!play, !leads]));
+            }
+        }
+        Ok(encoder.and(lits))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        check_unique_cards(&self.cards)
+    }
+
     impl_difficulty!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        card::{blue, green, pink, submarine, yellow},
+        player::Player,
+        state::State,
+    };
+
+    #[test]
+    fn failure_is_kept_after_the_trick() {
+        let mut state = State::new([
+            Player::new(vec![pink(1), submarine(4)].into()),
+            Player::new(vec![blue(2), green(3)].into()),
+            Player::new(vec![pink(3), yellow(3)].into()),
+        ]);
+        let task = TaskDontOpenTrickWith::new(None, [Card::Pink as fn(usize) -> Card]);
+        assert_eq!(task.eval(&state, 0), TaskStatus::Unknown);
+
+        state.play_card(&pink(1)).unwrap();
+        assert_eq!(task.eval(&state, 0), TaskStatus::Failed);
+        state.play_card(&blue(2)).unwrap();
+        state.play_card(&pink(3)).unwrap();
+        assert_eq!(task.eval(&state, 0), TaskStatus::Failed);
+    }
+}
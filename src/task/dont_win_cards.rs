@@ -1,14 +1,20 @@
 use std::collections::HashSet;
 
 use crate::{
-    card::{COLOR_RANGE, COLORS, Card},
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    card::{COLOR_RANGE, COLORS, Card, SUBMARINE_RANGE},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskStatus, check_unique_cards, unique_cards},
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskDontWinCards {
     difficulty: Option<TaskDifficulty>,
-    cards: HashSet<Card>,
+    cards: Vec<Card>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    win_no: bool,
 }
 
 impl TaskDontWinCards {
@@ -18,7 +24,8 @@ impl TaskDontWinCards {
     {
         TaskDontWinCards {
             difficulty,
-            cards: cards.into_iter().collect(),
+            cards: unique_cards(cards),
+            win_no: false,
         }
     }
 
@@ -26,28 +33,29 @@ impl TaskDontWinCards {
     where
         I: IntoIterator<Item = fn(usize) -> Card>,
     {
-        let mut cards = HashSet::new();
-        for color in colors {
-            for i in COLOR_RANGE {
-                cards.insert(color(i));
-            }
-        }
-
-        TaskDontWinCards { difficulty, cards }
+        // Submarines only go up to 4
+        let cards = colors
+            .into_iter()
+            .flat_map(|color| COLOR_RANGE.map(color).filter(Card::is_valid));
+        Self::new(difficulty, cards)
     }
 
     pub fn new_from_values<I>(difficulty: Option<TaskDifficulty>, values: I) -> Self
     where
         I: IntoIterator<Item = usize>,
     {
-        let mut cards = HashSet::new();
-        for i in values {
-            for color in COLORS {
-                cards.insert(color(i));
-            }
-        }
+        let cards = values
+            .into_iter()
+            .flat_map(|i| COLORS.iter().map(move |color| color(i)));
+        Self::new(difficulty, cards)
+    }
 
-        TaskDontWinCards { difficulty, cards }
+    /// Words the task as "Win no ..." rather than "Don't win any ...", as some cards do.
+    pub fn worded_win_no(self) -> Self {
+        Self {
+            win_no: true,
+            ..self
+        }
     }
 }
 
@@ -76,13 +84,114 @@ impl BaseTask for TaskDontWinCards {
             }
         }
 
-        if self.cards == found_cards.intersection(&self.cards).copied().collect() {
+        if self.cards.iter().all(|c| found_cards.contains(c)) {
             TaskStatus::Done
         } else {
             TaskStatus::Unknown
         }
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        // Whole colors are named first, then whole values, then the remaining single cards.
+        let mut cards = self.cards.clone();
+        let mut colors = Vec::new();
+        for card in &self.cards {
+            let color = COLORS.iter().find(|color| color(1).same_color(card));
+            if let Some(color) = color
+                && !colors.iter().any(|c: &Card| c.same_color(card))
+                && COLOR_RANGE.clone().all(|i| cards.contains(&color(i)))
+            {
+                colors.push(color(1));
+                cards.retain(|c| !c.same_color(card));
+            }
+        }
+        let submarines = SUBMARINE_RANGE
+            .clone()
+            .all(|i| cards.contains(&Card::Submarine(i)));
+        if submarines {
+            cards.retain(|c| !c.is_submarine());
+        }
+        let mut values = Vec::new();
+        for i in COLOR_RANGE {
+            if COLORS.iter().all(|color| cards.contains(&color(i))) {
+                values.push(i);
+                cards.retain(|c| c.is_submarine() || c.val() != i);
+            }
+        }
+
+        let colors: Vec<String> = colors
+            .iter()
+            .map(|c| locale.color(c, Gender::Feminine, false))
+            .collect();
+        let cards: Vec<String> = cards.iter().map(|c| locale.card(c)).collect();
+        let mut groups = Vec::new();
+        match locale {
+            Locale::English => {
+                if !colors.is_empty() {
+                    groups.push(format!("{} cards", locale.or(&colors)));
+                }
+                if submarines {
+                    groups.push("submarines".to_string());
+                }
+                groups.extend(values.iter().map(|v| format!("{}s", v)));
+                match (groups.is_empty(), cards.is_empty()) {
+                    (false, true) if self.win_no => format!("Win no {}", locale.or(&groups)),
+                    (false, true) => format!("Don't win any {}", locale.or(&groups)),
+                    (true, _) => format!("Don't win {}", locale.or(&cards)),
+                    (false, false) => format!(
+                        "Don't win any {} or {}",
+                        locale.or(&groups),
+                        locale.or(&cards)
+                    ),
+                }
+            }
+            Locale::French => {
+                if !colors.is_empty() {
+                    groups.push(format!("aucune carte {}", locale.or(&colors)));
+                }
+                if submarines {
+                    groups.push("aucun sous-marin".to_string());
+                }
+                if !values.is_empty() {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    groups.push(format!("aucun {}", locale.or(&values)));
+                }
+                if groups.is_empty() {
+                    format!("Ne remportez pas {}", locale.or(&cards))
+                } else {
+                    if !cards.is_empty() {
+                        groups.push(locale.or(&cards));
+                    }
+                    format!("Ne remportez {}", groups.join(" ni "))
+                }
+            }
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let keyword = if self.win_no { "win no" } else { "dont win" };
+        let text = format!("{} cards {}", keyword, dsl::render_cards(&self.cards));
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        // Every card must end up in the tricks of another player
+        let n_players = encoder.state().n_players();
+        let mut lits = vec![];
+        for card in &self.cards {
+            let won_by_others: Vec<Lit> = (0..n_players)
+                .filter(|&i| i != ip)
+                .map(|i| encoder.won(card, i))
+                .collect();
+            lits.push(encoder.or(won_by_others));
+        }
+        Ok(encoder.and(lits))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        check_unique_cards(&self.cards)
+    }
+
     impl_difficulty!();
 }
 
@@ -97,7 +206,7 @@ mod test {
         let task = TaskDontWinCards::new_from_colors(None, [Card::Blue as fn(usize) -> Card]);
         let p1 = Player::new([Card::Submarine(4)].into());
         let mut p2 = Player::new([Card::Green(4)].into());
-        p2.add_task(task.clone());
+        p2.add_task(task.clone()).unwrap();
         let mut p3 = Player::new([Card::Pink(8)].into());
         p3.add_trick((0, 0, [Card::Blue(7), Card::Blue(8), Card::Blue(9)]).into())
             .unwrap();
@@ -117,7 +226,7 @@ mod test {
         let mut p2 = Player::new([].into());
         p2.add_trick((0, 1, [Card::Submarine(4), Card::Pink(5)]).into())
             .unwrap();
-        p2.add_task(task.clone());
+        p2.add_task(task.clone()).unwrap();
         let state = State::new([p1, p2]);
 
         assert_eq!(task.eval(&state, 1), TaskStatus::Failed);
@@ -128,7 +237,7 @@ mod test {
         let task =
             TaskDontWinCards::new_from_colors(None, [Card::Green as fn(usize) -> Card, Card::Pink]);
         let mut p1 = Player::new([Card::Pink(2)].into());
-        p1.add_task(task.clone());
+        p1.add_task(task.clone()).unwrap();
         let mut p2 = Player::new([Card::Yellow(6)].into());
         p2.add_trick((0, 1, [Card::Submarine(4), Card::Green(5)]).into())
             .unwrap();
@@ -1,6 +1,12 @@
-use crate::task::{BaseTask, TaskDifficulty, TaskStatus};
+use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
+    task::{BaseTask, TaskDifficulty, TaskStatus},
+};
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskDontWinConsecutiveTricks {
     difficulty: Option<TaskDifficulty>,
 }
@@ -30,5 +36,27 @@ impl BaseTask for TaskDontWinConsecutiveTricks {
         TaskStatus::Unknown
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        match locale {
+            Locale::English => "Don't win two consecutive tricks".to_string(),
+            Locale::French => "Ne remportez jamais deux plis consécutifs".to_string(),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        dsl::render_difficulty("dont win consecutive tricks".to_string(), self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        if !encoder.game_is_over() {
+            return Ok(Lit::Const(false));
+        }
+        let won: Vec<Lit> = (0..encoder.final_trick_idx())
+            .map(|i| encoder.trick_won(i, ip))
+            .collect();
+        let lits: Vec<Lit> = won.windows(2).map(|w| encoder.or([!w[0], !w[1]])).collect();
+        Ok(encoder.and(lits))
+    }
+
     impl_difficulty!();
 }
@@ -1,11 +1,17 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
-use crate::task::{BaseTask, TaskDifficulty, TaskStatus};
+use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskStatus, assert_valid},
+};
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskDontWinTricks {
     difficulty: Option<TaskDifficulty>,
-    indexes: HashSet<usize>,
+    indexes: BTreeSet<usize>,
     any: bool,
 }
 
@@ -14,16 +20,11 @@ impl TaskDontWinTricks {
     where
         I: IntoIterator<Item = usize>,
     {
-        let indexes: HashSet<usize> = indexes.into_iter().collect();
-        assert!(
-            indexes.len() > 0 || any,
-            "at least one forbidden index should be provided"
-        );
-        Self {
+        assert_valid(Self {
             difficulty,
-            indexes,
+            indexes: indexes.into_iter().collect(),
             any,
-        }
+        })
     }
 
     pub fn new_n_first_tricks(difficulty: Option<TaskDifficulty>, n: usize) -> Self {
@@ -61,5 +62,56 @@ impl BaseTask for TaskDontWinTricks {
         TaskStatus::Unknown
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        let n = self.indexes.len();
+        match (locale, self.any, n) {
+            (Locale::English, true, _) => "Don't win any tricks".to_string(),
+            (Locale::English, false, 1) => "Don't win the first trick".to_string(),
+            (Locale::English, false, _) => format!(
+                "Don't win any of the first {} tricks",
+                locale.number(n, Gender::Masculine)
+            ),
+            (Locale::French, true, _) => "Ne remportez aucun pli".to_string(),
+            (Locale::French, false, 1) => "Ne remportez pas le premier pli".to_string(),
+            (Locale::French, false, _) => format!(
+                "Ne remportez aucun des {} premiers plis",
+                locale.number(n, Gender::Masculine)
+            ),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        // Only built by `new_any` and `new_n_first_tricks`
+        let text = if self.any {
+            "dont win tricks".to_string()
+        } else {
+            format!("dont win first {} tricks", self.indexes.len())
+        };
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let final_idx = encoder.final_trick_idx();
+        let over = match self.indexes.iter().max() {
+            Some(m) => final_idx > *m || encoder.game_is_over(),
+            None => encoder.game_is_over(),
+        };
+        if !over {
+            return Ok(Lit::Const(false));
+        }
+        let lits: Vec<Lit> = (0..final_idx)
+            .filter(|i| self.any || self.indexes.contains(i))
+            .map(|i| !encoder.trick_won(i, ip))
+            .collect();
+        Ok(encoder.and(lits))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.indexes.is_empty() && !self.any {
+            return Err("at least one forbidden index should be provided".to_string());
+        }
+        Ok(())
+    }
+
     impl_difficulty!();
 }
@@ -0,0 +1,86 @@
+use crate::{
+    dsl,
+    locale::Locale,
+    task::{BaseTask, Task, TaskDifficulty, TaskStatus},
+};
+
+/// Make sure the task is not completed: done when it fails, failed when it is done.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskNot {
+    difficulty: Option<TaskDifficulty>,
+    task: Box<Task>,
+}
+
+impl TaskNot {
+    pub fn new<T>(difficulty: Option<TaskDifficulty>, task: T) -> Self
+    where
+        T: Into<Task>,
+    {
+        Self {
+            difficulty,
+            task: Box::new(task.into()),
+        }
+    }
+}
+
+impl BaseTask for TaskNot {
+    fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
+        match self.task.eval(state, ip) {
+            TaskStatus::Done => TaskStatus::Failed,
+            TaskStatus::Unknown => TaskStatus::Unknown,
+            TaskStatus::Failed => TaskStatus::Done,
+        }
+    }
+
+    fn captain_forbidden(&self) -> bool {
+        self.task.captain_forbidden()
+    }
+
+    fn needs_redeal(&self, state: &crate::state::State) -> bool {
+        self.task.needs_redeal(state)
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        match locale {
+            Locale::English => format!("Do not complete: {}", self.task.describe(locale)),
+            Locale::French => format!("Ne réussissez pas : {}", self.task.describe(locale)),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = format!("not ({})", self.task.to_dsl());
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.task.validate()
+    }
+
+    impl_difficulty!();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{card::Card, player::Player, state::State, task::win_cards::TaskWinCards};
+
+    use super::*;
+
+    #[test]
+    fn negation() {
+        let mut p0 = Player::new(vec![Card::Submarine(4)].into());
+        p0.add_trick((0, 0, vec![Card::Blue(1), Card::Pink(1)]).into())
+            .unwrap();
+        let p1 = Player::new(vec![Card::Blue(3)].into());
+        let state = State::new(vec![p0, p1]);
+
+        let not = |card| TaskNot::new(None, TaskWinCards::new(None, [card]));
+        assert_eq!(not(Card::Blue(1)).eval(&state, 0), TaskStatus::Failed);
+        assert_eq!(not(Card::Blue(1)).eval(&state, 1), TaskStatus::Done);
+        assert_eq!(not(Card::Blue(3)).eval(&state, 0), TaskStatus::Unknown);
+        assert_eq!(
+            not(Card::Blue(3)).describe(Locale::English),
+            "Do not complete: Win the blue 3"
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use crate::{
-    card::{COLOR_RANGE, Card},
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    card::{COLOR_RANGE, COLORS, Card},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
+    task::{BaseTask, TaskDifficulty, TaskProgress, TaskStatus},
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinAllCardsColor {
     difficulty: Option<TaskDifficulty>,
 }
@@ -66,5 +70,46 @@ impl BaseTask for TaskWinAllCardsColor {
         TaskStatus::Unknown
     }
 
+    /// The fraction is the number of cards won in the color the player has won the most of.
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let tricks = state.get_player(ip).get_tricks();
+        let best = COLORS
+            .iter()
+            .map(|color| {
+                tricks
+                    .iter()
+                    .flat_map(|t| t.iter())
+                    .filter(|c| c.same_color(&color(1)))
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        TaskProgress::from(self.eval(state, ip)).with_fraction(best, COLOR_RANGE.len())
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        match locale {
+            Locale::English => {
+                "Win all cards of at least one color (excluding submarines)".to_string()
+            }
+            Locale::French => {
+                "Remportez toutes les cartes d'au moins une couleur (hors sous-marins)".to_string()
+            }
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        dsl::render_difficulty("win all of a color".to_string(), self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let mut colors = vec![];
+        for color in COLORS {
+            let won: Vec<Lit> = COLOR_RANGE.map(|i| encoder.won(&color(i), ip)).collect();
+            colors.push(encoder.and(won));
+        }
+        Ok(encoder.or(colors))
+    }
+
     impl_difficulty!();
 }
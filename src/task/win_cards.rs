@@ -6,19 +6,23 @@ use std::collections::HashSet;
 
 use crate::{
     card::Card,
-    task::{BaseTask, TaskDifficulty},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskProgress, check_unique_cards, unique_cards},
 };
 
 /// First, we define a **public** struct, deriving [`Debug`].
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinCards {
     /// All fields should be private (ie without a `pub` specifier).
     /// They are used to store the internal logic of the task.
     ///
-    /// Here, we just store the cards that must be won by the player
-    /// (and the difficulty field which should be present on all tasks).
+    /// Here, we just store the cards that must be won by the player, in the order the card
+    /// names them (and the difficulty field which should be present on all tasks).
     difficulty: Option<TaskDifficulty>,
-    cards: HashSet<Card>,
+    cards: Vec<Card>,
 }
 
 impl TaskWinCards {
@@ -31,7 +35,7 @@ impl TaskWinCards {
     {
         TaskWinCards {
             difficulty,
-            cards: cards.into_iter().collect(),
+            cards: unique_cards(cards),
         }
     }
 
@@ -52,7 +56,7 @@ impl BaseTask for TaskWinCards {
             }
         }
 
-        if self.cards == found_cards {
+        if found_cards.len() == self.cards.len() {
             return super::TaskStatus::Done;
         }
 
@@ -76,6 +80,83 @@ impl BaseTask for TaskWinCards {
         super::TaskStatus::Unknown
     }
 
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let won = state
+            .get_player(ip)
+            .get_tricks()
+            .iter()
+            .flat_map(|t| t.iter())
+            .filter(|c| self.cards.contains(c))
+            .count();
+        TaskProgress::from(self.eval(state, ip)).with_fraction(won, self.cards.len())
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let cards = &self.cards;
+        if cards.len() == 4
+            && cards
+                .iter()
+                .all(|c| !c.is_submarine() && c.val() == cards[0].val())
+        {
+            return match locale {
+                Locale::English => format!("Win all four {}s", cards[0].val()),
+                Locale::French => format!("Remportez les quatre {}", cards[0].val()),
+            };
+        }
+
+        // Cards of the same color are named together, in the order the colors first appear.
+        let mut groups: Vec<Vec<Card>> = Vec::new();
+        for card in cards {
+            match groups.iter_mut().find(|g| g[0].same_color(card)) {
+                Some(group) => group.push(*card),
+                None => groups.push(vec![*card]),
+            }
+        }
+        let groups: Vec<String> = groups
+            .into_iter()
+            .map(|g| {
+                let values: Vec<String> = g.iter().map(|c| c.val().to_string()).collect();
+                let values = locale.and(&values);
+                let plural = g.len() > 1;
+                match (locale, g[0].is_submarine()) {
+                    (Locale::English, true) if plural => format!("the {} submarines", values),
+                    (Locale::English, true) => format!("the {} submarine", values),
+                    (Locale::English, false) => format!(
+                        "the {} {}",
+                        locale.color(&g[0], Gender::Masculine, false),
+                        values
+                    ),
+                    (Locale::French, true) if plural => format!("les sous-marins {}", values),
+                    (Locale::French, true) => format!("le sous-marin {}", values),
+                    (Locale::French, false) => format!(
+                        "{} {} {}",
+                        if plural { "les" } else { "le" },
+                        values,
+                        locale.color(&g[0], Gender::Masculine, plural)
+                    ),
+                }
+            })
+            .collect();
+        match locale {
+            Locale::English => format!("Win {}", locale.and(&groups)),
+            Locale::French => format!("Remportez {}", locale.and(&groups)),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = format!("win cards {}", dsl::render_cards(&self.cards));
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let won: Vec<Lit> = self.cards.iter().map(|c| encoder.won(c, ip)).collect();
+        Ok(encoder.and(won))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        check_unique_cards(&self.cards)
+    }
+
     impl_difficulty!();
 }
 
@@ -91,7 +172,7 @@ mod test {
     fn done_2_cards_1_trick() {
         let task = TaskWinCards::new(None, [Card::Blue(1), Card::Blue(2)]);
         let mut p1 = Player::new(vec![Card::Submarine(4)].into());
-        p1.add_task(task.clone());
+        p1.add_task(task.clone()).unwrap();
         p1.add_trick((0, 0, vec![Card::Blue(1), Card::Blue(2)]).into())
             .unwrap();
 
@@ -101,11 +182,25 @@ mod test {
         assert_eq!(task.eval(&state, 0), TaskStatus::Done);
     }
 
+    #[test]
+    fn progress_2_of_3_cards() {
+        let task = TaskWinCards::new(None, [Card::Blue(1), Card::Blue(2), Card::Pink(3)]);
+        let mut p1 = Player::new(vec![Card::Submarine(4)].into());
+        p1.add_trick((0, 0, vec![Card::Blue(1), Card::Blue(2)]).into())
+            .unwrap();
+        let p2 = Player::new(vec![Card::Pink(3)].into());
+        let state = State::new(vec![p1, p2]);
+
+        let progress = task.progress(&state, 0);
+        assert_eq!(progress.status, TaskStatus::Unknown);
+        assert_eq!(progress.fraction, Some((2, 3)));
+    }
+
     #[test]
     fn done_2_cards_2_tricks() {
         let task = TaskWinCards::new(None, [Card::Blue(1), Card::Blue(2)]);
         let mut p1 = Player::new(vec![].into());
-        p1.add_task(task.clone());
+        p1.add_task(task.clone()).unwrap();
         p1.add_trick((0, 0, vec![Card::Submarine(4), Card::Blue(1)]).into())
             .unwrap();
         p1.add_trick((1, 0, vec![Card::Blue(2), Card::Pink(8)]).into())
@@ -121,7 +216,7 @@ mod test {
     fn failed_2_cards_2_tricks() {
         let task = TaskWinCards::new(None, [Card::Blue(1), Card::Blue(2)]);
         let mut p1 = Player::new(vec![].into());
-        p1.add_task(task.clone());
+        p1.add_task(task.clone()).unwrap();
         p1.add_trick((0, 0, vec![Card::Submarine(4), Card::Blue(1)]).into())
             .unwrap();
 
@@ -137,7 +232,7 @@ mod test {
     fn unknown_2_cards_1_trick() {
         let task = TaskWinCards::new(None, [Card::Blue(1), Card::Blue(2)]);
         let mut p1 = Player::new(vec![].into());
-        p1.add_task(task.clone());
+        p1.add_task(task.clone()).unwrap();
         p1.add_trick((0, 0, vec![Card::Submarine(4), Card::Blue(1)]).into())
             .unwrap();
 
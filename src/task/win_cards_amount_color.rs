@@ -1,16 +1,21 @@
-use std::collections::HashMap;
-
 use crate::{
-    card::{COLOR_RANGE, Card},
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    card::{Card, deck},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{self, Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskProgress, TaskStatus, redeal_sets},
     trick::Trick,
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinCardsAmountColor {
     difficulty: Option<TaskDifficulty>,
-    constraints: HashMap<fn(usize) -> Card, usize>,
+    /// Colors are stored as their card of value 1, so that the task can be compared, in the
+    /// usual order of colors.
+    constraints: Vec<(Card, usize)>,
     exactly: bool,
+    redeal_if: Vec<Vec<Card>>,
 }
 
 impl TaskWinCardsAmountColor {
@@ -18,25 +23,48 @@ impl TaskWinCardsAmountColor {
     where
         I: IntoIterator<Item = (fn(usize) -> Card, usize)>,
     {
+        // A color given twice keeps its last amount
+        let mut amounts: Vec<(Card, usize)> = Vec::new();
+        for (color, n) in constraints {
+            amounts.retain(|(c, _)| *c != color(1));
+            amounts.push((color(1), n));
+        }
+        amounts.sort_by_key(|(c, _)| locale::color_order(c));
         Self {
             difficulty,
             exactly,
-            constraints: constraints.into_iter().collect(),
+            constraints: amounts,
+            redeal_if: Vec::new(),
         }
     }
+
+    /// Deals again when a single player holds all cards of one of `sets`.
+    pub fn with_redeal_if<I, J>(mut self, sets: I) -> Self
+    where
+        I: IntoIterator<Item = J>,
+        J: IntoIterator<Item = Card>,
+    {
+        self.redeal_if = redeal_sets(sets);
+        self
+    }
 }
 
-fn count_won(tricks: &[Trick], color: fn(usize) -> Card) -> usize {
+/// Number of cards of the color in the deck (there are only 4 submarines).
+fn n_cards(color: &Card) -> usize {
+    deck().iter().filter(|c| c.same_color(color)).count()
+}
+
+fn count_won(tricks: &[Trick], color: &Card) -> usize {
     tricks.iter().fold(0, |acc, trick| {
-        acc + trick.iter().filter(|c| c.same_color(&color(1))).count()
+        acc + trick.iter().filter(|c| c.same_color(color)).count()
     })
 }
 
 impl BaseTask for TaskWinCardsAmountColor {
     fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
         let mut done = true;
-        for (&color, &v) in &self.constraints {
-            let won_by_ip = count_won(state.get_player(ip).get_tricks(), color);
+        for (color, v) in self.constraints.iter().copied() {
+            let won_by_ip = count_won(state.get_player(ip).get_tricks(), &color);
 
             if self.exactly && won_by_ip > v {
                 return TaskStatus::Failed;
@@ -50,10 +78,10 @@ impl BaseTask for TaskWinCardsAmountColor {
             let mut won_by_others = 0;
             for i in 0..state.n_players() {
                 if i != ip {
-                    won_by_others += count_won(state.get_player(i).get_tricks(), color);
+                    won_by_others += count_won(state.get_player(i).get_tricks(), &color);
                 }
             }
-            let available = COLOR_RANGE.len() - (won_by_ip + won_by_others);
+            let available = n_cards(&color) - (won_by_ip + won_by_others);
             if available < missing {
                 return TaskStatus::Failed;
             }
@@ -67,5 +95,199 @@ impl BaseTask for TaskWinCardsAmountColor {
         TaskStatus::Unknown
     }
 
+    fn needs_redeal(&self, state: &crate::state::State) -> bool {
+        super::someone_holds_all(state, &self.redeal_if)
+    }
+
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let tricks = state.get_player(ip).get_tricks();
+        let achieved = self
+            .constraints
+            .iter()
+            .map(|(color, v)| count_won(tricks, color).min(*v))
+            .sum();
+        let required = self.constraints.iter().map(|(_, v)| v).sum();
+        TaskProgress::from(self.eval(state, ip)).with_fraction(achieved, required)
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let constraints = &self.constraints;
+        let each_color = !self.exactly
+            && constraints.len() == 4
+            && constraints
+                .iter()
+                .all(|(c, n)| !c.is_submarine() && *n == 1);
+        if each_color {
+            return match locale {
+                Locale::English => {
+                    "Win at least one card of each color (excluding submarines)".to_string()
+                }
+                Locale::French => {
+                    "Remportez au moins une carte de chaque couleur (hors sous-marins)".to_string()
+                }
+            };
+        }
+
+        let text = match locale {
+            Locale::English => {
+                let amount = if self.exactly { "exactly" } else { "at least" };
+                let plural = |n: usize| if n == 1 { "" } else { "s" };
+                let items: Vec<String>;
+                if constraints.iter().all(|(c, _)| !c.is_submarine()) {
+                    // "one pink and one green card"
+                    items = constraints
+                        .iter()
+                        .map(|(c, n)| {
+                            format!(
+                                "{} {}",
+                                locale.number(*n, Gender::Feminine),
+                                locale.color(c, Gender::Feminine, false)
+                            )
+                        })
+                        .collect();
+                    let last = constraints.last().map_or(0, |(_, n)| *n);
+                    format!("Win {} {} card{}", amount, locale.and(&items), plural(last))
+                } else {
+                    items = constraints
+                        .iter()
+                        .map(|(c, n)| {
+                            let number = locale.number(*n, Gender::Feminine);
+                            if c.is_submarine() {
+                                format!("{} submarine{}", number, plural(*n))
+                            } else {
+                                let color = locale.color(c, Gender::Feminine, false);
+                                format!("{} {} card{}", number, color, plural(*n))
+                            }
+                        })
+                        .collect();
+                    format!("Win {} {}", amount, locale.and(&items))
+                }
+            }
+            Locale::French => {
+                let amount = if self.exactly {
+                    "exactement"
+                } else {
+                    "au moins"
+                };
+                let plural = |n: usize| if n == 1 { "" } else { "s" };
+                let items: Vec<String> = constraints
+                    .iter()
+                    .map(|(c, n)| {
+                        if c.is_submarine() {
+                            let number = locale.number(*n, Gender::Masculine);
+                            format!("{} sous-marin{}", number, plural(*n))
+                        } else {
+                            let number = locale.number(*n, Gender::Feminine);
+                            let color = locale.color(c, Gender::Feminine, *n > 1);
+                            format!("{} carte{} {}", number, plural(*n), color)
+                        }
+                    })
+                    .collect();
+                format!("Remportez {} {}", amount, locale.and(&items))
+            }
+        };
+        text + &locale.redeal(&self.redeal_if)
+    }
+
+    fn to_dsl(&self) -> String {
+        let amounts: Vec<String> = self
+            .constraints
+            .iter()
+            .map(|(c, n)| format!("{} {}", n, dsl::render_color(c)))
+            .collect();
+        let amount = if self.exactly { "exactly" } else { "at least" };
+        let text = format!(
+            "win {} {}{}",
+            amount,
+            amounts.join(", "),
+            dsl::render_redeal(&self.redeal_if)
+        );
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let mut lits = vec![];
+        for (color, v) in self.constraints.iter().copied() {
+            let cards: Vec<Card> = deck()
+                .into_iter()
+                .filter(|c| c.same_color(&color))
+                .collect();
+            let won: Vec<Lit> = cards.iter().map(|c| encoder.won(c, ip)).collect();
+            let won = encoder.count(won);
+            if self.exactly {
+                // As in `eval`, the task is only done once no card of the color is available
+                let played: Vec<Lit> = cards.iter().map(|c| encoder.played(c)).collect();
+                let played = encoder.count(played);
+                lits.push(encoder.exactly(&won, v));
+                lits.push(played.at_least(cards.len()));
+            } else {
+                lits.push(won.at_least(v));
+            }
+        }
+        Ok(encoder.and(lits))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let colors = self.constraints.iter().map(|(c, _)| locale::color_order(c));
+        let colors: Vec<usize> = colors.collect();
+        match colors.windows(2).all(|w| w[0] < w[1]) {
+            true => Ok(()),
+            false => Err("colors should be listed once, in the usual order".to_string()),
+        }
+    }
+
     impl_difficulty!();
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        card::{
+            BLUE,
+            Card::{Blue, Pink, Submarine},
+            PINK,
+        },
+        player::Player,
+        state::State,
+    };
+
+    use super::*;
+
+    #[test]
+    fn exactly_one_submarine() {
+        let task = TaskWinCardsAmountColor::new(None, true, [(Submarine as fn(usize) -> Card, 1)]);
+        let mut state = State::new([
+            Player::new([Submarine(4), Pink(1)].into()),
+            Player::new([Submarine(1), Pink(2)].into()),
+            Player::new([Submarine(2), Submarine(3)].into()),
+        ]);
+        state.get_mut_player(2).add_task(task.clone()).unwrap();
+        assert!(state.clone().play().is_ok());
+
+        for card in [Submarine(4), Submarine(1), Submarine(2), Pink(1), Pink(2)] {
+            state.play_card(&card).unwrap();
+        }
+        assert_eq!(task.eval(&state, 2), TaskStatus::Unknown);
+        state.play_card(&Submarine(3)).unwrap();
+        assert_eq!(task.eval(&state, 2), TaskStatus::Done);
+    }
+
+    #[test]
+    fn progress() {
+        let mut state = State::new([
+            Player::new([Submarine(4), Pink(9), Pink(1), Pink(2)].into()),
+            Player::new([Pink(3), Pink(4), Pink(7), Blue(1)].into()),
+            Player::new([Pink(5), Pink(6), Pink(8), Blue(2)].into()),
+        ]);
+        let task = TaskWinCardsAmountColor::new(None, false, [(PINK, 4), (BLUE, 1)]);
+        assert_eq!(task.progress(&state, 0).fraction, Some((0, 5)));
+        for card in [Submarine(4), Blue(1), Blue(2), Pink(9), Pink(3), Pink(5)] {
+            state.play_card(&card).unwrap();
+        }
+
+        // The second blue card won does not count
+        let progress = task.progress(&state, 0);
+        assert_eq!(progress.status, TaskStatus::Unknown);
+        assert_eq!(progress.fraction, Some((4, 5)));
+    }
+}
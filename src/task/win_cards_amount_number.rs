@@ -1,14 +1,19 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::{
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    card::{COLOR_RANGE, COLORS},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskProgress, TaskStatus, assert_valid},
     trick::Trick,
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinCardsAmountNumber {
     difficulty: Option<TaskDifficulty>,
-    constraints: HashMap<usize, usize>,
+    constraints: BTreeMap<usize, usize>,
     exactly: bool,
 }
 
@@ -26,11 +31,11 @@ impl TaskWinCardsAmountNumber {
     where
         I: IntoIterator<Item = (usize, usize)>,
     {
-        Self {
+        assert_valid(Self {
             difficulty,
             exactly,
             constraints: constraints.into_iter().collect(),
-        }
+        })
     }
 }
 
@@ -70,5 +75,69 @@ impl BaseTask for TaskWinCardsAmountNumber {
         TaskStatus::Unknown
     }
 
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let tricks = state.get_player(ip).get_tricks();
+        let achieved = self
+            .constraints
+            .iter()
+            .map(|(&value, &amount)| count_won_except_submarine(tricks, value).min(amount))
+            .sum();
+        let required = self.constraints.values().sum();
+        TaskProgress::from(self.eval(state, ip)).with_fraction(achieved, required)
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let items: Vec<String> = self
+            .constraints
+            .iter()
+            .map(|(&v, &n)| match locale {
+                Locale::English if n == 1 => format!("one {}", v),
+                Locale::English => format!("{} {}s", locale.number(n, Gender::Masculine), v),
+                Locale::French => format!("{} {}", locale.number(n, Gender::Masculine), v),
+            })
+            .collect();
+        match (locale, self.exactly) {
+            (Locale::English, true) => format!("Win exactly {}", locale.and(&items)),
+            (Locale::English, false) => format!("Win at least {}", locale.and(&items)),
+            (Locale::French, true) => format!("Remportez exactement {}", locale.and(&items)),
+            (Locale::French, false) => format!("Remportez au moins {}", locale.and(&items)),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let amounts: Vec<String> = self
+            .constraints
+            .iter()
+            .map(|(v, n)| format!("{} of {}", n, v))
+            .collect();
+        let amount = if self.exactly { "exactly" } else { "at least" };
+        let text = format!("win {} {}", amount, amounts.join(", "));
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let mut lits = vec![];
+        for (&value, &amount) in &self.constraints {
+            let cards = COLORS.map(|color| color(value));
+            let won: Vec<Lit> = cards.iter().map(|c| encoder.won(c, ip)).collect();
+            let won = encoder.count(won);
+            if self.exactly {
+                // As in `eval`, the task is only done once no card of the value is available
+                lits.push(encoder.exactly(&won, amount));
+                lits.extend(cards.iter().map(|c| encoder.played(c)));
+            } else {
+                lits.push(won.at_least(amount));
+            }
+        }
+        Ok(encoder.and(lits))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self.constraints.keys().all(|v| COLOR_RANGE.contains(v)) {
+            true => Ok(()),
+            false => Err("card values go from 1 to 9".to_string()),
+        }
+    }
+
     impl_difficulty!();
 }
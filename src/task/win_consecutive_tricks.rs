@@ -1,9 +1,13 @@
 use crate::{
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskProgress, TaskStatus},
     trick::Trick,
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinConsecutiveTricks {
     difficulty: Option<TaskDifficulty>,
     amount: usize,
@@ -68,5 +72,120 @@ impl BaseTask for TaskWinConsecutiveTricks {
         TaskStatus::Unknown
     }
 
+    /// The fraction is the current streak, out of the number of consecutive tricks required. The
+    /// streak is broken as soon as another player wins a trick.
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let status = self.eval(state, ip);
+        let tricks = state.get_player(ip).get_tricks();
+        let (_, _, current_streak) = review_gameplay(tricks);
+        let last_trick_won = tricks
+            .last()
+            .is_some_and(|t| t.idx() + 1 == state.get_current_trick().idx());
+        let streak = if status == TaskStatus::Done {
+            self.amount
+        } else if last_trick_won {
+            current_streak.min(self.amount)
+        } else {
+            0
+        };
+        TaskProgress::from(status).with_fraction(streak, self.amount)
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let amount = locale.number(self.amount, Gender::Masculine);
+        match (locale, self.exactly) {
+            // A single trick is always consecutive
+            (Locale::English, true) if self.amount == 1 => "Win exactly one trick".to_string(),
+            (Locale::English, false) if self.amount == 1 => "Win one trick".to_string(),
+            (Locale::French, true) if self.amount == 1 => "Remportez exactement un pli".to_string(),
+            (Locale::French, false) if self.amount == 1 => "Remportez un pli".to_string(),
+            (Locale::English, true) => format!("Win exactly {} consecutive tricks", amount),
+            (Locale::English, false) => format!("Win {} consecutive tricks", amount),
+            (Locale::French, true) => {
+                format!("Remportez exactement {} plis consécutifs", amount)
+            }
+            (Locale::French, false) => format!("Remportez {} plis consécutifs", amount),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let exactly = if self.exactly { "exactly " } else { "" };
+        let text = format!("win {}{} consecutive tricks", exactly, self.amount);
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        if self.exactly && !encoder.game_is_over() {
+            return Ok(Lit::Const(false));
+        }
+        let final_idx = encoder.final_trick_idx();
+        let won: Vec<Lit> = (0..final_idx).map(|i| encoder.trick_won(i, ip)).collect();
+
+        // The tricks won include a streak of `amount` tricks - and no other trick if exactly
+        let mut streaks = vec![];
+        for start in 0..(final_idx + 1).saturating_sub(self.amount) {
+            let streak = start..start + self.amount;
+            let lits: Vec<Lit> = won
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &w)| {
+                    if streak.contains(&i) {
+                        Some(w)
+                    } else {
+                        self.exactly.then_some(!w)
+                    }
+                })
+                .collect();
+            streaks.push(encoder.and(lits));
+        }
+        Ok(encoder.or(streaks))
+    }
+
     impl_difficulty!();
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        card::Card::{Blue, Pink, Submarine},
+        player::Player,
+        state::State,
+    };
+
+    use super::*;
+
+    #[test]
+    fn progress_streak_broken() {
+        let mut state = State::new([
+            Player::new([Submarine(4), Pink(9), Pink(1), Pink(2)].into()),
+            Player::new([Pink(3), Pink(4), Pink(7), Blue(1)].into()),
+            Player::new([Pink(5), Pink(6), Pink(8), Blue(2)].into()),
+        ]);
+        let task = TaskWinConsecutiveTricks::new(None, 3, false);
+        let mut play = |cards: [_; 3]| {
+            for card in cards {
+                state.play_card(&card).unwrap();
+            }
+            task.progress(&state, 0)
+        };
+
+        let progress = play([Submarine(4), Blue(1), Blue(2)]);
+        assert_eq!(progress.fraction, Some((1, 3)));
+        let progress = play([Pink(9), Pink(3), Pink(5)]);
+        assert_eq!(progress.status, TaskStatus::Unknown);
+        assert_eq!(progress.fraction, Some((2, 3)));
+        // Player 1 wins with the pink 7, then player 2 with the pink 8
+        assert_eq!(play([Pink(1), Pink(7), Pink(6)]).fraction, Some((0, 3)));
+        assert_eq!(play([Pink(4), Pink(8), Pink(2)]).fraction, Some((0, 3)));
+    }
+
+    #[test]
+    fn describe_one_trick() {
+        let task = TaskWinConsecutiveTricks::new(None, 1, false);
+        assert_eq!(task.describe(Locale::English), "Win one trick");
+        let task = TaskWinConsecutiveTricks::new(None, 1, true);
+        assert_eq!(task.describe(Locale::French), "Remportez exactement un pli");
+        let task = TaskWinConsecutiveTricks::new(None, 2, false);
+        assert_eq!(task.describe(Locale::English), "Win two consecutive tricks");
+    }
+}
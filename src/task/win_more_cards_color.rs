@@ -1,14 +1,19 @@
 use crate::{
-    card::Card,
+    card::{Card, deck},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
     task::{BaseTask, TaskDifficulty, TaskStatus},
     trick::Trick,
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinMoreCardsColor {
     difficulty: Option<TaskDifficulty>,
-    more_of: fn(usize) -> Card,
-    fewer_of: fn(usize) -> Card,
+    /// Colors are stored as their card of value 1, so that the task can be compared.
+    more_of: Card,
+    fewer_of: Card,
     equal: bool,
 }
 
@@ -21,18 +26,18 @@ impl TaskWinMoreCardsColor {
     ) -> Self {
         Self {
             difficulty,
-            more_of,
-            fewer_of,
+            more_of: more_of(1),
+            fewer_of: fewer_of(1),
             equal,
         }
     }
 }
 
-fn count_color(cards: &[Card], color: fn(usize) -> Card) -> usize {
-    cards.iter().filter(|c| c.same_color(&color(1))).count()
+fn count_color(cards: &[Card], color: &Card) -> usize {
+    cards.iter().filter(|c| c.same_color(color)).count()
 }
 
-fn count_won(tricks: &[Trick], color: fn(usize) -> Card) -> usize {
+fn count_won(tricks: &[Trick], color: &Card) -> usize {
     tricks
         .iter()
         .fold(0, |acc, trick| acc + count_color(trick, color))
@@ -41,19 +46,18 @@ fn count_won(tricks: &[Trick], color: fn(usize) -> Card) -> usize {
 impl BaseTask for TaskWinMoreCardsColor {
     fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
         let tricks = state.get_player(ip).get_tricks();
-        let won_more_of = count_won(tricks, self.more_of);
-        let won_fewer_of = count_won(tricks, self.fewer_of);
-
-        let mut left_more_of = 0;
-        let mut left_fewer_of = 0;
-        for i in 0..state.n_players() {
-            if i == ip {
-                continue;
-            }
+        let won_more_of = count_won(tricks, &self.more_of);
+        let won_fewer_of = count_won(tricks, &self.fewer_of);
 
-            let hand = state.get_player(i).get_hand();
-            left_more_of += count_color(hand, self.more_of);
-            left_fewer_of += count_color(hand, self.fewer_of);
+        // Cards not won yet, including those of ip and those on the table, may all end up in
+        // the tricks of ip
+        let current_trick = state.get_current_trick();
+        let mut left_more_of = count_color(current_trick, &self.more_of);
+        let mut left_fewer_of = count_color(current_trick, &self.fewer_of);
+        for p in state.get_players() {
+            let hand = p.get_hand();
+            left_more_of += count_color(hand, &self.more_of);
+            left_fewer_of += count_color(hand, &self.fewer_of);
         }
 
         if !self.equal {
@@ -81,5 +85,98 @@ impl BaseTask for TaskWinMoreCardsColor {
         TaskStatus::Unknown
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        let more_of = self.more_of;
+        let fewer_of = self.fewer_of;
+        match locale {
+            Locale::English => {
+                let more_of = locale.color(&more_of, Gender::Feminine, true);
+                let fewer_of = locale.color(&fewer_of, Gender::Feminine, true);
+                if self.equal {
+                    format!(
+                        "Win the same amount of {} and {} cards (more than 0)",
+                        more_of, fewer_of
+                    )
+                } else {
+                    format!(
+                        "Win more {} cards than {} cards (0 {} cards are allowed)",
+                        more_of, fewer_of, fewer_of
+                    )
+                }
+            }
+            Locale::French => {
+                let more = locale.color(&more_of, Gender::Feminine, true);
+                let fewer = locale.color(&fewer_of, Gender::Feminine, true);
+                if self.equal {
+                    format!(
+                        "Remportez autant de cartes {} que de cartes {} (au moins une)",
+                        more, fewer
+                    )
+                } else {
+                    format!(
+                        "Remportez plus de cartes {} que de cartes {} (ne remporter aucune carte {} est permis)",
+                        more,
+                        fewer,
+                        locale.color(&fewer_of, Gender::Feminine, false)
+                    )
+                }
+            }
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let more_of = dsl::render_color(&self.more_of);
+        let fewer_of = dsl::render_color(&self.fewer_of);
+        let text = if self.equal {
+            format!("win as many {} as {}", more_of, fewer_of)
+        } else {
+            format!("win more {} than {}", more_of, fewer_of)
+        };
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let won = |encoder: &mut Encoder, color: &Card| {
+            let won: Vec<Lit> = deck()
+                .iter()
+                .filter(|c| c.same_color(color))
+                .map(|c| encoder.won(c, ip))
+                .collect();
+            won
+        };
+        let won_more_of = won(encoder, &self.more_of);
+        let won_more_of = encoder.count(won_more_of);
+        let won_fewer_of = won(encoder, &self.fewer_of);
+
+        // Cards still in a hand at the end of the game
+        let held: Vec<Card> = encoder
+            .state()
+            .get_players()
+            .iter()
+            .flat_map(|p| p.get_hand().iter().copied())
+            .collect();
+        let mut left = |color: &Card| {
+            let left: Vec<Lit> = held
+                .iter()
+                .filter(|c| c.same_color(color))
+                .map(|c| !encoder.played(c))
+                .collect();
+            left
+        };
+
+        if !self.equal {
+            let mut fewer_of = left(&self.fewer_of);
+            fewer_of.extend(won_fewer_of);
+            let fewer_of = encoder.count(fewer_of);
+            Ok(encoder.greater(&won_more_of, &fewer_of))
+        } else {
+            let mut lits: Vec<Lit> = left(&self.more_of).into_iter().map(|l| !l).collect();
+            lits.extend(left(&self.fewer_of).into_iter().map(|l| !l));
+            let won_fewer_of = encoder.count(won_fewer_of);
+            lits.push(encoder.equal(&won_more_of, &won_fewer_of));
+            Ok(encoder.and(lits))
+        }
+    }
+
     impl_difficulty!();
 }
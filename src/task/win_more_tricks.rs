@@ -1,6 +1,12 @@
-use crate::task::{BaseTask, TaskDifficulty, TaskStatus};
+use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
+    task::{BaseTask, TaskDifficulty, TaskStatus, assert_valid},
+};
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinMoreTricks {
     difficulty: Option<TaskDifficulty>,
     everyone_else_together: bool,
@@ -13,14 +19,11 @@ impl TaskWinMoreTricks {
         everyone_else_together: bool,
         fewer: bool,
     ) -> Self {
-        // The following configuration does not exist in the game.
-        // We rely on this for the implementation of [`eval`].
-        assert!(!(everyone_else_together && fewer));
-        Self {
+        assert_valid(Self {
             difficulty,
             everyone_else_together,
             fewer,
-        }
+        })
     }
 }
 
@@ -77,5 +80,70 @@ impl BaseTask for TaskWinMoreTricks {
         }
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        match locale {
+            Locale::English => format!(
+                "Win {} tricks than everyone else{}",
+                if self.fewer { "fewer" } else { "more" },
+                if self.everyone_else_together {
+                    " together"
+                } else {
+                    ""
+                }
+            ),
+            Locale::French => format!(
+                "Remportez {} de plis que {}",
+                if self.fewer { "moins" } else { "plus" },
+                if self.everyone_else_together {
+                    "tous les autres joueurs réunis"
+                } else {
+                    "chacun des autres joueurs"
+                }
+            ),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = match (self.fewer, self.everyone_else_together) {
+            (true, _) => "win fewer tricks than everyone",
+            (false, false) => "win more tricks than everyone",
+            (false, true) => "win more tricks than everyone together",
+        };
+        dsl::render_difficulty(text.to_string(), self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let n_players = encoder.state().n_players();
+        let n_tricks_won = encoder.n_tricks_won(ip);
+        if self.everyone_else_together {
+            let won_by_others: Vec<Lit> = (0..encoder.final_trick_idx())
+                .flat_map(|idx| (0..n_players).filter(|&i| i != ip).map(move |i| (idx, i)))
+                .map(|(idx, i)| encoder.trick_won(idx, i))
+                .collect();
+            let n_tricks_others = encoder.count(won_by_others);
+            return Ok(encoder.greater(&n_tricks_won, &n_tricks_others));
+        }
+
+        let mut lits = vec![];
+        for i in (0..n_players).filter(|&i| i != ip) {
+            let n = encoder.n_tricks_won(i);
+            lits.push(if self.fewer {
+                encoder.greater(&n, &n_tricks_won)
+            } else {
+                encoder.greater(&n_tricks_won, &n)
+            });
+        }
+        Ok(encoder.and(lits))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        // The following configuration does not exist in the game.
+        // We rely on this for the implementation of [`eval`].
+        if self.everyone_else_together && self.fewer {
+            return Err("fewer tricks than everyone else together is not a task".to_string());
+        }
+        Ok(())
+    }
+
     impl_difficulty!();
 }
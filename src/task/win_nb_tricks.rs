@@ -1,14 +1,75 @@
-use crate::task::{BaseTask, TaskDifficulty, TaskStatus};
+use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
+    task::{BaseTask, TaskDifficulty, TaskProgress, TaskStatus},
+};
 
-#[derive(Debug, Clone)]
+/// Win exactly `n` tricks.
+///
+/// For the "Win X tricks" tasks, `n` is predicted by the task holder once the cards are dealt,
+/// and may be kept hidden from the other players.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinNbTricks {
     difficulty: Option<TaskDifficulty>,
-    n: usize,
+    n: Option<usize>,
+    /// Whether `n` is predicted by the task holder.
+    predicted: bool,
+    hidden: bool,
 }
 
 impl TaskWinNbTricks {
     pub fn new(difficulty: Option<TaskDifficulty>, n: usize) -> Self {
-        Self { difficulty, n }
+        Self {
+            difficulty,
+            n: Some(n),
+            predicted: false,
+            hidden: false,
+        }
+    }
+
+    /// A task whose number of tricks is still to be predicted by its holder (see
+    /// [`TaskWinNbTricks::predict`]). If `hidden`, the prediction is only known to the holder.
+    pub fn new_prediction(difficulty: Option<TaskDifficulty>, hidden: bool) -> Self {
+        Self {
+            difficulty,
+            n: None,
+            predicted: true,
+            hidden,
+        }
+    }
+
+    /// Sets the predicted number of tricks. Returns `false`, leaving the task unchanged, if the
+    /// number of tricks is fixed rather than predicted.
+    pub fn predict(&mut self, n: usize) -> bool {
+        if self.predicted {
+            self.n = Some(n);
+        }
+        self.predicted
+    }
+
+    /// Whether the number of tricks is predicted by the task holder, rather than fixed.
+    pub fn is_prediction(&self) -> bool {
+        self.predicted
+    }
+
+    /// The number of tricks to win, if already predicted (and visible).
+    pub fn prediction(&self) -> Option<usize> {
+        self.n
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Returns the task as seen by the other players: a hidden prediction is removed.
+    pub fn masked(&self) -> Self {
+        let mut task = self.clone();
+        if self.hidden {
+            task.n = None;
+        }
+        task
     }
 }
 
@@ -16,8 +77,17 @@ impl BaseTask for TaskWinNbTricks {
     fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
         let n_tricks = state.get_player(ip).get_tricks().len();
 
+        // Without a prediction, the task cannot be completed.
+        let Some(n) = self.n else {
+            return if state.game_is_over() {
+                TaskStatus::Failed
+            } else {
+                TaskStatus::Unknown
+            };
+        };
+
         if state.game_is_over() {
-            if n_tricks == self.n {
+            if n_tricks == n {
                 TaskStatus::Done
             } else {
                 TaskStatus::Failed
@@ -25,7 +95,7 @@ impl BaseTask for TaskWinNbTricks {
         } else {
             // If we won too many tricks, or there are not enough tricks anymore, task is failed.
             let left_tricks = state.n_tricks_left();
-            if n_tricks > self.n || n_tricks + left_tricks < self.n {
+            if n_tricks > n || n_tricks + left_tricks < n {
                 TaskStatus::Failed
             } else {
                 TaskStatus::Unknown
@@ -33,5 +103,189 @@ impl BaseTask for TaskWinNbTricks {
         }
     }
 
+    /// The fraction is the number of tricks won, out of the number to win (if known).
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let progress =
+            TaskProgress::from(self.eval(state, ip)).with_tricks_left(state.n_tricks_left());
+        match self.n {
+            Some(n) => progress.with_fraction(state.get_player(ip).get_tricks().len(), n),
+            None => progress,
+        }
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let Some(n) = self.n else {
+            // The prediction is still to be made, or is hidden from this player.
+            return match (locale, self.hidden) {
+                (Locale::English, false) => "Win X tricks (predict the exact number and show it)",
+                (Locale::English, true) => {
+                    "Win X tricks (predict the exact number but keep it hidden)"
+                }
+                (Locale::French, false) => {
+                    "Remportez X plis (prédisez le nombre exact et montrez-le)"
+                }
+                (Locale::French, true) => {
+                    "Remportez X plis (prédisez le nombre exact mais gardez-le secret)"
+                }
+            }
+            .to_string();
+        };
+        let number = locale.number(n, Gender::Masculine);
+        let text = match locale {
+            Locale::English if n == 1 => "Win exactly one trick".to_string(),
+            Locale::English => format!("Win exactly {} tricks", number),
+            Locale::French if n == 1 => "Remportez exactement un pli".to_string(),
+            Locale::French => format!("Remportez exactement {} plis", number),
+        };
+        if !self.predicted {
+            return text;
+        }
+        match (locale, self.hidden) {
+            (Locale::English, false) => text + " (predicted and shown)",
+            (Locale::English, true) => text + " (predicted and kept hidden)",
+            (Locale::French, false) => text + " (prédit et montré)",
+            (Locale::French, true) => text + " (prédit et gardé secret)",
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = match (self.predicted, self.n) {
+            (false, n) => format!("win exactly {} tricks", n.unwrap_or_default()),
+            (true, n) => {
+                let hidden = if self.hidden { " hidden" } else { "" };
+                let prediction = n.map_or(String::new(), |n| format!(" predicted {}", n));
+                format!("win x tricks{}{}", hidden, prediction)
+            }
+        };
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let Some(n) = self.n else {
+            return Ok(Lit::Const(false));
+        };
+        if !encoder.game_is_over() {
+            return Ok(Lit::Const(false));
+        }
+        let n_tricks = encoder.n_tricks_won(ip);
+        Ok(encoder.exactly(&n_tricks, n))
+    }
+
     impl_difficulty!();
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        state::{GameError, State},
+        task::{Task, win_x_tricks_hidden},
+    };
+
+    use super::*;
+
+    /// A 4-player game where only the last two tricks are left, player 1 holding the prediction.
+    fn endgame() -> State {
+        let mut state = State::new_random_with_rng(4, &mut StdRng::seed_from_u64(3));
+        state
+            .get_mut_player(1)
+            .add_task(win_x_tricks_hidden())
+            .unwrap();
+        while state.n_tricks_left() > 2 {
+            let hand = state.get_current_player().get_hand();
+            let card = hand.playable_cards(state.get_current_trick().first())[0];
+            state.play_card(&card).unwrap();
+        }
+        state
+    }
+
+    /// Numbers of tricks player `ip` can end the game with.
+    fn reachable(state: &State, ip: usize, acc: &mut BTreeSet<usize>) {
+        if state.game_is_over() {
+            acc.insert(state.get_player(ip).get_tricks().len());
+            return;
+        }
+        let hand = state.get_current_player().get_hand();
+        for card in hand.playable_cards(state.get_current_trick().first()) {
+            let mut s = state.clone();
+            s.play_card(&card).unwrap();
+            reachable(&s, ip, acc);
+        }
+    }
+
+    #[test]
+    fn unpredicted_is_unknown() {
+        let state = endgame();
+        assert_eq!(win_x_tricks_hidden().eval(&state, 1), TaskStatus::Unknown);
+    }
+
+    #[test]
+    fn progress() {
+        let state = endgame();
+        let won = state.get_player(1).get_tricks().len();
+        let mut task = win_x_tricks_hidden();
+        assert_eq!(task.progress(&state, 1).fraction, None);
+        task.predict(won + 1);
+        assert_eq!(
+            task.progress(&state, 1),
+            TaskProgress {
+                status: TaskStatus::Unknown,
+                fraction: Some((won, won + 1)),
+                tricks_left: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn hidden_in_view() {
+        let mut state = endgame();
+        if let Task::WinNbTricks(t) = &mut state.get_mut_player(1).get_mut_tasks()[0] {
+            t.predict(2);
+        }
+        let prediction = |view: &crate::view::PlayerView| match &view.get_tasks(1)[0] {
+            Task::WinNbTricks(t) => t.prediction(),
+            _ => panic!("unexpected task"),
+        };
+        assert_eq!(prediction(&state.view(1)), Some(2));
+        assert_eq!(prediction(&state.view(0)), None);
+
+        // Samples drawn by other players have a reachable prediction.
+        let won = state.get_player(1).get_tricks().len();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            let sample = state.view(0).sample(&mut rng).unwrap();
+            match &sample.get_player(1).get_tasks()[0] {
+                Task::WinNbTricks(t) => assert!((won..=won + 2).contains(&t.prediction().unwrap())),
+                _ => panic!("unexpected task"),
+            }
+        }
+    }
+
+    #[test]
+    fn feasible_predictions() {
+        let state = endgame();
+        let mut expected = BTreeSet::new();
+        reachable(&state, 1, &mut expected);
+        let feasible = state.feasible_predictions(1, 0, 100_000).unwrap();
+        assert_eq!(feasible, expected.into_iter().collect::<Vec<_>>());
+        assert!(matches!(
+            state.feasible_predictions(0, 0, 100_000),
+            Err(GameError::NotAPrediction(0, 0))
+        ));
+        assert!(matches!(
+            state.feasible_predictions(1, 0, 1),
+            Err(GameError::BudgetExceeded(1))
+        ));
+
+        // A fixed number of tricks cannot be predicted
+        let mut state = endgame();
+        state.get_mut_player(1).get_mut_tasks()[0] = TaskWinNbTricks::new(None, 1).into();
+        assert!(matches!(
+            state.feasible_predictions(1, 0, 100_000),
+            Err(GameError::NotAPrediction(1, 0))
+        ));
+    }
+}
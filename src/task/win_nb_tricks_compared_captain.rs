@@ -1,13 +1,18 @@
 use std::cmp::Ordering;
 
 use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
     state::State,
     task::{BaseTask, TaskDifficulty, TaskStatus},
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinNbTricksComparedCaptain {
     difficulty: Option<TaskDifficulty>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::ordering"))]
     comp: Ordering,
 }
 
@@ -29,7 +34,10 @@ impl BaseTask for TaskWinNbTricksComparedCaptain {
     fn eval(&self, state: &crate::state::State, ip: usize) -> super::TaskStatus {
         let i_captain =
             State::retrieve_captain(state.get_players(), Some(state.get_current_trick())).unwrap();
-        assert_ne!(ip, i_captain, "This task cannot be given to the captain.");
+        // This task cannot be given to the captain (see `captain_forbidden`).
+        if ip == i_captain {
+            return TaskStatus::Failed;
+        }
 
         if state.game_is_over() {
             let n_tricks_captain = state.get_player(i_captain).get_tricks().len();
@@ -71,5 +79,46 @@ impl BaseTask for TaskWinNbTricksComparedCaptain {
         TaskStatus::Unknown
     }
 
+    fn captain_forbidden(&self) -> bool {
+        true
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        match (locale, self.comp) {
+            (Locale::English, Ordering::Less) => "Win fewer tricks than the captain",
+            (Locale::English, Ordering::Equal) => "Win the same number of tricks as the captain",
+            (Locale::English, Ordering::Greater) => "Win more tricks than the captain",
+            (Locale::French, Ordering::Less) => "Remportez moins de plis que le capitaine",
+            (Locale::French, Ordering::Equal) => "Remportez autant de plis que le capitaine",
+            (Locale::French, Ordering::Greater) => "Remportez plus de plis que le capitaine",
+        }
+        .to_string()
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = match self.comp {
+            Ordering::Less => "win fewer tricks than captain",
+            Ordering::Equal => "win same tricks as captain",
+            Ordering::Greater => "win more tricks than captain",
+        };
+        dsl::render_difficulty(text.to_string(), self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let state = encoder.state();
+        let i_captain =
+            State::retrieve_captain(state.get_players(), Some(state.get_current_trick()))?;
+        if ip == i_captain || !encoder.game_is_over() {
+            return Ok(Lit::Const(false));
+        }
+        let n_tricks_player = encoder.n_tricks_won(ip);
+        let n_tricks_captain = encoder.n_tricks_won(i_captain);
+        Ok(match self.comp {
+            Ordering::Less => encoder.greater(&n_tricks_captain, &n_tricks_player),
+            Ordering::Greater => encoder.greater(&n_tricks_player, &n_tricks_captain),
+            Ordering::Equal => encoder.equal(&n_tricks_player, &n_tricks_captain),
+        })
+    }
+
     impl_difficulty!();
 }
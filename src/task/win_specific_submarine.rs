@@ -1,14 +1,37 @@
-use crate::task::{BaseTask, TaskDifficulty, TaskStatus};
+use crate::{
+    card::{Card, SUBMARINE_RANGE},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
+    task::{BaseTask, TaskDifficulty, TaskStatus, assert_valid, redeal_sets},
+};
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinSpecificSubmarine {
     difficulty: Option<TaskDifficulty>,
     value: usize,
+    redeal_if: Vec<Vec<Card>>,
 }
 
 impl TaskWinSpecificSubmarine {
     pub fn new(difficulty: Option<TaskDifficulty>, value: usize) -> Self {
-        Self { difficulty, value }
+        assert_valid(Self {
+            difficulty,
+            value,
+            redeal_if: Vec::new(),
+        })
+    }
+
+    /// Deals again when a single player holds all cards of one of `sets`, typically the
+    /// submarine to win along with stronger ones.
+    pub fn with_redeal_if<I, J>(mut self, sets: I) -> Self
+    where
+        I: IntoIterator<Item = J>,
+        J: IntoIterator<Item = Card>,
+    {
+        self.redeal_if = redeal_sets(sets);
+        self
     }
 }
 
@@ -31,7 +54,12 @@ impl BaseTask for TaskWinSpecificSubmarine {
 
         let mut target_in_hand = false;
         let mut biggest_other_in_hand = None;
-        for card in player.get_hand().iter() {
+        // A card on the table, in the trick being played, may still be won by anyone
+        let held = |i| {
+            let hand = state.get_player(i).get_hand().iter();
+            hand.chain(state.get_card_in_current_trick(i))
+        };
+        for card in held(ip) {
             if card.is_submarine() {
                 if card.val() == self.value {
                     target_in_hand = true;
@@ -56,7 +84,7 @@ impl BaseTask for TaskWinSpecificSubmarine {
             }
 
             let mut biggest_submarine = None;
-            for card in state.get_player(i).get_hand().iter() {
+            for card in held(i) {
                 if card.is_submarine() {
                     biggest_submarine = std::cmp::max(biggest_submarine, Some(card.val()));
                 }
@@ -79,5 +107,49 @@ impl BaseTask for TaskWinSpecificSubmarine {
         TaskStatus::Unknown
     }
 
+    fn needs_redeal(&self, state: &crate::state::State) -> bool {
+        super::someone_holds_all(state, &self.redeal_if)
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let text = match locale {
+            Locale::English => format!("Win the {} submarine and no other", self.value),
+            Locale::French => format!("Remportez le sous-marin {} et aucun autre", self.value),
+        };
+        text + &locale.redeal(&self.redeal_if)
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = format!(
+            "win submarine {} only{}",
+            self.value,
+            dsl::render_redeal(&self.redeal_if)
+        );
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let hand = encoder.state().get_player(ip).get_hand();
+        let mut lits = vec![encoder.won(&Card::Submarine(self.value), ip)];
+        for other in SUBMARINE_RANGE
+            .filter(|&v| v != self.value)
+            .map(Card::Submarine)
+        {
+            lits.push(!encoder.won(&other, ip));
+            // No other submarine may stay in the hand of the player
+            if hand.contains(&other) {
+                lits.push(encoder.played(&other));
+            }
+        }
+        Ok(encoder.and(lits))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match SUBMARINE_RANGE.contains(&self.value) {
+            true => Ok(()),
+            false => Err("submarine values go from 1 to 4".to_string()),
+        }
+    }
+
     impl_difficulty!();
 }
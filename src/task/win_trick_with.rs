@@ -1,10 +1,14 @@
 use crate::{
-    card::Card,
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    card::{COLOR_RANGE, Card},
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::Locale,
+    task::{BaseTask, TaskDifficulty, TaskStatus, assert_valid},
     trick::Trick,
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinTrickWith {
     difficulty: Option<TaskDifficulty>,
     win_with: usize,
@@ -17,11 +21,11 @@ impl TaskWinTrickWith {
         win_with: usize,
         must_win: Option<usize>,
     ) -> Self {
-        TaskWinTrickWith {
+        assert_valid(TaskWinTrickWith {
             difficulty,
             win_with,
             must_win,
-        }
+        })
     }
 }
 
@@ -30,15 +34,14 @@ impl BaseTask for TaskWinTrickWith {
         // Checking if a trick already satisfies the task
         let n_players = state.n_players();
         let idx_in_trick = |t: &Trick| (ip + n_players - t.get_first_player()) % n_players;
-        let won_with = |t: &Trick| t[idx_in_trick(t)].val() == self.win_with;
+        let is_win_with = |c: &Card| c.val() == self.win_with && !c.is_submarine();
+        let won_with = |t: &Trick| is_win_with(&t[idx_in_trick(t)]);
         let contains_additional_card = |t: &Trick| match self.must_win {
             None => true,
-            Some(c) => {
-                t.contains(&Card::Blue(c))
-                    || t.contains(&Card::Green(c))
-                    || t.contains(&Card::Pink(c))
-                    || t.contains(&Card::Yellow(c))
-            }
+            Some(v) => t
+                .iter()
+                .enumerate()
+                .any(|(i, c)| i != idx_in_trick(t) && c.val() == v && !c.is_submarine()),
         };
         let goal_trick = |t| won_with(t) && contains_additional_card(t);
         let p = state.get_player(ip);
@@ -46,10 +49,15 @@ impl BaseTask for TaskWinTrickWith {
             return TaskStatus::Done;
         }
 
-        // If player does not have a card of the required value, task is failed
+        // If player does not have a card of the required value, task is failed - the card may
+        // also be on the table, in the trick being played
         let mut missing_in_hand = true;
-        for c in p.get_hand().iter() {
-            if c.val() == self.win_with && !c.is_submarine() {
+        for c in p
+            .get_hand()
+            .iter()
+            .chain(state.get_card_in_current_trick(ip))
+        {
+            if is_win_with(c) {
                 missing_in_hand = false;
             }
         }
@@ -65,7 +73,10 @@ impl BaseTask for TaskWinTrickWith {
                     continue;
                 }
 
-                for c in state.get_player(i).get_hand().iter() {
+                // Cards on the table count too, except the own card of ip which cannot be in
+                // the same trick as the one to win with
+                let hand = state.get_player(i).get_hand().iter();
+                for c in hand.chain(state.get_card_in_current_trick(i)) {
                     if c.val() == target_val && !c.is_submarine() {
                         missing_other_card = false;
                     }
@@ -80,5 +91,80 @@ impl BaseTask for TaskWinTrickWith {
         TaskStatus::Unknown
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        let article = |v: usize| if v == 8 { "an" } else { "a" };
+        match (locale, self.must_win) {
+            (Locale::English, None) => {
+                format!(
+                    "Win a trick with {} {}",
+                    article(self.win_with),
+                    self.win_with
+                )
+            }
+            (Locale::English, Some(v)) if v == self.win_with => {
+                format!("Win {} {} with another {}", article(v), v, v)
+            }
+            (Locale::English, Some(v)) => format!(
+                "Win {} {} with {} {}",
+                article(v),
+                v,
+                article(self.win_with),
+                self.win_with
+            ),
+            (Locale::French, None) => format!("Remportez un pli avec un {}", self.win_with),
+            (Locale::French, Some(v)) if v == self.win_with => {
+                format!("Remportez un {} avec un autre {}", v, v)
+            }
+            (Locale::French, Some(v)) => format!("Remportez un {} avec un {}", v, self.win_with),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let containing = self
+            .must_win
+            .map_or(String::new(), |v| format!(" containing {}", v));
+        let text = format!("win trick with value {}{}", self.win_with, containing);
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let is_win_with = |c: &Card| c.val() == self.win_with && !c.is_submarine();
+        let mut goal_tricks = vec![];
+        for trick in encoder.tricks().to_vec() {
+            let won_with: Vec<Lit> = trick
+                .plays
+                .iter()
+                .filter(|p| p.seat == ip && is_win_with(&p.card))
+                .map(|p| p.lit)
+                .collect();
+            let won_with = encoder.or(won_with);
+            let additional_card = match self.must_win {
+                None => Lit::Const(true),
+                Some(v) => {
+                    let lits: Vec<Lit> = trick
+                        .plays
+                        .iter()
+                        .filter(|p| p.seat != ip && p.card.val() == v && !p.card.is_submarine())
+                        .map(|p| p.lit)
+                        .collect();
+                    encoder.or(lits)
+                }
+            };
+            goal_tricks.push(encoder.and([trick.winners[ip], won_with, additional_card]));
+        }
+        Ok(encoder.or(goal_tricks))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match [Some(self.win_with), self.must_win]
+            .into_iter()
+            .flatten()
+            .all(|v| COLOR_RANGE.contains(&v))
+        {
+            true => Ok(()),
+            false => Err("card values go from 1 to 9".to_string()),
+        }
+    }
+
     impl_difficulty!();
 }
@@ -1,79 +1,163 @@
-use std::{collections::HashSet, fmt::Debug, rc::Rc};
+use std::collections::HashSet;
 
 use crate::{
     card::Card,
+    cnf::{CnfError, Encoder, FutureTrick, Lit},
+    dsl,
+    locale::{Gender, Locale},
     player,
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    task::{BaseTask, TaskDifficulty, TaskStatus, assert_valid},
     trick::Trick,
 };
 
-#[derive(Clone)]
-pub struct TaskWinTrickWithPred {
-    difficulty: Option<TaskDifficulty>,
-    name: String,
-    pred: Rc<dyn Fn(&Trick) -> bool>,
+/// Condition a trick must satisfy, as printed on the task card.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrickCondition {
+    /// All cards have an even value (submarines included).
+    Even,
+    /// All cards have an odd value (submarines included).
+    Odd,
+    /// All cards have a value greater than the given one (submarines included).
+    Greater(usize),
+    /// All cards have a value lower than the given one, and there is no submarine.
+    Lower(usize),
+    /// The values add up to more than the given one, and there is no submarine.
+    TotalGreater(usize),
+    /// The values add up to less than the given one, and there is no submarine.
+    TotalLower(usize),
+    /// The values add up to one of the given ones, sorted without duplicates.
+    TotalIn(Vec<usize>),
+    /// As many cards of both colors (given by any card of that color), and at least one.
+    SameNbOfColors(Card, Card),
+    /// The card is in the trick, along with a submarine.
+    CardWithSubmarine(Card),
+    /// The card is in the trick, which is the last one of the game.
+    CardInLastTrick { card: Card, last_trick: usize },
 }
 
-/// We cannot derive Debug so we must implement it manually.
-impl Debug for TaskWinTrickWithPred {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "TaskWinTrickWithPred<name=\"{}\">", self.name)
+impl TrickCondition {
+    pub fn holds(&self, trick: &Trick) -> bool {
+        let no_submarine = || trick.iter().all(|c| !c.is_submarine());
+        let total = || trick.iter().map(|c| c.val()).sum::<usize>();
+        match self {
+            TrickCondition::Even => trick.iter().all(|c| c.val() % 2 == 0),
+            TrickCondition::Odd => trick.iter().all(|c| c.val() % 2 == 1),
+            TrickCondition::Greater(v) => trick.iter().all(|c| c.val() > *v),
+            TrickCondition::Lower(v) => trick.iter().all(|c| c.val() < *v && !c.is_submarine()),
+            TrickCondition::TotalGreater(v) => no_submarine() && total() > *v,
+            TrickCondition::TotalLower(v) => no_submarine() && total() < *v,
+            TrickCondition::TotalIn(values) => values.contains(&total()),
+            TrickCondition::SameNbOfColors(c1, c2) => {
+                let n1 = trick.iter().filter(|&c| c.same_color(c1)).count();
+                let n2 = trick.iter().filter(|&c| c.same_color(c2)).count();
+                n1 > 0 && n1 == n2
+            }
+            TrickCondition::CardWithSubmarine(card) => {
+                trick.contains(card) && trick.iter().any(|c| c.is_submarine())
+            }
+            TrickCondition::CardInLastTrick { card, last_trick } => {
+                trick.idx() == *last_trick && trick.contains(card)
+            }
+        }
+    }
+
+    /// Returns a literal of `encoder` which is true if and only if the condition holds for a
+    /// trick left to play, once complete.
+    pub fn to_cnf(&self, encoder: &mut Encoder, trick: &FutureTrick) -> Lit {
+        let plays = |f: &dyn Fn(&Card) -> bool| -> Vec<Lit> {
+            trick
+                .plays
+                .iter()
+                .filter(|p| f(&p.card))
+                .map(|p| p.lit)
+                .collect()
+        };
+        let none = |encoder: &mut Encoder, f: &dyn Fn(&Card) -> bool| !encoder.or(plays(f));
+        let total =
+            |encoder: &mut Encoder| encoder.sum(trick.plays.iter().map(|p| (p.lit, p.card.val())));
+        match self {
+            TrickCondition::Even => none(encoder, &|c| c.val() % 2 == 1),
+            TrickCondition::Odd => none(encoder, &|c| c.val() % 2 == 0),
+            TrickCondition::Greater(v) => none(encoder, &|c| c.val() <= *v),
+            TrickCondition::Lower(v) => none(encoder, &|c| c.val() >= *v || c.is_submarine()),
+            TrickCondition::TotalGreater(v) => {
+                let no_submarine = none(encoder, &Card::is_submarine);
+                let total = total(encoder);
+                encoder.and([no_submarine, total.at_least(v + 1)])
+            }
+            TrickCondition::TotalLower(v) => {
+                let no_submarine = none(encoder, &Card::is_submarine);
+                let total = total(encoder);
+                encoder.and([no_submarine, !total.at_least(*v)])
+            }
+            TrickCondition::TotalIn(values) => {
+                let total = total(encoder);
+                let lits: Vec<Lit> = values.iter().map(|&v| encoder.exactly(&total, v)).collect();
+                encoder.or(lits)
+            }
+            TrickCondition::SameNbOfColors(c1, c2) => {
+                let n1 = encoder.count(plays(&|c| c.same_color(c1)));
+                let n2 = encoder.count(plays(&|c| c.same_color(c2)));
+                let equal = encoder.equal(&n1, &n2);
+                encoder.and([equal, n1.at_least(1)])
+            }
+            TrickCondition::CardWithSubmarine(card) => {
+                let submarine = encoder.or(plays(&Card::is_submarine));
+                encoder.and([trick.play(card), submarine])
+            }
+            TrickCondition::CardInLastTrick { card, last_trick } => {
+                if trick.idx == *last_trick {
+                    trick.play(card)
+                } else {
+                    Lit::Const(false)
+                }
+            }
+        }
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskWinTrickWithPred {
+    difficulty: Option<TaskDifficulty>,
+    condition: TrickCondition,
+}
+
 impl TaskWinTrickWithPred {
-    pub fn new_even(difficulty: Option<TaskDifficulty>) -> Self {
-        Self {
+    pub fn new(difficulty: Option<TaskDifficulty>, condition: TrickCondition) -> Self {
+        assert_valid(Self {
             difficulty,
-            name: "even".to_string(),
-            pred: Rc::new(|t| t.iter().all(|c| c.val() % 2 == 0)),
-        }
+            condition,
+        })
+    }
+
+    pub fn condition(&self) -> &TrickCondition {
+        &self.condition
+    }
+
+    pub fn new_even(difficulty: Option<TaskDifficulty>) -> Self {
+        Self::new(difficulty, TrickCondition::Even)
     }
 
     pub fn new_odd(difficulty: Option<TaskDifficulty>) -> Self {
-        Self {
-            difficulty,
-            name: "odd".to_string(),
-            pred: Rc::new(|t| t.iter().all(|c| c.val() % 2 == 1)),
-        }
+        Self::new(difficulty, TrickCondition::Odd)
     }
 
     pub fn new_greater(difficulty: Option<TaskDifficulty>, value: usize) -> Self {
-        Self {
-            difficulty,
-            name: format!("greater than {}", value),
-            pred: Rc::new(move |t| t.iter().all(|c| c.val() > value)),
-        }
+        Self::new(difficulty, TrickCondition::Greater(value))
     }
 
     pub fn new_lower(difficulty: Option<TaskDifficulty>, value: usize) -> Self {
-        Self {
-            difficulty,
-            name: format!("lower than {} (without submarines)", value),
-            pred: Rc::new(move |t| t.iter().all(|c| c.val() < value && !c.is_submarine())),
-        }
+        Self::new(difficulty, TrickCondition::Lower(value))
     }
 
     pub fn new_total_greater(difficulty: Option<TaskDifficulty>, value: usize) -> Self {
-        Self {
-            difficulty,
-            name: format!("total value greather than {} (without submarines)", value),
-            pred: Rc::new(move |t| {
-                t.iter().all(|c| !c.is_submarine())
-                    && t.iter().map(|c| c.val()).sum::<usize>() > value
-            }),
-        }
+        Self::new(difficulty, TrickCondition::TotalGreater(value))
     }
 
     pub fn new_total_lower(difficulty: Option<TaskDifficulty>, value: usize) -> Self {
-        Self {
-            difficulty,
-            name: format!("total value lower than {} (without submarines)", value),
-            pred: Rc::new(move |t| {
-                t.iter().all(|c| !c.is_submarine())
-                    && t.iter().map(|c| c.val()).sum::<usize>() < value
-            }),
-        }
+        Self::new(difficulty, TrickCondition::TotalLower(value))
     }
 
     pub fn new_total_in<I>(difficulty: Option<TaskDifficulty>, values: I) -> Self
@@ -81,11 +165,9 @@ impl TaskWinTrickWithPred {
         I: IntoIterator<Item = usize>,
     {
         let set: HashSet<usize> = values.into_iter().collect();
-        Self {
-            difficulty,
-            name: format!("total value in {:?}", set),
-            pred: Rc::new(move |t| set.contains(&t.iter().map(|c| c.val()).sum::<usize>())),
-        }
+        let mut values: Vec<usize> = set.into_iter().collect();
+        values.sort();
+        Self::new(difficulty, TrickCondition::TotalIn(values))
     }
 
     pub fn new_same_nb_of_colors(
@@ -93,29 +175,14 @@ impl TaskWinTrickWithPred {
         color_1: fn(usize) -> Card,
         color_2: fn(usize) -> Card,
     ) -> Self {
-        let d1 = color_1(1);
-        let d2 = color_2(1);
-        assert!(!d1.same_color(&d2), "colors must be different");
-        Self {
+        Self::new(
             difficulty,
-            name: format!("same number of cards of two colors: {:?} and {:?}", d1, d2),
-            pred: Rc::new(move |t| {
-                let n1 = t.iter().filter(|&c| c.same_color(&d1)).count();
-                let n2 = t.iter().filter(|&c| c.same_color(&d2)).count();
-                n1 > 0 && n1 == n2
-            }),
-        }
+            TrickCondition::SameNbOfColors(color_1(1), color_2(1)),
+        )
     }
 
     pub fn new_win_card_with_submarine(difficulty: Option<TaskDifficulty>, card: Card) -> Self {
-        assert!(card.is_valid());
-        Self {
-            difficulty,
-            name: format!("win card {:?} with a submarine", card),
-            pred: Rc::new(move |t| {
-                t.iter().any(|c| *c == card) && t.iter().any(|c| c.is_submarine())
-            }),
-        }
+        Self::new(difficulty, TrickCondition::CardWithSubmarine(card))
     }
 
     pub fn new_win_card_last_trick(
@@ -123,14 +190,12 @@ impl TaskWinTrickWithPred {
         card: Card,
         n_players: usize,
     ) -> Self {
-        assert!(card.is_valid());
         player::check_valid_n_players(n_players).unwrap();
         let last_trick = player::n_tricks_total(n_players) - 1;
-        Self {
+        Self::new(
             difficulty,
-            name: format!("win card {:?} in the last trick ({})", card, last_trick),
-            pred: Rc::new(move |t| t.idx() == last_trick && t.iter().any(|c| *c == card)),
-        }
+            TrickCondition::CardInLastTrick { card, last_trick },
+        )
     }
 }
 
@@ -147,7 +212,7 @@ impl BaseTask for TaskWinTrickWithPred {
             .get_player(ip)
             .get_tricks()
             .iter()
-            .any(self.pred.as_ref())
+            .any(|t| self.condition.holds(t))
         {
             return TaskStatus::Done;
         }
@@ -157,5 +222,189 @@ impl BaseTask for TaskWinTrickWithPred {
         TaskStatus::Unknown
     }
 
+    fn describe(&self, locale: Locale) -> String {
+        let color = |c: &Card| locale.color(c, Gender::Feminine, locale == Locale::French);
+        match locale {
+            Locale::English => match &self.condition {
+                TrickCondition::Even => "Win a trick that has only even numbers".to_string(),
+                TrickCondition::Odd => "Win a trick that has only odd numbers".to_string(),
+                TrickCondition::Greater(v) => {
+                    format!(
+                        "Win a trick where all cards are of greater value than {}",
+                        v
+                    )
+                }
+                TrickCondition::Lower(v) => format!(
+                    "Win a trick where all cards are of lower value than {} (without submarines)",
+                    v
+                ),
+                TrickCondition::TotalGreater(v) => format!(
+                    "Win a trick with a total value higher than {} (without submarines)",
+                    v
+                ),
+                TrickCondition::TotalLower(v) => format!(
+                    "Win a trick with a total value lower than {} (without submarines)",
+                    v
+                ),
+                TrickCondition::TotalIn(values) => {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    format!("Win a trick with a total value of {}", locale.or(&values))
+                }
+                TrickCondition::SameNbOfColors(c1, c2) => format!(
+                    "Win a trick with the same amount of {} and {} cards (more than 0)",
+                    color(c1),
+                    color(c2)
+                ),
+                TrickCondition::CardWithSubmarine(c) => {
+                    format!("Win {} with a submarine", locale.card(c))
+                }
+                TrickCondition::CardInLastTrick { card: c, .. } => {
+                    format!("Win {} in the last trick", locale.card(c))
+                }
+            },
+            Locale::French => match &self.condition {
+                TrickCondition::Even => {
+                    "Remportez un pli ne contenant que des nombres pairs".to_string()
+                }
+                TrickCondition::Odd => {
+                    "Remportez un pli ne contenant que des nombres impairs".to_string()
+                }
+                TrickCondition::Greater(v) => format!(
+                    "Remportez un pli dont toutes les cartes ont une valeur supérieure à {}",
+                    v
+                ),
+                TrickCondition::Lower(v) => format!(
+                    "Remportez un pli dont toutes les cartes ont une valeur inférieure à {} (sans sous-marin)",
+                    v
+                ),
+                TrickCondition::TotalGreater(v) => format!(
+                    "Remportez un pli d'une valeur totale supérieure à {} (sans sous-marin)",
+                    v
+                ),
+                TrickCondition::TotalLower(v) => format!(
+                    "Remportez un pli d'une valeur totale inférieure à {} (sans sous-marin)",
+                    v
+                ),
+                TrickCondition::TotalIn(values) => {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    format!(
+                        "Remportez un pli d'une valeur totale de {}",
+                        locale.or(&values)
+                    )
+                }
+                TrickCondition::SameNbOfColors(c1, c2) => format!(
+                    "Remportez un pli contenant autant de cartes {} que de cartes {} (au moins une)",
+                    color(c1),
+                    color(c2)
+                ),
+                TrickCondition::CardWithSubmarine(c) => {
+                    format!("Remportez {} avec un sous-marin", locale.card(c))
+                }
+                TrickCondition::CardInLastTrick { card: c, .. } => {
+                    format!("Remportez {} lors du dernier pli", locale.card(c))
+                }
+            },
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let text = match &self.condition {
+            TrickCondition::Even => "win trick where all even".to_string(),
+            TrickCondition::Odd => "win trick where all odd".to_string(),
+            TrickCondition::Greater(v) => format!("win trick where all > {}", v),
+            TrickCondition::Lower(v) => format!("win trick where all < {}", v),
+            TrickCondition::TotalGreater(v) => format!("win trick where total > {}", v),
+            TrickCondition::TotalLower(v) => format!("win trick where total < {}", v),
+            TrickCondition::TotalIn(values) => format!(
+                "win trick where total in {}",
+                dsl::render_numbers(values.iter().copied())
+            ),
+            TrickCondition::SameNbOfColors(c1, c2) => format!(
+                "win trick where same {} and {}",
+                dsl::render_color(c1),
+                dsl::render_color(c2)
+            ),
+            TrickCondition::CardWithSubmarine(c) => format!("win {} with submarine", c),
+            TrickCondition::CardInLastTrick { card, .. } => format!("win {} in last trick", card),
+        };
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let mut goal_tricks = vec![];
+        for trick in encoder.tricks().to_vec() {
+            let holds = self.condition.to_cnf(encoder, &trick);
+            goal_tricks.push(encoder.and([trick.winners[ip], holds]));
+        }
+        Ok(encoder.or(goal_tricks))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match &self.condition {
+            TrickCondition::SameNbOfColors(c1, c2) if c1.same_color(c2) => {
+                Err("colors must be different".to_string())
+            }
+            TrickCondition::CardWithSubmarine(card)
+            | TrickCondition::CardInLastTrick { card, .. }
+                if !card.is_valid() =>
+            {
+                Err(format!("invalid card {:?}", card))
+            }
+            _ => Ok(()),
+        }
+    }
+
     impl_difficulty!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        card::{blue, green, pink, submarine, yellow},
+        task::Task,
+    };
+
+    #[test]
+    fn conditions() {
+        let trick = |idx, cards: &[Card]| Trick::from((idx, 0, cards.to_vec()));
+
+        assert!(TrickCondition::Even.holds(&trick(0, &[pink(2), submarine(4), blue(8)])));
+        assert!(!TrickCondition::Odd.holds(&trick(0, &[pink(3), submarine(4)])));
+        assert!(TrickCondition::Greater(5).holds(&trick(0, &[pink(6), blue(9)])));
+        assert!(!TrickCondition::Lower(7).holds(&trick(0, &[pink(6), submarine(1)])));
+        assert!(TrickCondition::TotalGreater(10).holds(&trick(0, &[pink(6), blue(5)])));
+        assert!(!TrickCondition::TotalLower(10).holds(&trick(0, &[pink(1), submarine(2)])));
+        assert!(
+            TrickCondition::TotalIn(vec![22, 23]).holds(&trick(0, &[pink(9), blue(9), green(5)]))
+        );
+
+        let same = TrickCondition::SameNbOfColors(green(1), yellow(1));
+        assert!(same.holds(&trick(0, &[green(3), yellow(4), pink(1)])));
+        assert!(!same.holds(&trick(0, &[pink(3), blue(4)])));
+
+        let with_sub = TrickCondition::CardWithSubmarine(pink(7));
+        assert!(with_sub.holds(&trick(0, &[pink(7), submarine(1)])));
+        assert!(!with_sub.holds(&trick(0, &[pink(7), pink(9)])));
+
+        let last = TaskWinTrickWithPred::new_win_card_last_trick(None, green(2), 4);
+        assert!(last.condition().holds(&trick(9, &[green(2), blue(1)])));
+        assert!(!last.condition().holds(&trick(8, &[green(2), blue(1)])));
+    }
+
+    #[test]
+    fn tasks_are_comparable_and_thread_safe() {
+        fn assert_traits<T: Send + Sync + Clone + PartialEq>() {}
+        assert_traits::<Task>();
+
+        let task: Task = TaskWinTrickWithPred::new_total_in(None, [23, 22, 23]).into();
+        assert_eq!(
+            task,
+            TaskWinTrickWithPred::new(None, TrickCondition::TotalIn(vec![22, 23])).into()
+        );
+        assert_ne!(task, TaskWinTrickWithPred::new_odd(None).into());
+        std::thread::spawn(move || task.get_difficulty(3))
+            .join()
+            .unwrap();
+    }
+}
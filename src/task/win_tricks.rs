@@ -1,14 +1,18 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use crate::{
+    cnf::{CnfError, Encoder, Lit},
+    dsl,
+    locale::{Gender, Locale},
     player::n_tricks_total,
-    task::{BaseTask, TaskDifficulty, TaskStatus},
+    task::{BaseTask, TaskDifficulty, TaskProgress, TaskStatus},
 };
 
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaskWinTricks {
     difficulty: Option<TaskDifficulty>,
-    indexes: HashSet<usize>,
+    indexes: BTreeSet<usize>,
     last: bool,
     strict: bool,
 }
@@ -18,9 +22,9 @@ impl TaskWinTricks {
     where
         I: IntoIterator<Item = usize>,
     {
-        let indexes: HashSet<usize> = indexes.into_iter().collect();
+        let indexes: BTreeSet<usize> = indexes.into_iter().collect();
         assert!(
-            indexes.len() > 0 || last,
+            !indexes.is_empty() || last,
             "at least one required index should be provided"
         );
         Self {
@@ -40,7 +44,7 @@ impl BaseTask for TaskWinTricks {
             indexes.insert(n_last);
         }
 
-        let tricks_idx: HashSet<usize> = state
+        let tricks_idx: BTreeSet<usize> = state
             .get_player(ip)
             .get_tricks()
             .iter()
@@ -62,5 +66,100 @@ impl BaseTask for TaskWinTricks {
         TaskStatus::Unknown
     }
 
+    fn progress(&self, state: &crate::state::State, ip: usize) -> TaskProgress {
+        let mut indexes = self.indexes.clone();
+        if self.last {
+            indexes.insert(n_tricks_total(state.n_players()) - 1);
+        }
+        let won = state
+            .get_player(ip)
+            .get_tricks()
+            .iter()
+            .filter(|t| indexes.contains(&t.idx()))
+            .count();
+        TaskProgress::from(self.eval(state, ip)).with_fraction(won, indexes.len())
+    }
+
+    fn describe(&self, locale: Locale) -> String {
+        let indexes: Vec<usize> = self.indexes.iter().copied().collect();
+        let n = indexes.len();
+        let first_n = indexes.iter().enumerate().all(|(i, &idx)| i == idx);
+        let number = locale.number(n, Gender::Masculine);
+        let tricks = match locale {
+            Locale::English => match (n, first_n, self.last) {
+                (0, _, _) => "the last trick".to_string(),
+                (1, true, true) => "the first and the last trick".to_string(),
+                (1, true, false) => "the first trick".to_string(),
+                (_, true, last) => format!(
+                    "the first {} tricks{}",
+                    number,
+                    if last { " and the last trick" } else { "" }
+                ),
+                (_, false, last) => {
+                    let idx: Vec<String> = indexes.iter().map(|i| (i + 1).to_string()).collect();
+                    format!(
+                        "tricks {}{}",
+                        locale.and(&idx),
+                        if last { " and the last trick" } else { "" }
+                    )
+                }
+            },
+            Locale::French => match (n, first_n, self.last) {
+                (0, _, _) => "le dernier pli".to_string(),
+                (1, true, true) => "le premier et le dernier pli".to_string(),
+                (1, true, false) => "le premier pli".to_string(),
+                (_, true, last) => format!(
+                    "les {} premiers plis{}",
+                    number,
+                    if last { " et le dernier pli" } else { "" }
+                ),
+                (_, false, last) => {
+                    let idx: Vec<String> = indexes.iter().map(|i| (i + 1).to_string()).collect();
+                    format!(
+                        "les plis {}{}",
+                        locale.and(&idx),
+                        if last { " et le dernier pli" } else { "" }
+                    )
+                }
+            },
+        };
+        match (locale, self.strict) {
+            (Locale::English, true) => format!("Win only {}", tricks),
+            (Locale::English, false) => format!("Win {}", tricks),
+            (Locale::French, true) => format!("Remportez uniquement {}", tricks),
+            (Locale::French, false) => format!("Remportez {}", tricks),
+        }
+    }
+
+    fn to_dsl(&self) -> String {
+        let mut text = if self.indexes.is_empty() {
+            "win last trick".to_string()
+        } else {
+            let tricks = dsl::render_numbers(self.indexes.iter().map(|i| i + 1));
+            let last = if self.last { " and last trick" } else { "" };
+            format!("win tricks {}{}", tricks, last)
+        };
+        if self.strict {
+            text += " only";
+        }
+        dsl::render_difficulty(text, self.difficulty)
+    }
+
+    fn to_cnf(&self, encoder: &mut Encoder, ip: usize) -> Result<Lit, CnfError> {
+        let mut indexes = self.indexes.clone();
+        if self.last {
+            indexes.insert(n_tricks_total(encoder.state().n_players()) - 1);
+        }
+        let mut lits: Vec<Lit> = indexes.iter().map(|&i| encoder.trick_won(i, ip)).collect();
+        if self.strict {
+            if !encoder.game_is_over() {
+                return Ok(Lit::Const(false));
+            }
+            let others = (0..encoder.final_trick_idx()).filter(|i| !indexes.contains(i));
+            lits.extend(others.map(|i| !encoder.trick_won(i, ip)));
+        }
+        Ok(encoder.and(lits))
+    }
+
     impl_difficulty!();
 }
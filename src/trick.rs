@@ -1,12 +1,14 @@
 use std::{
     collections::HashSet,
-    fmt::Debug,
+    fmt::{Debug, Display},
     ops::{Deref, DerefMut},
+    str::FromStr,
 };
 
-use crate::card::Card;
+use crate::card::{self, Card, ParseCardError};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Trick {
     idx: usize,
     first_player: usize,
@@ -53,6 +55,41 @@ where
     }
 }
 
+/// Reads a trick written as `#<index> @<first player>: <cards>`, such as `#3 @1: P7 P2 S1`,
+/// with cards in the order they were played. The header may be omitted for the first trick
+/// opened by player 0.
+impl FromStr for Trick {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((header, cards)) = s.split_once(':') else {
+            return Ok((0, 0, card::parse_cards(s)?).into());
+        };
+        let invalid = || ParseCardError::InvalidTrickHeader(header.to_string());
+        let mut words = header.split_whitespace();
+        let (Some(idx), Some(first_player), None) = (words.next(), words.next(), words.next())
+        else {
+            return Err(invalid());
+        };
+        let idx = idx.strip_prefix('#').and_then(|i| i.parse().ok());
+        let first_player = first_player.strip_prefix('@').and_then(|i| i.parse().ok());
+        let (Some(idx), Some(first_player)) = (idx, first_player) else {
+            return Err(invalid());
+        };
+        Ok((idx, first_player, card::parse_cards(cards)?).into())
+    }
+}
+
+impl Display for Trick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} @{}:", self.idx, self.first_player)?;
+        if !self.cards.is_empty() {
+            write!(f, " ")?;
+        }
+        card::fmt_cards(&self.cards, f)
+    }
+}
+
 impl Trick {
     pub fn idx(&self) -> usize {
         self.idx
@@ -74,14 +111,14 @@ impl Trick {
     where
         F: Fn(&Card) -> bool,
     {
-        let mut best_i = 0;
+        let mut best_i: Option<usize> = None;
         for (i, c) in self.cards.iter().enumerate() {
-            if f(c) && c.val() > self.cards[best_i].val() {
-                best_i = i;
+            if f(c) && best_i.is_none_or(|b| c.val() > self.cards[b].val()) {
+                best_i = Some(i);
             }
         }
 
-        best_i
+        best_i.expect("at least one card should match")
     }
 
     fn winner_rel(&self) -> usize {
@@ -180,10 +217,39 @@ mod test {
         assert_eq!(trick.winner(), 2);
     }
 
+    #[test]
+    fn test_winner_low_submarine() {
+        let trick: Trick = (0, 0, vec![Card::Pink(9), Card::Submarine(1), Card::Pink(2)]).into();
+        assert_eq!(trick.winner(), 1);
+    }
+
     #[test]
     #[should_panic]
     fn test_winner_invalid_first_player() {
         let trick: Trick = (0, 2, vec![Card::Blue(1), Card::Blue(2)]).into();
         trick.winner();
     }
+
+    #[test]
+    fn test_notation() {
+        let trick: Trick = "#3 @1: P7 p2 sub1".parse().unwrap();
+        assert_eq!(
+            trick,
+            (3, 1, [Card::Pink(7), Card::Pink(2), Card::Submarine(1)]).into()
+        );
+        assert_eq!(trick.to_string(), "#3 @1: P7 P2 S1");
+        assert_eq!(trick.to_string().parse(), Ok(trick));
+        assert_eq!(
+            "B1 B2".parse::<Trick>(),
+            Ok((0, 0, [Card::Blue(1), Card::Blue(2)]).into())
+        );
+        assert_eq!(
+            "#3 1: P7".parse::<Trick>(),
+            Err(ParseCardError::InvalidTrickHeader("#3 1".to_string()))
+        );
+        assert_eq!(
+            "B1 B1".parse::<Trick>(),
+            Err(ParseCardError::Duplicate(Card::Blue(1)))
+        );
+    }
 }
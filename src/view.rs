@@ -0,0 +1,355 @@
+//! What a single seat knows about the game.
+//!
+//! The perfect-information [`State`] contains every hand, which a real player never sees.
+//! A [`PlayerView`] only keeps the information available to one seat: its own hand, the public
+//! tricks, tasks and communications, the number of cards left in other hands and the voids that
+//! can be inferred from players not following suit. Hidden hands can then be sampled to get back a [`State`].
+use std::collections::HashSet;
+
+use rand::{Rng, seq::SliceRandom};
+
+use crate::{
+    card::{self, Card},
+    communication::Communication,
+    hand::Hand,
+    player::Player,
+    state::State,
+    task::Task,
+    trick::Trick,
+};
+
+/// Number of random deals tried by [`PlayerView::sample`] before giving up.
+const SAMPLE_ATTEMPTS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct PlayerView {
+    seat: usize,
+    captain: usize,
+    hand: Hand,
+    hand_sizes: Vec<usize>,
+    tricks: Vec<Vec<Trick>>,
+    tasks: Vec<Vec<Task>>,
+    current_trick: Trick,
+    communications: Vec<Communication>,
+    voids: Vec<Vec<Card>>,
+}
+
+impl State {
+    /// Returns the information available to player `seat`.
+    pub fn view(&self, seat: usize) -> PlayerView {
+        let n_players = self.n_players();
+        let captain = State::retrieve_captain(self.get_players(), Some(self.get_current_trick()))
+            .expect("a state always has a captain");
+
+        // A player not following suit has no card of the leading color anymore.
+        let mut voids: Vec<Vec<Card>> = vec![Vec::new(); n_players];
+        let tricks = self
+            .get_players()
+            .iter()
+            .flat_map(|p| p.get_tricks())
+            .chain([self.get_current_trick()]);
+        for trick in tricks {
+            let Some(first_card) = trick.first() else {
+                continue;
+            };
+            for (k, c) in trick.iter().enumerate().skip(1) {
+                let ip = (trick.get_first_player() + k) % n_players;
+                if !c.same_color(first_card) && !voids[ip].iter().any(|v| v.same_color(first_card))
+                {
+                    voids[ip].push(*first_card);
+                }
+            }
+        }
+
+        PlayerView {
+            seat,
+            captain,
+            hand: self.get_player(seat).get_hand().clone(),
+            hand_sizes: self
+                .get_players()
+                .iter()
+                .map(|p| p.get_hand().len())
+                .collect(),
+            tricks: self
+                .get_players()
+                .iter()
+                .map(|p| p.get_tricks().to_vec())
+                .collect(),
+            tasks: self
+                .get_players()
+                .iter()
+                .enumerate()
+                .map(|(ip, p)| {
+                    p.get_tasks()
+                        .iter()
+                        .map(|task| match task {
+                            Task::WinNbTricks(t) if ip != seat => Task::from(t.masked()),
+                            _ => task.clone(),
+                        })
+                        .collect()
+                })
+                .collect(),
+            current_trick: self.get_current_trick().clone(),
+            communications: self.get_communications().to_vec(),
+            voids,
+        }
+    }
+}
+
+impl PlayerView {
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    pub fn n_players(&self) -> usize {
+        self.hand_sizes.len()
+    }
+
+    pub fn captain(&self) -> usize {
+        self.captain
+    }
+
+    pub fn get_hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    pub fn hand_size(&self, ip: usize) -> usize {
+        self.hand_sizes[ip]
+    }
+
+    pub fn get_tricks(&self, ip: usize) -> &[Trick] {
+        &self.tricks[ip]
+    }
+
+    pub fn get_tasks(&self, ip: usize) -> &[Task] {
+        &self.tasks[ip]
+    }
+
+    pub fn get_current_trick(&self) -> &Trick {
+        &self.current_trick
+    }
+
+    pub fn get_current_player_idx(&self) -> usize {
+        (self.current_trick.get_first_player() + self.current_trick.len()) % self.n_players()
+    }
+
+    /// Cards this seat is allowed to play right now (following suit if possible).
+    pub fn legal_cards(&self) -> Vec<Card> {
+        self.hand.playable_cards(self.current_trick.first())
+    }
+
+    pub fn get_communications(&self) -> &[Communication] {
+        &self.communications
+    }
+
+    /// Returns true if player `ip` is known to have no card of the same color as `card`.
+    pub fn is_void(&self, ip: usize, card: &Card) -> bool {
+        self.voids[ip].iter().any(|v| v.same_color(card))
+    }
+
+    /// Cards which are neither in this seat's hand nor already played.
+    pub fn unknown_cards(&self) -> Vec<Card> {
+        let mut known: HashSet<Card> = self.hand.iter().copied().collect();
+        known.extend(self.tricks.iter().flatten().flat_map(|t| t.iter()));
+        known.extend(self.current_trick.iter());
+        card::deck()
+            .into_iter()
+            .filter(|c| !known.contains(c))
+            .collect()
+    }
+
+    /// Returns true if player `ip` may hold `card`, given the inferred voids and communications.
+    fn may_hold(&self, ip: usize, card: &Card) -> bool {
+        !self.is_void(ip, card)
+            && self
+                .communications
+                .iter()
+                .filter(|comm| comm.seat == ip)
+                .all(|comm| comm.allows_other(card))
+    }
+
+    /// Returns the player known to hold `card`, if any: the captain holds the 4 submarine until
+    /// it is played, and a communicated card stays in the hand of its owner.
+    fn known_owner(&self, card: &Card) -> Option<usize> {
+        if *card == Card::Submarine(4) {
+            return Some(self.captain);
+        }
+        self.communications
+            .iter()
+            .find(|comm| comm.card == *card)
+            .map(|comm| comm.seat)
+    }
+
+    /// Deals the unknown cards to the other players, consistently with the hand sizes, the
+    /// inferred voids, the captain and the communications. Unknown predictions of the number of
+    /// tricks to win are drawn among the values which are still reachable.
+    /// Returns `None` if no consistent deal was found.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Option<State> {
+        let n_players = self.n_players();
+        let unknown = self.unknown_cards();
+
+        // Cards not dealt to anyone (when playing with a reduced deck) go to an extra pseudo-seat.
+        let out_of_game = n_players;
+        let mut capacity: Vec<usize> = (0..n_players)
+            .map(|ip| {
+                if ip == self.seat {
+                    0
+                } else {
+                    self.hand_sizes[ip]
+                }
+            })
+            .collect();
+        let total: usize = capacity.iter().sum();
+        capacity.push(unknown.len().checked_sub(total)?);
+
+        let n_tricks_left =
+            (self.hand_sizes.iter().sum::<usize>() + self.current_trick.len()) / n_players;
+
+        'attempt: for _ in 0..SAMPLE_ATTEMPTS {
+            let mut left = capacity.clone();
+            let mut hands: Vec<Vec<Card>> = vec![Vec::new(); n_players + 1];
+            let mut cards = unknown.clone();
+            cards.shuffle(rng);
+
+            for c in cards {
+                let eligible: Vec<usize> = match self.known_owner(&c) {
+                    Some(ip) => vec![ip],
+                    None => (0..=n_players)
+                        .filter(|&ip| ip == out_of_game || self.may_hold(ip, &c))
+                        .collect(),
+                };
+                // Picking a seat with a probability proportional to its remaining capacity.
+                let n_slots: usize = eligible.iter().map(|&ip| left[ip]).sum();
+                if n_slots == 0 {
+                    continue 'attempt;
+                }
+                let mut slot = rng.random_range(0..n_slots);
+                for ip in eligible {
+                    if slot < left[ip] {
+                        left[ip] -= 1;
+                        hands[ip].push(c);
+                        break;
+                    }
+                    slot -= left[ip];
+                }
+            }
+
+            let players = (0..n_players)
+                .map(|ip| {
+                    let hand = if ip == self.seat {
+                        self.hand.clone()
+                    } else {
+                        std::mem::take(&mut hands[ip]).into()
+                    };
+                    let mut p = Player::new(hand);
+                    for trick in &self.tricks[ip] {
+                        p.add_trick(trick.clone())
+                            .expect("tricks come from a valid state");
+                    }
+                    for task in &self.tasks[ip] {
+                        let mut task = task.clone();
+                        if let Task::WinNbTricks(t) = &mut task
+                            && t.prediction().is_none()
+                        {
+                            let won = self.tricks[ip].len();
+                            t.predict(rng.random_range(won..=won + n_tricks_left));
+                        }
+                        p.add_task(task).expect("tasks come from a valid state");
+                    }
+                    p
+                })
+                .collect();
+            return Some(State::from_parts(
+                players,
+                self.current_trick.clone(),
+                self.communications.clone(),
+            ));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::communication::CommunicationKind;
+
+    use super::*;
+
+    fn state() -> State {
+        let mut p0 = Player::new([Card::Submarine(4), Card::Blue(5), Card::Pink(2)].into());
+        p0.add_trick((0, 0, [Card::Blue(9), Card::Blue(1), Card::Green(3)]).into())
+            .unwrap();
+        let p1 = Player::new([Card::Pink(1), Card::Blue(2), Card::Yellow(3)].into());
+        let p2 = Player::new([Card::Green(2), Card::Yellow(2), Card::Pink(9)].into());
+        let mut s = State::new([p0, p1, p2]);
+        s.get_mut_current_trick().incr();
+        s
+    }
+
+    #[test]
+    fn test_voids() {
+        let view = state().view(1);
+        assert!(view.is_void(2, &Card::Blue(4)));
+        assert!(!view.is_void(2, &Card::Green(4)));
+        assert!(!view.is_void(0, &Card::Blue(4)));
+        assert!(!view.is_void(1, &Card::Blue(4)));
+    }
+
+    #[test]
+    fn test_unknown_cards() {
+        let view = state().view(1);
+        let unknown = view.unknown_cards();
+        assert_eq!(unknown.len(), 40 - 3 - 3);
+        assert!(!unknown.contains(&Card::Blue(9)));
+        assert!(!unknown.contains(&Card::Pink(1)));
+        assert!(unknown.contains(&Card::Pink(9)));
+    }
+
+    #[test]
+    fn test_sample() {
+        let view = state().view(1);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let sample = view.sample(&mut rng).unwrap();
+            assert_eq!(sample.get_player(1).get_hand(), view.get_hand());
+            assert_eq!(sample.get_player(0).get_hand().len(), 3);
+            assert_eq!(sample.get_player(2).get_hand().len(), 3);
+            assert!(sample.get_player(0).is_captain(None));
+            assert!(
+                !sample
+                    .get_player(2)
+                    .get_hand()
+                    .iter()
+                    .any(|c| c.same_color(&Card::Blue(1)))
+            );
+            assert_eq!(sample.get_current_trick(), view.get_current_trick());
+        }
+    }
+
+    #[test]
+    fn test_sample_with_communication() {
+        let mut s = state();
+        s.communicate(Communication {
+            seat: 2,
+            card: Card::Yellow(2),
+            kind: CommunicationKind::Only,
+        })
+        .unwrap();
+        let view = s.view(1);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let sample = view.sample(&mut rng).unwrap();
+            let hand = sample.get_player(2).get_hand();
+            assert!(hand.contains(&Card::Yellow(2)));
+            assert_eq!(
+                hand.iter()
+                    .filter(|c| c.same_color(&Card::Yellow(1)))
+                    .count(),
+                1
+            );
+        }
+    }
+}